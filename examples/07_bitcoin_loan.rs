@@ -67,21 +67,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  new ltv: {:.1}%", ltv.as_decimal() * dec!(100));
     println!("  status: {:?}", status);
     
-    match status {
-        LtvStatus::MarginCall => {
-            println!("\n⚠️  margin call triggered!");
-            println!("  options:");
-            println!("    1. add more btc collateral");
-            println!("    2. pay down principal");
-            println!("    3. pay off loan to retrieve btc");
-            
-            // option 1: add collateral
-            println!("\n  choosing option 1: add 1 btc");
-            loan.add_collateral(dec!(1))?;
-            println!("  new collateral: 4 btc");
-            println!("  new ltv: {:.1}%", loan.calculate_ltv().as_decimal() * dec!(100));
-        }
-        _ => {}
+    if status == LtvStatus::MarginCall {
+        println!("\n⚠️  margin call triggered!");
+        println!("  options:");
+        println!("    1. add more btc collateral");
+        println!("    2. pay down principal");
+        println!("    3. pay off loan to retrieve btc");
+        
+        // option 1: add collateral
+        println!("\n  choosing option 1: add 1 btc");
+        loan.add_collateral(dec!(1))?;
+        println!("  new collateral: 4 btc");
+        println!("  new ltv: {:.1}%", loan.calculate_ltv().as_decimal() * dec!(100));
     }
     
     // advance 1 year
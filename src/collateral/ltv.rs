@@ -143,7 +143,7 @@ impl LtvMonitor {
         
         // check if it's time to check
         if now - self.last_check < self.check_frequency {
-            return Ok(self.get_current_status(cvl, collateral_value)?);
+            return self.get_current_status(cvl, collateral_value);
         }
         
         self.last_check = now;
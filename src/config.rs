@@ -4,9 +4,11 @@ use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 
 use crate::decimal::{Money, Rate};
-use crate::interest::{CompoundingFrequency, DayCountConvention, PenaltyConfig};
-use crate::payments::PartialPaymentStrategy;
-use crate::types::{AmortizationMethod, LtvThresholds, OpenTermType, OverpaymentStrategy, PaymentSchedule, RevolvingType, TermLoanType};
+use crate::errors::{FacilityError, Result};
+use crate::interest::{CompoundingFrequency, DayCountConvention, InterestMethod, PenaltyConfig};
+use crate::payments::{PartialPaymentStrategy, PaymentWaterfall};
+use crate::state::TransactionUsage;
+use crate::types::{AmortizationMethod, DelinquencyBucket, LtvThresholds, OpenTermType, OverpaymentStrategy, PaymentSchedule, RevolvingType, TermLoanType};
 
 /// facility configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +18,7 @@ pub struct FacilityConfig {
     pub interest_config: InterestConfig,
     pub payment_config: PaymentConfig,
     pub fee_config: FeeConfig,
+    pub delinquency_policy: DelinquencyPolicy,
     pub collateral_config: Option<CollateralConfig>,
     pub limits: FacilityLimits,
 }
@@ -41,6 +44,8 @@ pub struct FinancialTerms {
     pub maturity_date: Option<DateTime<Utc>>,
     pub amortization_method: AmortizationMethod,
     pub balloon_payment: Option<Money>,
+    /// draw period for lines that convert to amortizing repayment
+    pub draw_period_months: Option<u32>,
 }
 
 /// interest configuration
@@ -48,6 +53,8 @@ pub struct FinancialTerms {
 pub struct InterestConfig {
     pub day_count_convention: DayCountConvention,
     pub compounding_frequency: CompoundingFrequency,
+    pub interest_method: InterestMethod,
+    pub penalty_apr: Option<PenaltyAprPolicy>,
     pub penalty_config: Option<PenaltyConfig>,
     pub grace_period_days: u32,
     pub default_rate: Option<Rate>,
@@ -56,6 +63,16 @@ pub struct InterestConfig {
     pub margin: Option<Rate>,
 }
 
+/// penalty apr triggered by delinquency, charged at `default_rate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PenaltyAprPolicy {
+    pub trigger_days_past_due: u32,
+    /// reprice existing balances as well as new transactions
+    pub apply_to_existing_balances: bool,
+    /// consecutive on-time minimum payments needed to cure
+    pub cure_after_on_time_payments: u32,
+}
+
 /// payment configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaymentConfig {
@@ -65,6 +82,8 @@ pub struct PaymentConfig {
     pub overpayment_allowed: bool,
     pub overpayment_strategy: OverpaymentStrategy,
     pub partial_payment_strategy: PartialPaymentStrategy,
+    /// order payments are applied to balances
+    pub waterfall: PaymentWaterfall,
     pub autopay_enabled: bool,
 }
 
@@ -74,11 +93,181 @@ pub struct FeeConfig {
     pub origination_fee: Option<Money>,
     pub origination_fee_percentage: Option<Decimal>,
     pub late_fee: Option<Money>,
+    /// percentage of the late installment
     pub late_fee_percentage: Option<Decimal>,
+    /// how `late_fee` and `late_fee_percentage` combine
+    pub late_fee_method: LateFeeMethod,
+    /// statutory maximum for a single late fee
+    pub late_fee_cap: Option<Money>,
     pub overlimit_fee: Option<Money>,
     pub annual_fee: Option<Money>,
+    pub annual_fee_policy: Option<AnnualFeePolicy>,
     pub prepayment_penalty: Option<PrepaymentPenalty>,
     pub commitment_fee_rate: Option<Rate>,
+    pub commitment_fee_schedule: Option<CommitmentFeeSchedule>,
+    pub cash_advance_fee: Option<TransactionFee>,
+    pub balance_transfer_fee: Option<TransactionFee>,
+    pub foreign_transaction_fee: Option<TransactionFee>,
+    pub overdraft_fees: Option<OverdraftFeeSchedule>,
+}
+
+/// how a late fee is derived from the flat and percentage amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LateFeeMethod {
+    #[default]
+    Flat,
+    Percentage,
+    GreaterOf,
+    LesserOf,
+}
+
+impl FeeConfig {
+    /// late fee for a late installment (or minimum payment) of `amount_due`
+    pub fn late_fee_for(&self, amount_due: Money) -> Option<Money> {
+        let flat = self.late_fee;
+        let percentage = self.late_fee_percentage.map(|pct| amount_due.percentage(pct));
+
+        let fee = match self.late_fee_method {
+            LateFeeMethod::Flat => flat,
+            LateFeeMethod::Percentage => percentage,
+            LateFeeMethod::GreaterOf => flat.max(percentage),
+            LateFeeMethod::LesserOf => match (flat, percentage) {
+                (Some(flat), Some(percentage)) => Some(flat.min(percentage)),
+                (flat, percentage) => flat.or(percentage),
+            },
+        }?;
+
+        let fee = match self.late_fee_cap {
+            Some(cap) => fee.min(cap),
+            None => fee,
+        };
+        Some(fee.round_dp(2)).filter(|fee| *fee > Money::ZERO)
+    }
+}
+
+/// billing frequency for periodic fees
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FeeBillingFrequency {
+    Monthly,
+    Quarterly,
+    #[default]
+    Annual,
+}
+
+impl FeeBillingFrequency {
+    /// months in one billing period
+    pub fn months(&self) -> u32 {
+        match self {
+            FeeBillingFrequency::Monthly => 1,
+            FeeBillingFrequency::Quarterly => 3,
+            FeeBillingFrequency::Annual => 12,
+        }
+    }
+}
+
+/// anniversary billing terms for the annual fee
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnnualFeePolicy {
+    pub billing_frequency: FeeBillingFrequency,
+    pub waive_first_year: bool,
+    /// refund a prorated share if the account closes within this many days of assessment
+    pub refund_window_days: Option<u32>,
+}
+
+/// billing terms for commitment fees accrued daily on the undrawn line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitmentFeeSchedule {
+    /// fees are billed in arrears at the end of each period
+    pub billing_frequency: FeeBillingFrequency,
+    /// utilization-based rates, replacing `commitment_fee_rate` when not empty
+    pub utilization_grid: Vec<UtilizationFeeTier>,
+}
+
+impl Default for CommitmentFeeSchedule {
+    fn default() -> Self {
+        Self {
+            billing_frequency: FeeBillingFrequency::Monthly,
+            utilization_grid: Vec::new(),
+        }
+    }
+}
+
+/// commitment fee rate applying up to a utilization level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtilizationFeeTier {
+    pub max_utilization: Rate,
+    pub rate: Rate,
+}
+
+/// per-transaction fee charged as the greater of a flat amount or a percentage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionFee {
+    pub flat_amount: Money,
+    pub percentage: Decimal,
+    pub cap: Option<Money>,
+}
+
+impl TransactionFee {
+    /// calculate fee for a transaction amount
+    pub fn calculate(&self, amount: Money) -> Money {
+        let fee = self.flat_amount.max(amount.percentage(self.percentage));
+        match self.cap {
+            Some(cap) => fee.min(cap),
+            None => fee,
+        }
+    }
+}
+
+/// fees and interest-free allowance for an overdraft line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverdraftFeeSchedule {
+    /// charged for each debit paid into the overdraft
+    pub paid_item_fee: Option<Money>,
+    /// charged for each debit returned because it would breach the limit
+    pub returned_item_fee: Option<Money>,
+    /// charged once per day while overdrawn past the fee-free buffer zone
+    pub daily_fee: Option<Money>,
+    /// charged once per overdraft after it has run for `sustained_after_days`
+    pub sustained_fee: Option<Money>,
+    pub sustained_after_days: u32,
+    /// most overdraft fees charged in one calendar day
+    pub daily_fee_cap: Option<Money>,
+    /// most overdraft fees charged in one calendar month
+    pub monthly_fee_cap: Option<Money>,
+    /// items at or below this amount are paid without an item fee
+    pub de_minimis: Money,
+    /// overdrawn amount on which no interest accrues
+    pub interest_free_buffer: Money,
+}
+
+impl Default for OverdraftFeeSchedule {
+    fn default() -> Self {
+        Self {
+            paid_item_fee: None,
+            returned_item_fee: None,
+            daily_fee: Some(Money::from_major(5)),
+            sustained_fee: None,
+            sustained_after_days: 0,
+            daily_fee_cap: None,
+            monthly_fee_cap: None,
+            de_minimis: Money::ZERO,
+            interest_free_buffer: Money::ZERO,
+        }
+    }
+}
+
+impl OverdraftFeeSchedule {
+    /// trim a fee so the daily and monthly caps are not exceeded
+    pub fn capped_fee(&self, fee: Money, usage: &TransactionUsage, now: DateTime<Utc>) -> Money {
+        let mut allowed = fee;
+        if let Some(cap) = self.daily_fee_cap {
+            allowed = allowed.min(cap - cap.min(usage.daily_total(now)));
+        }
+        if let Some(cap) = self.monthly_fee_cap {
+            allowed = allowed.min(cap - cap.min(usage.monthly_total(now)));
+        }
+        allowed
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -86,6 +275,10 @@ pub struct PrepaymentPenalty {
     pub penalty_percentage: Decimal,
     pub penalty_months: u32,
     pub step_down: Vec<StepDown>,
+    /// how the penalty is measured inside the penalty window
+    pub method: PrepaymentPenaltyMethod,
+    /// percentage of the original principal that may be prepaid each loan year without penalty
+    pub free_allowance_percentage: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -94,6 +287,99 @@ pub struct StepDown {
     pub penalty_percentage: Decimal,
 }
 
+/// prepayment penalty calculation method
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PrepaymentPenaltyMethod {
+    /// flat percentage of the prepaid amount, following the step-down tiers
+    Percentage,
+    /// lender's lost interest margin over the treasury rate for the remaining term
+    YieldMaintenance { treasury_rate: Rate },
+    /// cost of replacing the remaining payments at the discount rate (defeasance-style)
+    MakeWhole { discount_rate: Rate },
+}
+
+impl PrepaymentPenalty {
+    /// percentage tier that applies after the given number of months, none outside the window
+    pub fn percentage_at(&self, months_since_origination: u32) -> Option<Decimal> {
+        if months_since_origination >= self.penalty_months {
+            return None;
+        }
+        let percentage = self.step_down.iter()
+            .filter(|step| months_since_origination >= step.after_months)
+            .max_by_key(|step| step.after_months)
+            .map(|step| step.penalty_percentage)
+            .unwrap_or(self.penalty_percentage);
+        Some(percentage)
+    }
+
+    /// amount of the original principal that can be prepaid each loan year without penalty
+    pub fn yearly_allowance(&self, original_principal: Money) -> Money {
+        self.free_allowance_percentage
+            .map(|pct| original_principal.percentage(pct))
+            .unwrap_or(Money::ZERO)
+    }
+
+    /// penalty for prepaying `prepaid` out of `outstanding` principal
+    ///
+    /// `remaining_payments` are the scheduled payments still to come, used to price
+    /// yield maintenance and make-whole penalties.
+    pub fn calculate(
+        &self,
+        prepaid: Money,
+        outstanding: Money,
+        months_since_origination: u32,
+        note_rate: Rate,
+        remaining_payments: &[Money],
+    ) -> Money {
+        if prepaid <= Money::ZERO {
+            return Money::ZERO;
+        }
+        let percentage = match self.percentage_at(months_since_origination) {
+            Some(percentage) => percentage,
+            None => return Money::ZERO,
+        };
+
+        let penalty = match self.method {
+            PrepaymentPenaltyMethod::Percentage => prepaid.percentage(percentage),
+            PrepaymentPenaltyMethod::YieldMaintenance { treasury_rate } => {
+                let spread = note_rate.as_decimal() - treasury_rate.as_decimal();
+                if spread <= Decimal::ZERO {
+                    return Money::ZERO;
+                }
+                let monthly = treasury_rate.as_decimal() / dec!(12);
+                let months = Decimal::from(remaining_payments.len() as u64);
+                let annuity = if monthly.is_zero() {
+                    months
+                } else {
+                    let mut discount = Decimal::ONE;
+                    for _ in 0..remaining_payments.len() {
+                        discount /= Decimal::ONE + monthly;
+                    }
+                    (Decimal::ONE - discount) / monthly
+                };
+                Money::from_decimal(prepaid.as_decimal() * spread / dec!(12) * annuity)
+            }
+            PrepaymentPenaltyMethod::MakeWhole { discount_rate } => {
+                if outstanding <= Money::ZERO {
+                    return Money::ZERO;
+                }
+                let monthly = discount_rate.as_decimal() / dec!(12);
+                let mut discount = Decimal::ONE;
+                let mut present_value = Decimal::ZERO;
+                for payment in remaining_payments {
+                    discount /= Decimal::ONE + monthly;
+                    present_value += payment.as_decimal() * discount;
+                }
+                let share = prepaid.min(outstanding).as_decimal() / outstanding.as_decimal();
+                Money::from_decimal(present_value * share - prepaid.as_decimal())
+                    .max(Money::ZERO)
+            }
+        };
+
+        penalty.round_dp(2)
+    }
+}
+
 /// collateral configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CollateralConfig {
@@ -116,6 +402,322 @@ pub struct FacilityLimits {
     pub overdraft_limit: Option<Money>,
     pub daily_transaction_limit: Option<Money>,
     pub monthly_transaction_limit: Option<Money>,
+    pub credit_line_review: Option<CreditLineReviewPolicy>,
+    pub overlimit_policy: OverlimitPolicy,
+    pub overdraft_decisioning: Option<OverdraftDecisionPolicy>,
+    pub overdraft_review: Option<OverdraftReviewPolicy>,
+}
+
+/// how draws over the credit limit are authorized and charged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlimitPolicy {
+    /// share of the credit limit a draw may exceed it by
+    pub tolerance: Decimal,
+    /// decline overlimit draws unless the customer has opted in
+    pub require_opt_in: bool,
+    pub fee_frequency: OverlimitFeeFrequency,
+    /// consecutive cycles an overlimit fee may be charged while the balance stays over
+    pub max_fee_cycles: Option<u32>,
+    pub limit_decrease: LimitDecreaseHandling,
+}
+
+impl OverlimitPolicy {
+    /// up to 10% over with a fee on every overlimit draw, no opt-in
+    pub fn tolerant() -> Self {
+        Self {
+            tolerance: dec!(0.10),
+            require_opt_in: false,
+            fee_frequency: OverlimitFeeFrequency::PerTransaction,
+            max_fee_cycles: None,
+            limit_decrease: LimitDecreaseHandling::AllowOverlimit,
+        }
+    }
+    
+    /// opt-in overlimit with at most one fee per cycle for three consecutive cycles
+    pub fn opt_in() -> Self {
+        Self {
+            tolerance: dec!(0.10),
+            require_opt_in: true,
+            fee_frequency: OverlimitFeeFrequency::OncePerCycle,
+            max_fee_cycles: Some(3),
+            limit_decrease: LimitDecreaseHandling::FloorAtBalance,
+        }
+    }
+    
+    /// no overlimit draws and no decreases below the balance
+    pub fn strict() -> Self {
+        Self {
+            tolerance: Decimal::ZERO,
+            require_opt_in: false,
+            fee_frequency: OverlimitFeeFrequency::OncePerCycle,
+            max_fee_cycles: None,
+            limit_decrease: LimitDecreaseHandling::Reject,
+        }
+    }
+}
+
+impl Default for OverlimitPolicy {
+    fn default() -> Self {
+        Self::tolerant()
+    }
+}
+
+/// how often the overlimit fee may be charged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverlimitFeeFrequency {
+    PerTransaction,
+    OncePerCycle,
+}
+
+/// handling of a credit limit decrease below the current balance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitDecreaseHandling {
+    /// apply the lower limit, leaving the account overlimit
+    AllowOverlimit,
+    /// lower the limit no further than the current balance
+    FloorAtBalance,
+    /// reject the change
+    Reject,
+}
+
+/// how debits that would overdraw a linked account are paid or returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverdraftDecisionPolicy {
+    /// return overdrawing items unless the customer has opted in
+    pub require_opt_in: bool,
+    /// amount past the overdraft limit an item may still be paid into
+    pub overlimit_tolerance: Money,
+    /// largest single item paid into the overdraft
+    pub max_paid_item: Option<Money>,
+    /// suspend after this many excess items within `excess_window_days`
+    pub suspend_after_excess_items: Option<u32>,
+    pub excess_window_days: u32,
+    /// suspend once the account has been overdrawn this many days in a row
+    pub suspend_after_negative_days: Option<u32>,
+}
+
+/// rules for reviewing an arranged overdraft at the end of its term
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverdraftReviewPolicy {
+    /// months an arranged limit runs before it must be reviewed
+    pub term_months: u32,
+    /// days overdrawn since the last review at which the limit is reduced
+    pub reduce_after_days_overdrawn: u32,
+    /// days overdrawn since the last review at which the limit is withdrawn
+    pub withdraw_after_days_overdrawn: u32,
+    /// average overdrawn share of the limit at which the limit is reduced
+    pub reduce_average_usage: Decimal,
+    /// deposits needed since the last review to keep the limit
+    pub min_deposit_inflows: Money,
+    pub reduce_percentage: Decimal,
+    /// a reduction below this withdraws the limit instead
+    pub minimum_limit: Money,
+    /// days of notice before a withdrawal takes effect
+    pub withdrawal_notice_days: u32,
+    /// term of the repayment plan for a balance left when the limit is withdrawn
+    pub repayment_months: u32,
+}
+
+impl Default for OverdraftReviewPolicy {
+    fn default() -> Self {
+        Self {
+            term_months: 12,
+            reduce_after_days_overdrawn: 180,
+            withdraw_after_days_overdrawn: 300,
+            reduce_average_usage: dec!(0.75),
+            min_deposit_inflows: Money::from_major(1_000),
+            reduce_percentage: dec!(0.25),
+            minimum_limit: Money::from_major(100),
+            withdrawal_notice_days: 30,
+            repayment_months: 12,
+        }
+    }
+}
+
+impl Default for OverdraftDecisionPolicy {
+    fn default() -> Self {
+        Self {
+            require_opt_in: false,
+            overlimit_tolerance: Money::ZERO,
+            max_paid_item: None,
+            suspend_after_excess_items: None,
+            excess_window_days: 30,
+            suspend_after_negative_days: None,
+        }
+    }
+}
+
+impl FacilityLimits {
+    /// check a drawdown against the per-drawdown limits and calendar day and month usage
+    pub fn check_drawdown(&self, amount: Money, usage: &TransactionUsage, now: DateTime<Utc>) -> Result<()> {
+        if let Some(minimum) = self.minimum_drawdown {
+            if amount < minimum {
+                return Err(FacilityError::BelowMinimumDrawdown {
+                    minimum,
+                    requested: amount,
+                });
+            }
+        }
+        
+        if let Some(maximum) = self.maximum_drawdown {
+            if amount > maximum {
+                return Err(FacilityError::AboveMaximumDrawdown {
+                    maximum,
+                    requested: amount,
+                });
+            }
+        }
+        
+        if let Some(limit) = self.daily_transaction_limit {
+            let remaining = (limit - usage.daily_total(now)).max(Money::ZERO);
+            if amount > remaining {
+                return Err(FacilityError::DailyTransactionLimitExceeded {
+                    limit,
+                    remaining,
+                    requested: amount,
+                });
+            }
+        }
+        
+        if let Some(limit) = self.monthly_transaction_limit {
+            let remaining = (limit - usage.monthly_total(now)).max(Money::ZERO);
+            if amount > remaining {
+                return Err(FacilityError::MonthlyTransactionLimitExceeded {
+                    limit,
+                    remaining,
+                    requested: amount,
+                });
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// check a payment against the payment limits, always accepting one that clears the balance
+    pub fn check_payment(&self, amount: Money, outstanding: Money) -> Result<()> {
+        if let Some(minimum) = self.minimum_payment {
+            let minimum = minimum.min(outstanding);
+            if amount < minimum {
+                return Err(FacilityError::PaymentBelowMinimum {
+                    minimum,
+                    provided: amount,
+                });
+            }
+        }
+        
+        if let Some(maximum) = self.maximum_payment {
+            if amount > maximum {
+                return Err(FacilityError::PaymentAboveMaximum {
+                    maximum,
+                    provided: amount,
+                });
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// rules for periodic credit line reviews on revolving facilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditLineReviewPolicy {
+    pub review_frequency_months: u32,
+    /// history window considered at each review
+    pub lookback_months: u32,
+    /// consecutive on-time minimum payments needed for an increase
+    pub min_on_time_payments: u32,
+    /// average utilization over the lookback needed for an increase
+    pub increase_utilization_threshold: Rate,
+    /// average utilization at or below which an unused line is reduced, unless use is picking up
+    pub low_utilization_threshold: Option<Rate>,
+    /// rise in utilization from the earlier to the later half of the lookback above which
+    /// the line is reduced rather than increased
+    pub max_utilization_rise: Option<Rate>,
+    /// delinquencies tolerated in the lookback before a decrease
+    pub max_delinquencies: u32,
+    /// overlimit occurrences tolerated in the lookback before a decrease
+    pub max_overlimit_occurrences: u32,
+    pub increase_percentage: Decimal,
+    pub decrease_percentage: Decimal,
+    pub minimum_limit: Money,
+    pub maximum_limit: Money,
+    /// days of notice before a decrease takes effect
+    pub decrease_notice_days: u32,
+    /// apply decisions directly instead of holding them as proposals
+    pub auto_apply: bool,
+}
+
+impl Default for CreditLineReviewPolicy {
+    fn default() -> Self {
+        Self {
+            review_frequency_months: 6,
+            lookback_months: 6,
+            min_on_time_payments: 6,
+            increase_utilization_threshold: Rate::from_percentage(30),
+            low_utilization_threshold: None,
+            max_utilization_rise: Some(Rate::from_percentage(25)),
+            max_delinquencies: 0,
+            max_overlimit_occurrences: 1,
+            increase_percentage: dec!(0.20),
+            decrease_percentage: dec!(0.25),
+            minimum_limit: Money::from_major(500),
+            maximum_limit: Money::from_major(50_000),
+            decrease_notice_days: 45,
+            auto_apply: true,
+        }
+    }
+}
+
+/// days past due at which a delinquent facility escalates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelinquencyPolicy {
+    /// days past due at which each bucket of `DelinquencyBucket::PAST_DUE` starts
+    pub bucket_starts: [u32; 5],
+    /// move to `Default` status
+    pub default_after_days: Option<u32>,
+    /// stop accruing interest until arrears are cleared
+    pub non_accrual_after_days: Option<u32>,
+    /// write the outstanding balance off as a loss
+    pub charge_off_after_days: Option<u32>,
+}
+
+impl DelinquencyPolicy {
+    /// closed-end retail credit, charged off at 120 days
+    pub fn closed_end() -> Self {
+        Self {
+            bucket_starts: [1, 30, 60, 90, 120],
+            default_after_days: Some(90),
+            non_accrual_after_days: Some(90),
+            charge_off_after_days: Some(120),
+        }
+    }
+
+    /// open-end and real estate secured credit, charged off at 180 days
+    pub fn open_end() -> Self {
+        Self {
+            charge_off_after_days: Some(180),
+            ..Self::closed_end()
+        }
+    }
+
+    /// bucket for a days past due count
+    pub fn bucket_for(&self, days_past_due: u32) -> DelinquencyBucket {
+        if days_past_due == 0 {
+            return DelinquencyBucket::Current;
+        }
+        self.bucket_starts
+            .iter()
+            .zip(DelinquencyBucket::PAST_DUE)
+            .rev()
+            .find(|(start, _)| days_past_due >= **start)
+            .map_or(DelinquencyBucket::Current, |(_, bucket)| bucket)
+    }
+}
+
+impl Default for DelinquencyPolicy {
+    fn default() -> Self {
+        Self::closed_end()
+    }
 }
 
 impl FacilityConfig {
@@ -141,10 +743,13 @@ impl FacilityConfig {
                 maturity_date: Some(origination_date + chrono::Duration::days((term_months * 30) as i64)),
                 amortization_method: AmortizationMethod::EqualInstallments,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual360,
                 compounding_frequency: CompoundingFrequency::Monthly,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(1.5))),
                 grace_period_days: 15,
                 default_rate: Some(Rate::from_decimal(rate.as_decimal() * dec!(1.5))),
@@ -159,6 +764,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::HoldInSuspense,
+                waterfall: PaymentWaterfall::mortgage(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -166,8 +772,11 @@ impl FacilityConfig {
                 origination_fee_percentage: Some(dec!(1.0)),
                 late_fee: Some(Money::from_major(50)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: if term_months > 60 {
                     Some(PrepaymentPenalty {
                         penalty_percentage: dec!(2.0),
@@ -176,12 +785,20 @@ impl FacilityConfig {
                             StepDown { after_months: 12, penalty_percentage: dec!(1.0) },
                             StepDown { after_months: 24, penalty_percentage: dec!(0) },
                         ],
+                        method: PrepaymentPenaltyMethod::Percentage,
+                        free_allowance_percentage: None,
                     })
                 } else {
                     None
                 },
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::open_end(),
             collateral_config: Some(CollateralConfig {
                 collateral_type: "real_estate".to_string(),
                 initial_value: property_value,
@@ -204,6 +821,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -226,10 +847,13 @@ impl FacilityConfig {
                 maturity_date: Some(origination_date + chrono::Duration::days((term_months * 30) as i64)),
                 amortization_method: AmortizationMethod::EqualInstallments,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Monthly,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(2.0))),
                 grace_period_days: 10,
                 default_rate: Some(Rate::from_decimal(rate.as_decimal() * dec!(2.0))),
@@ -244,6 +868,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReduceTerm,
                 partial_payment_strategy: PartialPaymentStrategy::ApplyImmediately,
+                waterfall: PaymentWaterfall::installment(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -251,11 +876,20 @@ impl FacilityConfig {
                 origination_fee_percentage: Some(dec!(3.0)),
                 late_fee: Some(Money::from_major(35)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::closed_end(),
             collateral_config: None,
             limits: FacilityLimits {
                 minimum_drawdown: Some(Money::from_major(1000)),
@@ -266,6 +900,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -297,10 +935,13 @@ impl FacilityConfig {
                     AmortizationMethod::EqualInstallments
                 },
                 balloon_payment,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Monthly,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(1.5))),
                 grace_period_days: 10,
                 default_rate: Some(Rate::from_decimal(rate.as_decimal() * dec!(1.5))),
@@ -315,6 +956,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::HoldInSuspense,
+                waterfall: PaymentWaterfall::installment(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -322,11 +964,20 @@ impl FacilityConfig {
                 origination_fee_percentage: None,
                 late_fee: Some(Money::from_major(25)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::closed_end(),
             collateral_config: Some(CollateralConfig {
                 collateral_type: "vehicle".to_string(),
                 initial_value: vehicle_value,
@@ -349,6 +1000,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -374,10 +1029,13 @@ impl FacilityConfig {
                 maturity_date: None, // no maturity
                 amortization_method: AmortizationMethod::InterestOnly,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Daily,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: None, // no penalties for open-term
                 grace_period_days: 0, // no grace period needed
                 default_rate: None, // ltv-based liquidation instead
@@ -392,6 +1050,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::ApplyImmediately,
+                waterfall: PaymentWaterfall::standard(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -399,10 +1058,25 @@ impl FacilityConfig {
                 origination_fee_percentage: Some(dec!(1.0)),
                 late_fee: None, // no late fees for open-term
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
+            },
+            delinquency_policy: DelinquencyPolicy {
+                default_after_days: Some(60),
+                non_accrual_after_days: Some(90),
+                // recovered through collateral liquidation instead
+                charge_off_after_days: None,
+                ..DelinquencyPolicy::closed_end()
             },
             collateral_config: Some(CollateralConfig {
                 collateral_type: "BTC".to_string(),
@@ -426,6 +1100,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -447,10 +1125,17 @@ impl FacilityConfig {
                 maturity_date: None,
                 amortization_method: AmortizationMethod::InterestOnly,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Daily,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: Some(PenaltyAprPolicy {
+                    trigger_days_past_due: 60,
+                    apply_to_existing_balances: false,
+                    cure_after_on_time_payments: 6,
+                }),
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(1.5))),
                 grace_period_days: 5,
                 default_rate: Some(Rate::from_percentage(29)),
@@ -465,6 +1150,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::ApplyImmediately,
+                waterfall: PaymentWaterfall::standard(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -472,11 +1158,36 @@ impl FacilityConfig {
                 origination_fee_percentage: None,
                 late_fee: Some(Money::from_major(39)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: Some(Money::from_major(35)),
                 annual_fee: Some(Money::from_major(95)),
+                annual_fee_policy: Some(AnnualFeePolicy {
+                    billing_frequency: FeeBillingFrequency::Annual,
+                    waive_first_year: false,
+                    refund_window_days: Some(30),
+                }),
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: Some(TransactionFee {
+                    flat_amount: Money::from_major(10),
+                    percentage: dec!(5.0),
+                    cap: None,
+                }),
+                balance_transfer_fee: Some(TransactionFee {
+                    flat_amount: Money::from_major(5),
+                    percentage: dec!(3.0),
+                    cap: None,
+                }),
+                foreign_transaction_fee: Some(TransactionFee {
+                    flat_amount: Money::ZERO,
+                    percentage: dec!(3.0),
+                    cap: None,
+                }),
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::open_end(),
             collateral_config: None,
             limits: FacilityLimits {
                 minimum_drawdown: Some(Money::from_major(20)),
@@ -487,6 +1198,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: Some(Money::from_major(5000)),
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -508,10 +1223,13 @@ impl FacilityConfig {
                 maturity_date: None,
                 amortization_method: AmortizationMethod::InterestOnly,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual360,
                 compounding_frequency: CompoundingFrequency::Monthly,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(2.0))),
                 grace_period_days: 10,
                 default_rate: Some(Rate::from_decimal(rate.as_decimal() + dec!(0.05))),
@@ -526,6 +1244,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::HoldInSuspense,
+                waterfall: PaymentWaterfall::standard(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -533,11 +1252,20 @@ impl FacilityConfig {
                 origination_fee_percentage: Some(dec!(0.5)),
                 late_fee: Some(Money::from_major(50)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: None,
                 commitment_fee_rate: Some(commitment_fee_rate),
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::open_end(),
             collateral_config: None,
             limits: FacilityLimits {
                 minimum_drawdown: Some(Money::from_major(10000)),
@@ -548,6 +1276,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -573,10 +1305,13 @@ impl FacilityConfig {
                 maturity_date: None,
                 amortization_method: AmortizationMethod::InterestOnly, // during draw period
                 balloon_payment: None,
+                draw_period_months: Some(draw_period_months),
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Monthly,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: Some(PenaltyConfig::new(rate, dec!(1.5))),
                 grace_period_days: 15,
                 default_rate: Some(Rate::from_decimal(rate.as_decimal() * dec!(1.5))),
@@ -591,6 +1326,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::HoldInSuspense,
+                waterfall: PaymentWaterfall::standard(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -598,11 +1334,24 @@ impl FacilityConfig {
                 origination_fee_percentage: None,
                 late_fee: Some(Money::from_major(35)),
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: None,
                 annual_fee: Some(Money::from_major(75)),
+                annual_fee_policy: Some(AnnualFeePolicy {
+                    billing_frequency: FeeBillingFrequency::Annual,
+                    waive_first_year: false,
+                    refund_window_days: Some(30),
+                }),
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: None,
             },
+            delinquency_policy: DelinquencyPolicy::open_end(),
             collateral_config: Some(CollateralConfig {
                 collateral_type: "real_estate".to_string(),
                 initial_value: property_value,
@@ -625,6 +1374,10 @@ impl FacilityConfig {
                 overdraft_limit: None,
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: None,
+                overdraft_review: None,
             },
         }
     }
@@ -647,10 +1400,13 @@ impl FacilityConfig {
                 maturity_date: None,
                 amortization_method: AmortizationMethod::InterestOnly,
                 balloon_payment: None,
+                draw_period_months: None,
             },
             interest_config: InterestConfig {
                 day_count_convention: DayCountConvention::Actual365,
                 compounding_frequency: CompoundingFrequency::Continuous,
+                interest_method: InterestMethod::DailyAccrual,
+                penalty_apr: None,
                 penalty_config: None, // no penalties for overdraft
                 grace_period_days: 0, // no grace period
                 default_rate: None,
@@ -665,6 +1421,7 @@ impl FacilityConfig {
                 overpayment_allowed: true,
                 overpayment_strategy: OverpaymentStrategy::ReducePrincipal,
                 partial_payment_strategy: PartialPaymentStrategy::ApplyImmediately,
+                waterfall: PaymentWaterfall::standard(),
                 autopay_enabled: false,
             },
             fee_config: FeeConfig {
@@ -672,10 +1429,24 @@ impl FacilityConfig {
                 origination_fee_percentage: None,
                 late_fee: None, // no late fees for overdraft
                 late_fee_percentage: None,
+                late_fee_method: LateFeeMethod::Flat,
+                late_fee_cap: None,
                 overlimit_fee: Some(Money::from_major(35)),
                 annual_fee: None,
+                annual_fee_policy: None,
                 prepayment_penalty: None,
                 commitment_fee_rate: None,
+                commitment_fee_schedule: None,
+                cash_advance_fee: None,
+                balance_transfer_fee: None,
+                foreign_transaction_fee: None,
+                overdraft_fees: Some(OverdraftFeeSchedule::default()),
+            },
+            delinquency_policy: DelinquencyPolicy {
+                default_after_days: Some(30),
+                non_accrual_after_days: Some(60),
+                charge_off_after_days: Some(60),
+                ..DelinquencyPolicy::closed_end()
             },
             collateral_config: None,
             limits: FacilityLimits {
@@ -687,6 +1458,10 @@ impl FacilityConfig {
                 overdraft_limit: Some(overdraft_limit),
                 daily_transaction_limit: None,
                 monthly_transaction_limit: None,
+                credit_line_review: None,
+                overlimit_policy: OverlimitPolicy::default(),
+                overdraft_decisioning: Some(OverdraftDecisionPolicy::default()),
+                overdraft_review: None,
             },
         }
     }
@@ -90,7 +90,7 @@ impl Money {
     pub fn compound(&self, rate: Decimal, periods: u32) -> Self {
         let mut factor = Decimal::ONE;
         for _ in 0..periods {
-            factor = factor * (Decimal::ONE + rate);
+            factor *= Decimal::ONE + rate;
         }
         Money((self.0 * factor).round_dp(8))
     }
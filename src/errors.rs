@@ -2,7 +2,7 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use crate::decimal::{Money, Rate};
-use crate::types::FacilityStatus;
+use crate::types::{FacilityStatus, OverdraftReturnReason};
 
 #[derive(Error, Debug)]
 pub enum FacilityError {
@@ -34,6 +34,12 @@ pub enum FacilityError {
         requested: Money,
     },
     
+    #[error("overdraft item returned: {reason:?}, amount {amount}")]
+    OverdraftItemReturned {
+        reason: OverdraftReturnReason,
+        amount: Money,
+    },
+    
     #[error("credit limit exceeded: limit {limit}, requested {requested}")]
     CreditLimitExceeded {
         limit: Money,
@@ -45,6 +51,16 @@ pub enum FacilityError {
         id: Uuid,
     },
     
+    #[error("authorization not found: {id}")]
+    AuthorizationNotFound {
+        id: Uuid,
+    },
+    
+    #[error("installment plan not found: {id}")]
+    InstallmentPlanNotFound {
+        id: Uuid,
+    },
+    
     #[error("milestone not approved for disbursement: {name}")]
     MilestoneNotApproved {
         name: String,
@@ -111,6 +127,38 @@ pub enum FacilityError {
         requested: Money,
     },
     
+    #[error("above maximum drawdown: maximum {maximum}, requested {requested}")]
+    AboveMaximumDrawdown {
+        maximum: Money,
+        requested: Money,
+    },
+    
+    #[error("daily transaction limit exceeded: limit {limit}, remaining {remaining}, requested {requested}")]
+    DailyTransactionLimitExceeded {
+        limit: Money,
+        remaining: Money,
+        requested: Money,
+    },
+    
+    #[error("monthly transaction limit exceeded: limit {limit}, remaining {remaining}, requested {requested}")]
+    MonthlyTransactionLimitExceeded {
+        limit: Money,
+        remaining: Money,
+        requested: Money,
+    },
+    
+    #[error("payment more than maximum: maximum {maximum}, provided {provided}")]
+    PaymentAboveMaximum {
+        maximum: Money,
+        provided: Money,
+    },
+    
+    #[error("credit limit below balance: limit {limit}, balance {balance}")]
+    CreditLimitBelowBalance {
+        limit: Money,
+        balance: Money,
+    },
+    
     #[error("exceeds credit limit: available {available}, requested {requested}")]
     ExceedsCreditLimit {
         available: Money,
@@ -2,8 +2,12 @@ use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::decimal::{Money, Rate};
-use crate::types::{FacilityId, FacilityStatus, OverpaymentStrategy};
+use crate::types::{
+    BalanceSegment, CreditLimitChangeReason, DelinquencyBucket, DrawType, FacilityId, FacilityStatus,
+    OverdraftFeeKind, OverdraftReturnReason, OverpaymentStrategy, TransactionFeeKind,
+};
 use rust_decimal::Decimal;
+use uuid::Uuid;
 
 /// all events that can be emitted by the facility
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -52,6 +56,26 @@ pub enum Event {
         applied_to_principal: Money,
         timestamp: DateTime<Utc>,
     },
+    PaymentHeldInSuspense {
+        facility_id: FacilityId,
+        amount: Money,
+        suspense_balance: Money,
+        minimum_payment: Money,
+        timestamp: DateTime<Utc>,
+    },
+    SuspenseReleased {
+        facility_id: FacilityId,
+        amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    PrepaymentPenaltyAssessed {
+        facility_id: FacilityId,
+        /// principal prepaid beyond the schedule and the free allowance
+        prepaid_amount: Money,
+        months_since_origination: u32,
+        penalty: Money,
+        timestamp: DateTime<Utc>,
+    },
     PaymentMissed {
         facility_id: FacilityId,
         expected_amount: Money,
@@ -70,6 +94,12 @@ pub enum Event {
         amount: Money,
         timestamp: DateTime<Utc>,
     },
+    /// interest accrued on non-accrual, owed but not recognised as income
+    InterestSuspended {
+        facility_id: FacilityId,
+        amount: Money,
+        timestamp: DateTime<Utc>,
+    },
     InterestCapitalized {
         facility_id: FacilityId,
         amount: Money,
@@ -103,6 +133,15 @@ pub enum Event {
         facility_id: FacilityId,
         old_limit: Money,
         new_limit: Money,
+        reason: CreditLimitChangeReason,
+        timestamp: DateTime<Utc>,
+    },
+    CreditLimitDecreaseScheduled {
+        facility_id: FacilityId,
+        old_limit: Money,
+        new_limit: Money,
+        reason: CreditLimitChangeReason,
+        effective_date: DateTime<Utc>,
         timestamp: DateTime<Utc>,
     },
     OverlimitOccurred {
@@ -111,10 +150,116 @@ pub enum Event {
         fees_applied: Money,
         timestamp: DateTime<Utc>,
     },
+    CycleInterestCharged {
+        facility_id: FacilityId,
+        segment: BalanceSegment,
+        average_daily_balance: Money,
+        days: u32,
+        amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    StatementGenerated {
+        facility_id: FacilityId,
+        statement_balance: Money,
+        minimum_payment: Money,
+        payment_due_date: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    TransactionFeeCharged {
+        facility_id: FacilityId,
+        kind: TransactionFeeKind,
+        draw_type: DrawType,
+        segment: BalanceSegment,
+        transaction_amount: Money,
+        fee: Money,
+        timestamp: DateTime<Utc>,
+    },
+    DrawPeriodEnded {
+        facility_id: FacilityId,
+        outstanding_principal: Money,
+        repayment_months: u32,
+        scheduled_payment: Option<Money>,
+        timestamp: DateTime<Utc>,
+    },
+    AuthorizationHeld {
+        facility_id: FacilityId,
+        authorization_id: Uuid,
+        amount: Money,
+        total_held: Money,
+        expires_at: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    AuthorizationCaptured {
+        facility_id: FacilityId,
+        authorization_id: Uuid,
+        authorized_amount: Money,
+        captured_amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    AuthorizationReleased {
+        facility_id: FacilityId,
+        authorization_id: Uuid,
+        amount: Money,
+        reason: String,
+        timestamp: DateTime<Utc>,
+    },
+    InstallmentPlanCreated {
+        facility_id: FacilityId,
+        plan_id: Uuid,
+        segment: BalanceSegment,
+        amount: Money,
+        term_months: u32,
+        installment_amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    InstallmentBilled {
+        facility_id: FacilityId,
+        plan_id: Uuid,
+        installment_number: u32,
+        principal: Money,
+        /// plan interest or fixed monthly fee billed with the installment
+        charge: Money,
+        remaining_principal: Money,
+        timestamp: DateTime<Utc>,
+    },
+    InstallmentPlanPrepaid {
+        facility_id: FacilityId,
+        plan_id: Uuid,
+        amount: Money,
+        remaining_principal: Money,
+        timestamp: DateTime<Utc>,
+    },
+    InstallmentPlanClosed {
+        facility_id: FacilityId,
+        plan_id: Uuid,
+        /// principal returned to the revolving balance
+        remaining_principal: Money,
+        reason: String,
+        timestamp: DateTime<Utc>,
+    },
+    AnnualFeeAssessed {
+        facility_id: FacilityId,
+        amount: Money,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        waived: bool,
+        timestamp: DateTime<Utc>,
+    },
+    AnnualFeeRefunded {
+        facility_id: FacilityId,
+        amount: Money,
+        /// refund left after clearing the balance, owed to the customer
+        unapplied: Money,
+        days_unused: u32,
+        timestamp: DateTime<Utc>,
+    },
     CommitmentFeeCharged {
         facility_id: FacilityId,
+        /// average undrawn amount over the billing period
         undrawn_amount: Money,
         fee: Money,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
         timestamp: DateTime<Utc>,
     },
 
@@ -141,6 +286,60 @@ pub enum Event {
         repayment_amount: Money,
         timestamp: DateTime<Utc>,
     },
+    OverdraftItemPaid {
+        facility_id: FacilityId,
+        amount: Money,
+        overdraft_amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftItemReturned {
+        facility_id: FacilityId,
+        item_id: Uuid,
+        amount: Money,
+        reason: OverdraftReturnReason,
+        fee: Money,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftSuspended {
+        facility_id: FacilityId,
+        overdraft_amount: Money,
+        reason: String,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftReinstated {
+        facility_id: FacilityId,
+        overdraft_amount: Money,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftProtectionSweep {
+        facility_id: FacilityId,
+        linked_account_id: String,
+        amount: Money,
+        fee: Money,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftRenewed {
+        facility_id: FacilityId,
+        limit: Money,
+        expiry_date: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftRepaymentPlanCreated {
+        facility_id: FacilityId,
+        principal: Money,
+        term_months: u32,
+        monthly_payment: Money,
+        first_payment_date: DateTime<Utc>,
+        timestamp: DateTime<Utc>,
+    },
+    OverdraftFeeCharged {
+        facility_id: FacilityId,
+        kind: OverdraftFeeKind,
+        /// fee before daily and monthly caps
+        scheduled_fee: Money,
+        fee: Money,
+        timestamp: DateTime<Utc>,
+    },
 
     // collateral events
     CollateralValueUpdated {
@@ -216,6 +415,8 @@ pub enum Event {
         facility_id: FacilityId,
         fee_amount: Money,
         days_overdue: u32,
+        /// late installment, when installments are tracked
+        installment_number: Option<u32>,
         timestamp: DateTime<Utc>,
     },
 
@@ -259,6 +460,21 @@ pub enum Event {
         reason: String,
         timestamp: DateTime<Utc>,
     },
+    DelinquencyBucketChanged {
+        facility_id: FacilityId,
+        old_bucket: DelinquencyBucket,
+        new_bucket: DelinquencyBucket,
+        days_past_due: u32,
+        timestamp: DateTime<Utc>,
+    },
+    NonAccrualStatusChanged {
+        facility_id: FacilityId,
+        non_accrual: bool,
+        days_past_due: u32,
+        /// suspended interest recognised as the facility leaves non-accrual
+        recognised_interest: Money,
+        timestamp: DateTime<Utc>,
+    },
 }
 
 /// event store for collecting events during operations
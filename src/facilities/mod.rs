@@ -5,6 +5,12 @@ pub mod serialization;
 pub mod term_loan;
 
 pub use open_term::{OpenTermLoan, OpenTermLoanBuilder};
-pub use overdraft::{OverdraftFacility, OverdraftBuilder, OverdraftState};
-pub use revolving::{RevolvingFacility, RevolvingFacilityBuilder, UtilizationState};
-pub use term_loan::{TermLoan, TermLoanBuilder};
\ No newline at end of file
+pub use overdraft::{
+    LinkedAccount, LinkedAccountKind, OverdraftBuilder, OverdraftDecision, OverdraftFacility,
+    OverdraftReview, OverdraftReviewDecision, OverdraftState, ReturnedItem,
+};
+pub use revolving::{
+    Authorization, CreditLineDecision, InstallmentPlan, InstallmentPlanStatus, InstallmentPricing,
+    PayoffDisclosure, RevolvingFacility, RevolvingFacilityBuilder, Statement, UtilizationState,
+};
+pub use term_loan::{PayoffQuote, TermLoan, TermLoanBuilder};
\ No newline at end of file
@@ -536,6 +536,12 @@ pub struct OpenTermLoanBuilder {
     time_provider: Option<*const SafeTimeProvider>,
 }
 
+impl Default for OpenTermLoanBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OpenTermLoanBuilder {
     pub fn new() -> Self {
         Self {
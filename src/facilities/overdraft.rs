@@ -1,13 +1,27 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use hourglass_rs::SafeTimeProvider;
-use rust_decimal_macros::dec;
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use crate::config::{FacilityConfig, FacilityType};
+use crate::config::{
+    FacilityConfig, FacilityType, OverdraftDecisionPolicy, OverdraftFeeSchedule, OverdraftReviewPolicy,
+};
 use crate::decimal::{Money, Rate};
 use crate::errors::{FacilityError, Result};
 use crate::events::Event;
 use crate::facility::Facility;
-use crate::types::FacilityStatus;
+use crate::interest::{exp, AccrualEngine, DailyAccrual};
+use crate::payments::{
+    AmortizationSchedule, PaymentContext, PaymentProcessor, PaymentRequest, PaymentWaterfall,
+};
+use crate::state::TransactionUsage;
+use crate::types::{
+    AmortizationMethod, CreditLimitChangeReason, FacilityStatus, OverdraftFeeKind,
+    OverdraftReturnReason,
+};
+
+use super::revolving::add_months;
+use super::term_loan::schedule_installments;
 
 /// overdraft states
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,17 +33,188 @@ pub enum OverdraftState {
     Suspended,   // facility withdrawn
 }
 
+/// outcome of deciding a debit against the linked account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverdraftDecision {
+    Pay,
+    Return(OverdraftReturnReason),
+}
+
+/// debit returned unpaid
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnedItem {
+    pub id: Uuid,
+    pub amount: Money,
+    pub reason: OverdraftReturnReason,
+    /// returned item (nsf) fee charged, after caps
+    pub fee: Money,
+    pub returned_at: DateTime<Utc>,
+}
+
+/// kind of account that can cover an overdraft
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkedAccountKind {
+    Savings,
+    CreditLine,
+}
+
+/// savings account or line swept to cover a negative balance
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkedAccount {
+    pub id: String,
+    pub kind: LinkedAccountKind,
+    /// funds or credit available to sweep
+    pub available: Money,
+    pub transfer_fee: Money,
+    /// transfers are made in multiples of this amount, zero for exact transfers
+    pub transfer_increment: Money,
+}
+
+impl LinkedAccount {
+    pub fn savings(id: String, available: Money) -> Self {
+        Self {
+            id,
+            kind: LinkedAccountKind::Savings,
+            available,
+            transfer_fee: Money::ZERO,
+            transfer_increment: Money::ZERO,
+        }
+    }
+    
+    pub fn credit_line(id: String, available: Money) -> Self {
+        Self {
+            kind: LinkedAccountKind::CreditLine,
+            ..Self::savings(id, available)
+        }
+    }
+    
+    pub fn transfer_fee(mut self, fee: Money) -> Self {
+        self.transfer_fee = fee;
+        self
+    }
+    
+    pub fn transfer_increment(mut self, increment: Money) -> Self {
+        self.transfer_increment = increment;
+        self
+    }
+    
+    /// transfer toward a shortfall, rounded up to whole increments and
+    /// limited to the whole increments available
+    pub fn transfer_for(&self, shortfall: Money) -> Money {
+        if shortfall <= Money::ZERO || self.available <= Money::ZERO {
+            return Money::ZERO;
+        }
+        if self.transfer_increment <= Money::ZERO {
+            return shortfall.min(self.available);
+        }
+        
+        let increment = self.transfer_increment.as_decimal();
+        let needed = (shortfall.as_decimal() / increment).ceil();
+        let possible = (self.available.as_decimal() / increment).floor();
+        Money::from_decimal(needed.min(possible) * increment)
+    }
+}
+
+/// outcome of an overdraft limit review
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverdraftReviewDecision {
+    Renew,
+    Reduce { new_limit: Money },
+    Withdraw { reason: CreditLimitChangeReason, effective_date: DateTime<Utc> },
+}
+
+/// account conduct since the last review and the decision taken
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverdraftReview {
+    pub reviewed_at: DateTime<Utc>,
+    /// time-weighted account balance, negative while mostly overdrawn
+    pub average_balance: Money,
+    pub days_overdrawn: u32,
+    pub deposit_inflows: Money,
+    pub old_limit: Money,
+    pub decision: OverdraftReviewDecision,
+    /// next review date, none once withdrawal is scheduled
+    pub expiry_date: Option<DateTime<Utc>>,
+}
+
+/// account balances and deposits since the last review
+#[derive(Debug, Clone)]
+struct ReviewActivity {
+    since: DateTime<Utc>,
+    opening_balance: Money,
+    balances: Vec<(DateTime<Utc>, Money)>,
+    deposits: Money,
+}
+
+impl ReviewActivity {
+    fn new(since: DateTime<Utc>, balance: Money) -> Self {
+        Self {
+            since,
+            opening_balance: balance,
+            balances: Vec::new(),
+            deposits: Money::ZERO,
+        }
+    }
+    
+    fn record(&mut self, at: DateTime<Utc>, balance: Money, deposit: Money) {
+        self.balances.push((at, balance));
+        self.deposits += deposit;
+    }
+    
+    /// time-weighted average balance and whole days spent overdrawn up to `now`
+    fn summarize(&self, now: DateTime<Utc>) -> (Money, u32) {
+        let mut weighted = Decimal::ZERO;
+        let mut overdrawn_seconds = 0;
+        let mut cursor = self.since;
+        let mut balance = self.opening_balance;
+        
+        let closing = std::iter::once((now, balance));
+        for (at, next_balance) in self.balances.iter().copied().chain(closing) {
+            let seconds = (at - cursor).num_seconds().max(0);
+            weighted += balance.as_decimal() * Decimal::from(seconds);
+            if balance < Money::ZERO {
+                overdrawn_seconds += seconds;
+            }
+            cursor = at.max(cursor);
+            balance = next_balance;
+        }
+        
+        let total_seconds = (now - self.since).num_seconds();
+        let average = if total_seconds > 0 {
+            Money::from_decimal(weighted / Decimal::from(total_seconds))
+        } else {
+            balance
+        };
+        (average, (overdrawn_seconds / 86_400) as u32)
+    }
+}
+
 /// overdraft facility linked to account
 pub struct OverdraftFacility {
     facility: Facility,
     time: Option<*const SafeTimeProvider>,
     overdraft_limit: Money,
     buffer_zone: Money,
-    _linked_account_id: String,
+    linked_account_id: String,
     linked_account_balance: Money,
+    linked_accounts: Vec<LinkedAccount>,
+    deposit_waterfall: PaymentWaterfall,
     is_active: bool,
-    daily_fee: Option<Money>,
     state: OverdraftState,
+    accrual_base: Money,
+    balance_changes: Vec<(DateTime<Utc>, Money)>,
+    fee_usage: TransactionUsage,
+    overdrawn_since: Option<DateTime<Utc>>,
+    sustained_fee_charged: bool,
+    last_daily_fee: Option<NaiveDate>,
+    opt_in: bool,
+    suspended_at: Option<DateTime<Utc>>,
+    returned_items: Vec<ReturnedItem>,
+    excess_usage: Vec<DateTime<Utc>>,
+    expiry_date: Option<DateTime<Utc>>,
+    scheduled_withdrawal: Option<(DateTime<Utc>, CreditLimitChangeReason)>,
+    review_activity: ReviewActivity,
+    repayment_plan: Option<AmortizationSchedule>,
 }
 
 impl OverdraftFacility {
@@ -51,17 +236,37 @@ impl OverdraftFacility {
         
         let overdraft_limit = facility.config.limits.overdraft_limit
             .unwrap_or(facility.config.financial_terms.commitment_amount);
+        let origination_date = facility.state.origination_date;
+        let expiry_date = match &facility.config.limits.overdraft_review {
+            Some(policy) => Some(add_months(origination_date, policy.term_months)?),
+            None => None,
+        };
         
         Ok(Self {
             facility,
             time: None,
             overdraft_limit,
             buffer_zone,
-            _linked_account_id: linked_account_id,
+            linked_account_id,
             linked_account_balance: Money::ZERO,
+            linked_accounts: Vec::new(),
+            deposit_waterfall: PaymentWaterfall::standard(),
             is_active: false,
-            daily_fee: None,
             state: OverdraftState::Available,
+            accrual_base: Money::ZERO,
+            balance_changes: Vec::new(),
+            fee_usage: TransactionUsage::default(),
+            overdrawn_since: None,
+            sustained_fee_charged: false,
+            last_daily_fee: None,
+            opt_in: false,
+            suspended_at: None,
+            returned_items: Vec::new(),
+            excess_usage: Vec::new(),
+            expiry_date,
+            scheduled_withdrawal: None,
+            review_activity: ReviewActivity::new(origination_date, Money::ZERO),
+            repayment_plan: None,
         })
     }
     
@@ -84,23 +289,45 @@ impl OverdraftFacility {
     }
     
     /// process account transaction and activate overdraft if needed with explicit time
+    ///
+    /// debits that would overdraw the account are decided first; a returned
+    /// item is recorded with its fee and reported as an error. paid debits
+    /// sweep linked accounts before using the overdraft, and deposits repay
+    /// fees, interest and principal through the deposit waterfall
     pub fn process_account_transaction_with_time(
         &mut self,
         transaction_amount: Money,
         time_provider: &SafeTimeProvider,
     ) -> Result<Money> {
+        let now = time_provider.now();
+        self.apply_scheduled_withdrawal(time_provider)?;
+        self.review_suspension_with_time(time_provider);
+        
         let previous_balance = self.linked_account_balance;
-        let new_balance = previous_balance + transaction_amount;
         
-        // check if transaction would exceed overdraft
-        if new_balance < Money::ZERO - self.overdraft_limit {
-            return Err(FacilityError::OverdraftLimitExceeded {
-                limit: self.overdraft_limit,
-                requested: (Money::ZERO - new_balance) - self.overdraft_limit,
+        if let OverdraftDecision::Return(reason) = self.decide_transaction(transaction_amount) {
+            let new_balance = previous_balance + transaction_amount + self.sweep_cover(transaction_amount);
+            self.return_item(transaction_amount.abs(), reason, now);
+            return Err(match reason {
+                OverdraftReturnReason::LimitExceeded => FacilityError::OverdraftLimitExceeded {
+                    limit: self.overdraft_limit,
+                    requested: (Money::ZERO - new_balance) - self.overdraft_limit,
+                },
+                _ => FacilityError::OverdraftItemReturned {
+                    reason,
+                    amount: transaction_amount.abs(),
+                },
             });
         }
         
+        let posted = if transaction_amount > Money::ZERO {
+            self.apply_deposit(transaction_amount, time_provider)?
+        } else {
+            transaction_amount + self.sweep_linked_accounts(transaction_amount, now)
+        };
+        let new_balance = previous_balance + posted;
         self.linked_account_balance = new_balance;
+        self.review_activity.record(now, new_balance, transaction_amount.max(Money::ZERO));
         
         // handle overdraft activation/deactivation
         if previous_balance >= Money::ZERO && new_balance < Money::ZERO {
@@ -114,9 +341,575 @@ impl OverdraftFacility {
             self.update_overdraft_amount(Money::ZERO - new_balance, time_provider)?;
         }
         
+        // debit paid into the overdraft
+        if transaction_amount < Money::ZERO && new_balance < Money::ZERO {
+            let overdraft_amount = Money::ZERO - new_balance;
+            self.facility.events.emit(Event::OverdraftItemPaid {
+                facility_id: self.facility.id,
+                amount: transaction_amount.abs(),
+                overdraft_amount,
+                timestamp: now,
+            });
+            self.charge_paid_item_fee(transaction_amount.abs(), now);
+            
+            if overdraft_amount > self.overdraft_limit {
+                self.record_excess_usage(now);
+            }
+        }
+        
         Ok(new_balance)
     }
     
+    /// decide whether a transaction would be paid or returned, counting
+    /// linked account sweeps
+    pub fn decide_transaction(&self, transaction_amount: Money) -> OverdraftDecision {
+        let new_balance = self.linked_account_balance + transaction_amount + self.sweep_cover(transaction_amount);
+        if transaction_amount >= Money::ZERO || new_balance >= Money::ZERO {
+            return OverdraftDecision::Pay;
+        }
+        
+        let policy = self.decision_policy().cloned().unwrap_or_default();
+        let overdraft_amount = Money::ZERO - new_balance;
+        
+        let reason = if self.is_suspended() {
+            OverdraftReturnReason::Suspended
+        } else if policy.require_opt_in && !self.opt_in {
+            OverdraftReturnReason::NotOptedIn
+        } else if policy.max_paid_item.is_some_and(|max| transaction_amount.abs() > max) {
+            OverdraftReturnReason::ItemTooLarge
+        } else if overdraft_amount > self.overdraft_limit + policy.overlimit_tolerance {
+            OverdraftReturnReason::LimitExceeded
+        } else {
+            return OverdraftDecision::Pay;
+        };
+        OverdraftDecision::Return(reason)
+    }
+    
+    /// transfers from linked accounts, in order, to keep a debit from
+    /// overdrawing the account
+    fn planned_sweeps(&self, transaction_amount: Money) -> Vec<(usize, Money)> {
+        let mut shortfall = Money::ZERO - (self.linked_account_balance + transaction_amount);
+        let mut sweeps = Vec::new();
+        if transaction_amount >= Money::ZERO {
+            return sweeps;
+        }
+        
+        for (index, account) in self.linked_accounts.iter().enumerate() {
+            if shortfall <= Money::ZERO {
+                break;
+            }
+            let transfer = account.transfer_for(shortfall);
+            if transfer > Money::ZERO {
+                sweeps.push((index, transfer));
+                shortfall -= transfer;
+            }
+        }
+        sweeps
+    }
+    
+    /// total linked account sweeps a debit would draw on
+    fn sweep_cover(&self, transaction_amount: Money) -> Money {
+        self.planned_sweeps(transaction_amount)
+            .iter()
+            .fold(Money::ZERO, |sum, (_, transfer)| sum + *transfer)
+    }
+    
+    /// sweep linked accounts ahead of a debit, charging transfer fees
+    fn sweep_linked_accounts(&mut self, transaction_amount: Money, now: DateTime<Utc>) -> Money {
+        let mut swept = Money::ZERO;
+        for (index, transfer) in self.planned_sweeps(transaction_amount) {
+            let account = &mut self.linked_accounts[index];
+            account.available -= transfer;
+            let fee = account.transfer_fee;
+            let linked_account_id = account.id.clone();
+            
+            if fee > Money::ZERO {
+                self.facility.state.accrued_fees += fee;
+                self.facility.state.total_fees_charged += fee;
+            }
+            swept += transfer;
+            
+            self.facility.events.emit(Event::OverdraftProtectionSweep {
+                facility_id: self.facility.id,
+                linked_account_id,
+                amount: transfer,
+                fee,
+                timestamp: now,
+            });
+        }
+        swept
+    }
+    
+    /// repay fees, interest and overdrawn principal from a deposit through
+    /// the deposit waterfall, returning the amount credited to the account
+    fn apply_deposit(&mut self, amount: Money, time_provider: &SafeTimeProvider) -> Result<Money> {
+        let state = &self.facility.state;
+        let mut context = PaymentContext {
+            facility_id: self.facility.id,
+            accrued_fees: state.accrued_fees,
+            accrued_penalties: state.accrued_penalties,
+            accrued_interest: state.accrued_interest,
+            outstanding_principal: state.outstanding_principal,
+            minimum_payment: None,
+            payment_due_date: None,
+            days_overdue: 0,
+            ..Default::default()
+        };
+        if context.total_outstanding().is_zero() {
+            return Ok(amount);
+        }
+        
+        let payment = PaymentRequest {
+            facility_id: self.facility.id,
+            amount,
+            payment_date: time_provider.now(),
+            reference: format!("Deposit to {}", self.linked_account_id),
+            is_principal_only: false,
+        };
+        let processor = PaymentProcessor::new(self.deposit_waterfall.clone());
+        let result = processor.process(payment, &mut context, time_provider, &mut self.facility.events)?;
+        let application = result.application;
+        
+        let state = &mut self.facility.state;
+        state.accrued_fees -= application.to_fees;
+        state.total_fees_paid += application.to_fees;
+        state.accrued_penalties -= application.to_penalties;
+        state.accrued_interest -= application.to_interest;
+        state.total_interest_paid += application.to_interest;
+        state.total_payments_received += application.to_fees + application.to_penalties + application.to_interest;
+        
+        // deposits pay down the repayment plan, oldest installment first
+        if !state.installments.is_empty() {
+            let now = time_provider.now();
+            state.allocate_to_installments(application.to_interest + application.to_principal, now);
+            state.refresh_installments(now);
+        }
+        
+        Ok(application.to_principal + application.excess)
+    }
+    
+    /// review the limit using stored time once the term has run
+    pub fn review_limit(&mut self) -> Result<Option<OverdraftReview>> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.review_limit_with_time(time)
+    }
+    
+    /// review the limit once the term has run with explicit time
+    ///
+    /// the limit is renewed, reduced, or withdrawn after a notice period
+    /// depending on the average balance, days overdrawn and deposits since
+    /// the last review
+    pub fn review_limit_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Option<OverdraftReview>> {
+        self.apply_scheduled_withdrawal(time_provider)?;
+        
+        let now = time_provider.now();
+        let policy = match self.review_policy() {
+            Some(policy) => policy.clone(),
+            None => return Ok(None),
+        };
+        let expiry_date = match self.expiry_date {
+            Some(expiry_date) if now >= expiry_date && self.scheduled_withdrawal.is_none() => expiry_date,
+            _ => return Ok(None),
+        };
+        
+        let (average_balance, days_overdrawn) = self.review_activity.summarize(now);
+        let deposit_inflows = self.review_activity.deposits;
+        let old_limit = self.overdraft_limit;
+        let average_usage = if average_balance < Money::ZERO && old_limit > Money::ZERO {
+            average_balance.abs().as_decimal() / old_limit.as_decimal()
+        } else {
+            Decimal::ZERO
+        };
+        
+        let withdraw_reason = if days_overdrawn >= policy.withdraw_after_days_overdrawn {
+            Some(CreditLimitChangeReason::PersistentUse)
+        } else if deposit_inflows < policy.min_deposit_inflows {
+            Some(CreditLimitChangeReason::LowDeposits)
+        } else {
+            None
+        };
+        let reduce = days_overdrawn >= policy.reduce_after_days_overdrawn
+            || average_usage >= policy.reduce_average_usage;
+        let reduced_limit = Money::from_decimal(
+            (old_limit.as_decimal() * (Decimal::ONE - policy.reduce_percentage)).round_dp(2)
+        );
+        
+        let decision = match withdraw_reason {
+            Some(reason) => OverdraftReviewDecision::Withdraw {
+                reason,
+                effective_date: now + chrono::Duration::days(policy.withdrawal_notice_days as i64),
+            },
+            None if reduce && reduced_limit < policy.minimum_limit => OverdraftReviewDecision::Withdraw {
+                reason: CreditLimitChangeReason::PersistentUse,
+                effective_date: now + chrono::Duration::days(policy.withdrawal_notice_days as i64),
+            },
+            None if reduce => OverdraftReviewDecision::Reduce {
+                new_limit: reduced_limit.max(self.facility.state.outstanding_principal),
+            },
+            None => OverdraftReviewDecision::Renew,
+        };
+        
+        match decision {
+            OverdraftReviewDecision::Withdraw { reason, effective_date } => {
+                self.scheduled_withdrawal = Some((effective_date, reason));
+                self.expiry_date = None;
+                self.facility.events.emit(Event::CreditLimitDecreaseScheduled {
+                    facility_id: self.facility.id,
+                    old_limit,
+                    new_limit: Money::ZERO,
+                    reason,
+                    effective_date,
+                    timestamp: now,
+                });
+            }
+            OverdraftReviewDecision::Reduce { new_limit } => {
+                self.set_overdraft_limit(new_limit, CreditLimitChangeReason::PersistentUse, now);
+                self.renew(add_months(expiry_date, policy.term_months)?, now);
+            }
+            OverdraftReviewDecision::Renew => {
+                self.renew(add_months(expiry_date, policy.term_months)?, now);
+            }
+        }
+        
+        self.review_activity = ReviewActivity::new(now, self.linked_account_balance);
+        
+        Ok(Some(OverdraftReview {
+            reviewed_at: now,
+            average_balance,
+            days_overdrawn,
+            deposit_inflows,
+            old_limit,
+            decision,
+            expiry_date: self.expiry_date,
+        }))
+    }
+    
+    fn renew(&mut self, expiry_date: DateTime<Utc>, now: DateTime<Utc>) {
+        self.expiry_date = Some(expiry_date);
+        self.facility.events.emit(Event::OverdraftRenewed {
+            facility_id: self.facility.id,
+            limit: self.overdraft_limit,
+            expiry_date,
+            timestamp: now,
+        });
+    }
+    
+    fn set_overdraft_limit(&mut self, new_limit: Money, reason: CreditLimitChangeReason, now: DateTime<Utc>) {
+        let old_limit = self.overdraft_limit;
+        self.overdraft_limit = new_limit;
+        self.facility.config.limits.overdraft_limit = Some(new_limit);
+        self.refresh_state();
+        
+        self.facility.events.emit(Event::CreditLimitChanged {
+            facility_id: self.facility.id,
+            old_limit,
+            new_limit,
+            reason,
+            timestamp: now,
+        });
+    }
+    
+    /// withdraw the limit once the notice period has passed, putting any
+    /// overdrawn balance on a repayment plan
+    fn apply_scheduled_withdrawal(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        let now = time_provider.now();
+        let reason = match self.scheduled_withdrawal {
+            Some((effective_date, reason)) if now >= effective_date => reason,
+            _ => return Ok(()),
+        };
+        self.scheduled_withdrawal = None;
+        self.set_overdraft_limit(Money::ZERO, reason, now);
+        
+        let principal = self.facility.state.outstanding_principal;
+        if principal.is_zero() {
+            return Ok(());
+        }
+        
+        let repayment_months = self.review_policy()
+            .map(|policy| policy.repayment_months)
+            .unwrap_or(12);
+        let schedule = AmortizationSchedule::generate(
+            self.facility.id,
+            principal,
+            self.facility.config.financial_terms.interest_rate,
+            repayment_months,
+            now,
+            AmortizationMethod::EqualInstallments,
+            time_provider,
+        )?;
+        let first_payment = schedule.payments.first().cloned();
+        
+        // the plan's installments set the amounts due from here on
+        self.facility.state.replace_installments_after(0, schedule_installments(&schedule, 0));
+        self.facility.state.refresh_installments(now);
+        
+        if let Some(payment) = first_payment {
+            self.facility.events.emit(Event::OverdraftRepaymentPlanCreated {
+                facility_id: self.facility.id,
+                principal,
+                term_months: repayment_months,
+                monthly_payment: payment.payment_amount,
+                first_payment_date: payment.payment_date,
+                timestamp: now,
+            });
+        }
+        self.repayment_plan = Some(schedule);
+        
+        Ok(())
+    }
+    
+    /// review policy from the facility configuration
+    pub fn review_policy(&self) -> Option<&OverdraftReviewPolicy> {
+        self.facility.config.limits.overdraft_review.as_ref()
+    }
+    
+    /// date the arranged limit is next reviewed
+    pub fn expiry_date(&self) -> Option<DateTime<Utc>> {
+        self.expiry_date
+    }
+    
+    /// date a scheduled withdrawal of the limit takes effect
+    pub fn scheduled_withdrawal(&self) -> Option<DateTime<Utc>> {
+        self.scheduled_withdrawal.map(|(effective_date, _)| effective_date)
+    }
+    
+    /// repayment plan for a balance left when the limit was withdrawn
+    pub fn repayment_plan(&self) -> Option<&AmortizationSchedule> {
+        self.repayment_plan.as_ref()
+    }
+    
+    /// current arranged limit
+    pub fn overdraft_limit(&self) -> Money {
+        self.overdraft_limit
+    }
+    
+    /// add a linked account, swept after those already linked
+    pub fn add_linked_account(&mut self, account: LinkedAccount) {
+        self.linked_accounts.push(account);
+    }
+    
+    /// linked accounts in sweep order
+    pub fn linked_accounts(&self) -> &[LinkedAccount] {
+        &self.linked_accounts
+    }
+    
+    /// update the funds available in a linked account
+    pub fn set_linked_account_available(&mut self, id: &str, available: Money) -> Result<()> {
+        let account = self.linked_accounts
+            .iter_mut()
+            .find(|account| account.id == id)
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: format!("Linked account {} not found", id),
+            })?;
+        account.available = available;
+        Ok(())
+    }
+    
+    /// change the order deposits repay fees, interest and principal
+    pub fn set_deposit_waterfall(&mut self, waterfall: PaymentWaterfall) {
+        self.deposit_waterfall = waterfall;
+    }
+    
+    /// id of the account the overdraft is attached to
+    pub fn linked_account_id(&self) -> &str {
+        &self.linked_account_id
+    }
+    
+    /// record a returned item and charge the returned item fee
+    fn return_item(&mut self, amount: Money, reason: OverdraftReturnReason, now: DateTime<Utc>) {
+        let fee = match self.fee_schedule().and_then(|schedule| schedule.returned_item_fee) {
+            Some(fee) => self.charge_fee(OverdraftFeeKind::ReturnedItem, fee, now),
+            None => Money::ZERO,
+        };
+        
+        let item = ReturnedItem {
+            id: Uuid::new_v4(),
+            amount,
+            reason,
+            fee,
+            returned_at: now,
+        };
+        self.facility.events.emit(Event::OverdraftItemReturned {
+            facility_id: self.facility.id,
+            item_id: item.id,
+            amount,
+            reason,
+            fee,
+            timestamp: now,
+        });
+        self.returned_items.push(item);
+        
+        if matches!(reason, OverdraftReturnReason::LimitExceeded | OverdraftReturnReason::ItemTooLarge) {
+            self.record_excess_usage(now);
+        }
+    }
+    
+    /// count an item paid or returned for exceeding the line, suspending on repeated use
+    fn record_excess_usage(&mut self, now: DateTime<Utc>) {
+        let policy = self.decision_policy().cloned().unwrap_or_default();
+        let window = chrono::Duration::days(policy.excess_window_days as i64);
+        self.excess_usage.retain(|at| now - *at < window);
+        self.excess_usage.push(now);
+        
+        if let Some(max_items) = policy.suspend_after_excess_items {
+            if self.excess_usage.len() >= max_items as usize {
+                self.suspend("Repeated excess overdraft usage", now);
+            }
+        }
+    }
+    
+    /// suspend the overdraft using stored time if it has been negative too long
+    pub fn review_suspension(&mut self) -> Result<bool> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        Ok(self.review_suspension_with_time(time))
+    }
+    
+    /// suspend the overdraft if it has been negative too long with explicit time
+    pub fn review_suspension_with_time(&mut self, time_provider: &SafeTimeProvider) -> bool {
+        if self.is_suspended() {
+            return true;
+        }
+        
+        let now = time_provider.now();
+        let limit_days = self.decision_policy().and_then(|policy| policy.suspend_after_negative_days);
+        if let (Some(limit_days), Some(since)) = (limit_days, self.overdrawn_since) {
+            if (now - since).num_days() >= limit_days as i64 {
+                self.suspend("Extended negative balance", now);
+            }
+        }
+        self.is_suspended()
+    }
+    
+    fn suspend(&mut self, reason: &str, now: DateTime<Utc>) {
+        if self.is_suspended() {
+            return;
+        }
+        self.suspended_at = Some(now);
+        self.refresh_state();
+        
+        self.facility.events.emit(Event::OverdraftSuspended {
+            facility_id: self.facility.id,
+            overdraft_amount: self.facility.state.outstanding_principal,
+            reason: reason.to_string(),
+            timestamp: now,
+        });
+    }
+    
+    /// reinstate a suspended overdraft using stored time
+    pub fn reinstate(&mut self) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.reinstate_with_time(time)
+    }
+    
+    /// reinstate a suspended overdraft with explicit time
+    ///
+    /// excess usage is forgotten and the negative balance clock restarts
+    pub fn reinstate_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        if !self.is_suspended() {
+            return Err(FacilityError::InvalidState {
+                current: format!("{:?}", self.state),
+                expected: "Suspended".to_string(),
+            });
+        }
+        
+        let now = time_provider.now();
+        self.suspended_at = None;
+        self.excess_usage.clear();
+        if self.is_active {
+            self.overdrawn_since = Some(now);
+        }
+        self.refresh_state();
+        
+        self.facility.events.emit(Event::OverdraftReinstated {
+            facility_id: self.facility.id,
+            overdraft_amount: self.facility.state.outstanding_principal,
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// opt in to or out of having overdrawing items paid
+    pub fn set_overdraft_opt_in(&mut self, opt_in: bool) {
+        self.opt_in = opt_in;
+    }
+    
+    /// check if the customer has opted in to overdraft coverage
+    pub fn overdraft_opt_in(&self) -> bool {
+        self.opt_in
+    }
+    
+    /// check if the overdraft is suspended
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_at.is_some()
+    }
+    
+    /// when the overdraft was suspended
+    pub fn suspended_at(&self) -> Option<DateTime<Utc>> {
+        self.suspended_at
+    }
+    
+    /// items returned unpaid
+    pub fn returned_items(&self) -> &[ReturnedItem] {
+        &self.returned_items
+    }
+    
+    /// current overdraft state
+    pub fn state(&self) -> OverdraftState {
+        self.state
+    }
+    
+    /// decisioning policy from the facility configuration
+    pub fn decision_policy(&self) -> Option<&OverdraftDecisionPolicy> {
+        self.facility.config.limits.overdraft_decisioning.as_ref()
+    }
+    
+    /// overdraft limit usable right now, zero while suspended
+    fn available_limit(&self) -> Money {
+        if self.is_suspended() {
+            Money::ZERO
+        } else {
+            self.overdraft_limit
+        }
+    }
+    
+    /// derive the state from the overdrawn amount and suspension
+    fn refresh_state(&mut self) {
+        let amount = self.facility.state.outstanding_principal;
+        self.state = if self.is_suspended() {
+            OverdraftState::Suspended
+        } else if !self.is_active {
+            OverdraftState::Available
+        } else if amount <= self.buffer_zone {
+            OverdraftState::BufferZone
+        } else if amount <= self.overdraft_limit {
+            OverdraftState::Active
+        } else {
+            OverdraftState::Exceeded
+        };
+    }
+    
     /// activate overdraft
     fn activate_overdraft(
         &mut self,
@@ -125,16 +918,11 @@ impl OverdraftFacility {
     ) -> Result<()> {
         self.is_active = true;
         self.facility.state.outstanding_principal = amount;
+        self.record_balance_change(time_provider.now());
+        self.overdrawn_since = Some(time_provider.now());
+        self.sustained_fee_charged = false;
         self.facility.state.update_status(FacilityStatus::Active, time_provider.now());
-        
-        // determine state based on amount
-        self.state = if amount <= self.buffer_zone {
-            OverdraftState::BufferZone
-        } else if amount <= self.overdraft_limit {
-            OverdraftState::Active
-        } else {
-            OverdraftState::Exceeded
-        };
+        self.refresh_state();
         
         // emit activation event
         self.facility.events.emit(Event::OverdraftActivated {
@@ -162,7 +950,20 @@ impl OverdraftFacility {
         
         self.is_active = false;
         self.facility.state.outstanding_principal = Money::ZERO;
-        self.state = OverdraftState::Available;
+        self.record_balance_change(time_provider.now());
+        self.overdrawn_since = None;
+        self.refresh_state();
+        
+        // nothing left to repay on the plan
+        if self.repayment_plan.take().is_some() {
+            let state = &mut self.facility.state;
+            state.installments.clear();
+            state.installments_past_due = 0;
+            state.arrears_amount = Money::ZERO;
+            state.next_payment_due = None;
+            state.next_payment_amount = None;
+            state.minimum_payment_due = None;
+        }
         
         // emit cleared event
         self.facility.events.emit(Event::OverdraftCleared {
@@ -180,17 +981,11 @@ impl OverdraftFacility {
         new_amount: Money,
         time_provider: &SafeTimeProvider,
     ) -> Result<()> {
-        let previous_amount = self.facility.state.outstanding_principal;
-        self.facility.state.outstanding_principal = new_amount;
-        
-        // update state
-        self.state = if new_amount <= self.buffer_zone {
-            OverdraftState::BufferZone
-        } else if new_amount <= self.overdraft_limit {
-            OverdraftState::Active
-        } else {
-            OverdraftState::Exceeded
-        };
+        let previous_amount = self.facility.state.outstanding_principal;
+        self.facility.state.outstanding_principal = new_amount;
+        self.record_balance_change(time_provider.now());
+        
+        self.refresh_state();
         
         // emit appropriate event
         if new_amount > previous_amount {
@@ -200,7 +995,7 @@ impl OverdraftFacility {
                 new_total: new_amount,
                 timestamp: time_provider.now(),
             });
-        } else {
+        } else if new_amount < previous_amount {
             // this is a partial repayment
             let repayment = previous_amount - new_amount;
             self.facility.state.record_payment(repayment, time_provider.now());
@@ -218,8 +1013,22 @@ impl OverdraftFacility {
         Ok(())
     }
     
+    /// note a change in overdrawn principal for the next accrual run
+    fn record_balance_change(&mut self, at: DateTime<Utc>) {
+        let principal = self.facility.state.outstanding_principal;
+        
+        // nothing is accruing, so the accrual clock starts now
+        if self.accrual_base.is_zero() && self.balance_changes.is_empty() {
+            self.facility.state.last_interest_accrual = at;
+            self.accrual_base = principal;
+            return;
+        }
+        
+        self.balance_changes.push((at, principal));
+    }
+    
     /// accrue interest using stored time
-    pub fn accrue_interest(&mut self) -> Result<()> {
+    pub fn accrue_interest(&mut self) -> Result<Vec<DailyAccrual>> {
         let time_ptr = self.time
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "Time provider not set. Call set_time() first".to_string(),
@@ -231,34 +1040,93 @@ impl OverdraftFacility {
         self.accrue_interest_with_time(time)
     }
     
-    /// accrue interest with continuous compounding with explicit time
-    pub fn accrue_interest_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
-        if !self.is_active || self.facility.state.outstanding_principal.is_zero() {
-            return Ok(());
+    /// accrue continuously compounded interest for every whole day since the
+    /// last accrual with explicit time
+    ///
+    /// each day is split at balance changes so every interval accrues on the
+    /// balance in force during it, less any interest-free buffer; a trailing
+    /// partial day is left for the next run
+    pub fn accrue_interest_with_time(
+        &mut self,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Vec<DailyAccrual>> {
+        let mut accruals = Vec::new();
+        if self.accrual_base.is_zero() && self.balance_changes.is_empty() {
+            return Ok(accruals);
         }
         
-        let principal = self.facility.state.outstanding_principal;
+        let engine = AccrualEngine::new(self.facility.config.interest_config.day_count_convention);
         let rate = self.facility.config.financial_terms.interest_rate;
+        let last_accrual = self.facility.state.last_interest_accrual;
+        let days = engine.calculate_days(last_accrual, time_provider.now());
+        
+        let interest_free = self.fee_schedule()
+            .map(|schedule| schedule.interest_free_buffer)
+            .unwrap_or(Money::ZERO);
+        let seconds_per_day = Decimal::from(86_400);
+        let mut balance = self.accrual_base;
+        let mut applied = 0;
+        let mut day_start = last_accrual;
+        
+        for _ in 0..days {
+            let day_end = day_start + chrono::Duration::days(1);
+            let year_basis = engine.year_basis(day_end.year());
+            let daily_rate = rate.as_decimal() / Decimal::from(year_basis);
+            
+            // continuous compounding: interest = P * (e^(r * t) - 1)
+            let mut interest = Decimal::ZERO;
+            let mut weighted_balance = Decimal::ZERO;
+            let mut cursor = day_start;
+            loop {
+                let next_change = self.balance_changes.get(applied)
+                    .filter(|(at, _)| *at <= day_end)
+                    .copied();
+                let interval_end = next_change.map(|(at, _)| at.max(cursor)).unwrap_or(day_end);
+                
+                // only the amount past the interest-free buffer accrues
+                let chargeable = (balance - interest_free).max(Money::ZERO).as_decimal();
+                let fraction = Decimal::from((interval_end - cursor).num_seconds()) / seconds_per_day;
+                interest += chargeable * (exp(daily_rate * fraction) - Decimal::ONE);
+                weighted_balance += chargeable * fraction;
+                cursor = interval_end;
+                
+                match next_change {
+                    Some((_, new_balance)) => {
+                        balance = new_balance;
+                        applied += 1;
+                    }
+                    None => break,
+                }
+            }
+            
+            day_start = day_end;
+            if weighted_balance.is_zero() {
+                continue;
+            }
+            
+            accruals.push(DailyAccrual {
+                date: day_end,
+                principal_base: Money::from_decimal(weighted_balance),
+                interest_amount: Money::from_decimal(interest),
+                daily_rate: Rate::from_decimal(daily_rate),
+            });
+        }
         
-        // continuous compounding: A = P * e^(rt)
-        // for daily: interest = P * (e^(r/365) - 1)
-        let daily_rate = rate.as_decimal() / dec!(365);
-        
-        // approximate e^x for small x: e^x ≈ 1 + x + x²/2
-        // for daily rates this is very accurate
-        let e_power = dec!(1) + daily_rate + (daily_rate * daily_rate) / dec!(2);
-        let interest = principal.as_decimal() * (e_power - dec!(1));
-        
-        self.facility.state.accrued_interest += Money::from_decimal(interest);
+        self.balance_changes.drain(..applied);
+        self.accrual_base = balance;
+        self.facility.state.last_interest_accrual = day_start;
         
-        // emit interest event
-        self.facility.events.emit(Event::InterestAccrued {
-            facility_id: self.facility.id,
-            amount: Money::from_decimal(interest),
-            timestamp: time_provider.now(),
-        });
+        for accrual in &accruals {
+            self.facility.state.accrued_interest += accrual.interest_amount;
+            
+            self.facility.events.emit(Event::InterestAccrued {
+                facility_id: self.facility.id,
+                amount: accrual.interest_amount,
+                timestamp: accrual.date,
+            });
+        }
         
-        Ok(())
+        Ok(accruals)
     }
     
     /// apply daily fees using stored time
@@ -274,69 +1142,115 @@ impl OverdraftFacility {
         self.apply_daily_fees_with_time(time)
     }
     
-    /// apply daily fees if applicable with explicit time
-    pub fn apply_daily_fees_with_time(&mut self, _time_provider: &SafeTimeProvider) -> Result<()> {
+    /// apply the daily and sustained overdraft fees with explicit time
+    ///
+    /// the daily fee is charged at most once per calendar day
+    pub fn apply_daily_fees_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
         if !self.is_active {
             return Ok(());
         }
-        
-        let mut fee = Money::ZERO;
-        
-        // apply fee based on state
-        match self.state {
-            OverdraftState::Active => {
-                // fee on amount over buffer
-                if self.facility.state.outstanding_principal > self.buffer_zone {
-                    fee = self.daily_fee.unwrap_or(Money::from_major(5));
-                }
+        let schedule = match self.fee_schedule() {
+            Some(schedule) => schedule.clone(),
+            None => return Ok(()),
+        };
+        let now = time_provider.now();
+        
+        // no daily fee inside the fee-free buffer zone
+        let past_buffer = self.facility.state.outstanding_principal > self.buffer_zone;
+        if let Some(fee) = schedule.daily_fee {
+            if past_buffer && self.last_daily_fee != Some(now.date_naive()) {
+                self.last_daily_fee = Some(now.date_naive());
+                self.charge_fee(OverdraftFeeKind::Daily, fee, now);
             }
-            OverdraftState::Exceeded => {
-                // higher fee for exceeded
-                fee = Money::from_major(10);
-                
-                // also apply overlimit fee
-                if let Some(overlimit_fee) = self.facility.config.fee_config.overlimit_fee {
-                    fee += overlimit_fee;
-                }
+        }
+        
+        if let (Some(fee), Some(since)) = (schedule.sustained_fee, self.overdrawn_since) {
+            let days_overdrawn = (now - since).num_days();
+            if !self.sustained_fee_charged && days_overdrawn >= schedule.sustained_after_days as i64 {
+                self.sustained_fee_charged = true;
+                self.charge_fee(OverdraftFeeKind::Sustained, fee, now);
             }
-            _ => {}
         }
         
+        Ok(())
+    }
+    
+    /// fee schedule from the facility configuration
+    pub fn fee_schedule(&self) -> Option<&OverdraftFeeSchedule> {
+        self.facility.config.fee_config.overdraft_fees.as_ref()
+    }
+    
+    /// overdraft fees charged so far in the calendar month of `now`
+    pub fn fees_charged_this_month(&self, now: DateTime<Utc>) -> Money {
+        self.fee_usage.monthly_total(now)
+    }
+    
+    /// charge the paid item fee unless the item or the overdraft is too small
+    fn charge_paid_item_fee(&mut self, item_amount: Money, now: DateTime<Utc>) {
+        let (fee, de_minimis) = match self.fee_schedule() {
+            Some(schedule) => (schedule.paid_item_fee, schedule.de_minimis),
+            None => return,
+        };
+        let Some(fee) = fee else {
+            return;
+        };
+        
+        if item_amount <= de_minimis || self.facility.state.outstanding_principal <= self.buffer_zone {
+            return;
+        }
+        self.charge_fee(OverdraftFeeKind::PaidItem, fee, now);
+    }
+    
+    /// charge an overdraft fee within the daily and monthly caps
+    fn charge_fee(&mut self, kind: OverdraftFeeKind, scheduled_fee: Money, now: DateTime<Utc>) -> Money {
+        let fee = match self.fee_schedule() {
+            Some(schedule) => schedule.capped_fee(scheduled_fee, &self.fee_usage, now),
+            None => scheduled_fee,
+        };
+        
         if fee > Money::ZERO {
+            self.fee_usage.record(fee, now);
             self.facility.state.accrued_fees += fee;
             self.facility.state.total_fees_charged += fee;
         }
         
-        Ok(())
+        self.facility.events.emit(Event::OverdraftFeeCharged {
+            facility_id: self.facility.id,
+            kind,
+            scheduled_fee,
+            fee,
+            timestamp: now,
+        });
+        
+        fee
     }
     
     /// get effective balance (account + available overdraft)
     pub fn effective_balance(&self) -> Money {
         if self.linked_account_balance >= Money::ZERO {
-            self.linked_account_balance + self.overdraft_limit
+            self.linked_account_balance + self.available_limit()
         } else {
-            self.overdraft_limit - self.linked_account_balance.abs()
+            self.available_limit() - self.linked_account_balance.abs()
         }
     }
     
     /// get available funds
     pub fn available_funds(&self) -> Money {
         if self.linked_account_balance >= Money::ZERO {
-            self.linked_account_balance + self.overdraft_limit
+            self.linked_account_balance + self.available_limit()
         } else {
             let used = self.linked_account_balance.abs();
-            if used < self.overdraft_limit {
-                self.overdraft_limit - used
+            if used < self.available_limit() {
+                self.available_limit() - used
             } else {
                 Money::ZERO
             }
         }
     }
     
-    /// check if can process transaction
+    /// check if a debit of `amount` would be paid
     pub fn can_process_transaction(&self, amount: Money) -> bool {
-        let new_balance = self.linked_account_balance - amount;
-        new_balance >= Money::ZERO - self.overdraft_limit
+        self.decide_transaction(Money::ZERO - amount) == OverdraftDecision::Pay
     }
     
     /// get facility reference
@@ -351,15 +1265,24 @@ impl OverdraftFacility {
     
     /// get json representation of current state
     pub fn to_json_pretty(&self) -> String {
-        use super::serialization::{FacilityView, OverdraftView};
+        use super::serialization::{FacilityView, OverdraftView, RepaymentPlanView};
         
         let view = OverdraftView {
             facility: FacilityView::from_facility(&self.facility),
             overdraft_limit: self.overdraft_limit,
             buffer_zone: self.buffer_zone,
+            linked_account_id: self.linked_account_id.clone(),
             linked_account_balance: self.linked_account_balance,
             is_active: self.is_active,
             available_funds: self.available_funds(),
+            expiry_date: self.expiry_date,
+            scheduled_withdrawal: self.scheduled_withdrawal(),
+            repayment_plan: self.repayment_plan.as_ref().map(|plan| RepaymentPlanView {
+                principal: plan.principal,
+                term_months: plan.term_months,
+                monthly_payment: plan.payments.first().map_or(Money::ZERO, |p| p.payment_amount),
+                installments_paid: self.facility.state.installments_paid(),
+            }),
         };
         
         serde_json::to_string_pretty(&view).unwrap_or_else(|e| format!("JSON error: {}", e))
@@ -437,11 +1360,22 @@ pub struct OverdraftBuilder {
     buffer_zone: Option<Money>,
     linked_account_id: Option<String>,
     daily_fee: Option<Money>,
+    fee_schedule: Option<OverdraftFeeSchedule>,
+    decision_policy: Option<OverdraftDecisionPolicy>,
+    linked_accounts: Vec<LinkedAccount>,
+    deposit_waterfall: Option<PaymentWaterfall>,
+    review_policy: Option<OverdraftReviewPolicy>,
     account_number: Option<String>,
     customer_id: Option<String>,
     time_provider: Option<*const SafeTimeProvider>,
 }
 
+impl Default for OverdraftBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OverdraftBuilder {
     pub fn new() -> Self {
         Self {
@@ -450,6 +1384,11 @@ impl OverdraftBuilder {
             buffer_zone: None,
             linked_account_id: None,
             daily_fee: None,
+            fee_schedule: None,
+            decision_policy: None,
+            linked_accounts: Vec::new(),
+            deposit_waterfall: None,
+            review_policy: None,
             account_number: None,
             customer_id: None,
             time_provider: None,
@@ -481,11 +1420,39 @@ impl OverdraftBuilder {
         self
     }
     
+    /// daily fee, overriding the one in the fee schedule
     pub fn daily_fee(mut self, fee: Money) -> Self {
         self.daily_fee = Some(fee);
         self
     }
     
+    pub fn fee_schedule(mut self, schedule: OverdraftFeeSchedule) -> Self {
+        self.fee_schedule = Some(schedule);
+        self
+    }
+    
+    pub fn decision_policy(mut self, policy: OverdraftDecisionPolicy) -> Self {
+        self.decision_policy = Some(policy);
+        self
+    }
+    
+    /// link an account to sweep, in the order added
+    pub fn linked_account(mut self, account: LinkedAccount) -> Self {
+        self.linked_accounts.push(account);
+        self
+    }
+    
+    pub fn deposit_waterfall(mut self, waterfall: PaymentWaterfall) -> Self {
+        self.deposit_waterfall = Some(waterfall);
+        self
+    }
+    
+    /// grant the limit for a fixed term, reviewed under this policy
+    pub fn review_policy(mut self, policy: OverdraftReviewPolicy) -> Self {
+        self.review_policy = Some(policy);
+        self
+    }
+    
     pub fn account_number(mut self, account: String) -> Self {
         self.account_number = Some(account);
         self
@@ -532,13 +1499,25 @@ impl OverdraftBuilder {
             message: "Linked account ID required".to_string(),
         })?;
         
-        let config = FacilityConfig::overdraft(
+        let mut config = FacilityConfig::overdraft(
             overdraft_limit,
             rate,
             buffer_zone,
             linked_account_id.clone(),
         );
         
+        let mut fee_schedule = self.fee_schedule.unwrap_or_default();
+        if let Some(daily_fee) = self.daily_fee {
+            fee_schedule.daily_fee = Some(daily_fee);
+        }
+        config.fee_config.overdraft_fees = Some(fee_schedule);
+        if let Some(policy) = self.decision_policy {
+            config.limits.overdraft_decisioning = Some(policy);
+        }
+        if let Some(policy) = self.review_policy {
+            config.limits.overdraft_review = Some(policy);
+        }
+        
         let account_number = self.account_number.unwrap_or_else(|| {
             format!("OD-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
         });
@@ -550,7 +1529,10 @@ impl OverdraftBuilder {
         let facility = Facility::originate(config, account_number, customer_id, time_provider)?;
         
         let mut overdraft = OverdraftFacility::new(facility, buffer_zone, linked_account_id)?;
-        overdraft.daily_fee = self.daily_fee;
+        overdraft.linked_accounts = self.linked_accounts;
+        if let Some(waterfall) = self.deposit_waterfall {
+            overdraft.deposit_waterfall = waterfall;
+        }
         
         // If time was set in builder, pass it to the facility
         if let Some(time_ptr) = self.time_provider {
@@ -566,6 +1548,8 @@ mod tests {
     use super::*;
     use hourglass_rs::TimeSource;
     use chrono::{TimeZone, Utc};
+    use rust_decimal_macros::dec;
+    use crate::payments::WaterfallComponent;
     
     #[test]
     fn test_overdraft_activation() {
@@ -653,6 +1637,462 @@ mod tests {
         assert!(overdraft.facility.state.accrued_interest > total_simple);
     }
     
+    #[test]
+    fn test_interest_accrues_over_elapsed_days_and_balance_changes() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(1000))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // 500 overdrawn for 4 days, then 800 from noon on day 5 until cleared
+        // at noon on day 9
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(500), &time).unwrap();
+        control.advance(chrono::Duration::hours(4 * 24 + 12));
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(300), &time).unwrap();
+        control.advance(chrono::Duration::days(4));
+        overdraft.process_account_transaction_with_time(Money::from_major(800), &time).unwrap();
+        control.advance(chrono::Duration::hours(12 + 24 * 5 + 6));
+        
+        // one call after a 14 day gap covers every day
+        let accruals = overdraft.accrue_interest_with_time(&time).unwrap();
+        assert_eq!(accruals.len(), 9);
+        assert_eq!(accruals[0].principal_base, Money::from_major(500));
+        assert_eq!(accruals[4].principal_base, Money::from_major(650));
+        assert_eq!(accruals[5].principal_base, Money::from_major(800));
+        assert_eq!(accruals[8].principal_base, Money::from_major(400));
+        
+        let daily_rate = dec!(0.2) / dec!(365);
+        let full_day = |p: i64| Decimal::from(p) * (exp(daily_rate) - Decimal::ONE);
+        let half_day = |p: i64| Decimal::from(p) * (exp(daily_rate / dec!(2)) - Decimal::ONE);
+        assert_eq!(accruals[0].interest_amount, Money::from_decimal(full_day(500)));
+        assert_eq!(accruals[4].interest_amount, Money::from_decimal(half_day(500) + half_day(800)));
+        assert_eq!(accruals[8].interest_amount, Money::from_decimal(half_day(800)));
+        
+        let total = accruals.iter().fold(Money::ZERO, |sum, a| sum + a.interest_amount);
+        assert_eq!(overdraft.facility.state.accrued_interest, total);
+        
+        // nothing more accrues once the overdraft is cleared
+        control.advance(chrono::Duration::days(3));
+        assert!(overdraft.accrue_interest_with_time(&time).unwrap().is_empty());
+        assert_eq!(overdraft.facility.state.accrued_interest, total);
+    }
+    
+    #[test]
+    fn test_overdraft_fee_schedule_with_caps() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let schedule = OverdraftFeeSchedule {
+            paid_item_fee: Some(Money::from_major(30)),
+            returned_item_fee: Some(Money::from_major(25)),
+            daily_fee: None,
+            sustained_fee: Some(Money::from_major(20)),
+            sustained_after_days: 5,
+            daily_fee_cap: Some(Money::from_major(70)),
+            monthly_fee_cap: Some(Money::from_major(100)),
+            de_minimis: Money::from_major(5),
+            interest_free_buffer: Money::ZERO,
+        };
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(1000))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .fee_schedule(schedule)
+            .daily_fee(Money::from_major(10))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // inside the buffer zone and under the de minimis: no item fees
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(40), &time).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(20), &time).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(3), &time).unwrap();
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(30));
+        
+        // two more items, the second trimmed by the daily cap
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(100), &time).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(100), &time).unwrap();
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(70));
+        
+        // the builder daily fee overrides the schedule, once per day
+        control.advance(chrono::Duration::days(1));
+        overdraft.apply_daily_fees_with_time(&time).unwrap();
+        overdraft.apply_daily_fees_with_time(&time).unwrap();
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(80));
+        
+        // a returned item is charged even though the debit is refused
+        let result = overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(900), &time);
+        assert!(result.is_err());
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(100));
+        
+        // the sustained fee is due after five days but the monthly cap is used up
+        control.advance(chrono::Duration::days(4));
+        overdraft.apply_daily_fees_with_time(&time).unwrap();
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(100));
+        assert!(overdraft.sustained_fee_charged);
+        assert_eq!(overdraft.fees_charged_this_month(time.now()), Money::from_major(100));
+    }
+    
+    #[test]
+    fn test_interest_free_buffer() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(1000))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .fee_schedule(OverdraftFeeSchedule {
+                interest_free_buffer: Money::from_major(100),
+                ..OverdraftFeeSchedule::default()
+            })
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // fully inside the buffer
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(80), &time).unwrap();
+        control.advance(chrono::Duration::days(2));
+        assert!(overdraft.accrue_interest_with_time(&time).unwrap().is_empty());
+        
+        // only the 200 past the buffer accrues
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(220), &time).unwrap();
+        control.advance(chrono::Duration::days(1));
+        let accruals = overdraft.accrue_interest_with_time(&time).unwrap();
+        assert_eq!(accruals.len(), 1);
+        assert_eq!(accruals[0].principal_base, Money::from_major(200));
+        let expected = dec!(200) * (exp(dec!(0.2) / dec!(365)) - Decimal::ONE);
+        assert_eq!(accruals[0].interest_amount, Money::from_decimal(expected));
+    }
+    
+    #[test]
+    fn test_item_decisioning_and_returned_items() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(500))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .fee_schedule(OverdraftFeeSchedule {
+                returned_item_fee: Some(Money::from_major(25)),
+                daily_fee: None,
+                ..OverdraftFeeSchedule::default()
+            })
+            .decision_policy(OverdraftDecisionPolicy {
+                require_opt_in: true,
+                overlimit_tolerance: Money::from_major(50),
+                max_paid_item: Some(Money::from_major(300)),
+                ..OverdraftDecisionPolicy::default()
+            })
+            .set_time(&time)
+            .build()
+            .unwrap();
+        overdraft.process_account_transaction_with_time(Money::from_major(100), &time).unwrap();
+        
+        // debits covered by the balance are always paid
+        assert_eq!(overdraft.decide_transaction(Money::ZERO - Money::from_major(100)), OverdraftDecision::Pay);
+        
+        // no opt-in: returned with an nsf fee
+        let result = overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(150), &time);
+        assert!(matches!(result, Err(FacilityError::OverdraftItemReturned { reason: OverdraftReturnReason::NotOptedIn, .. })));
+        assert_eq!(overdraft.linked_account_balance, Money::from_major(100));
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(25));
+        
+        overdraft.set_overdraft_opt_in(true);
+        assert!(!overdraft.can_process_transaction(Money::from_major(400)));
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(300), &time).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(280), &time).unwrap();
+        
+        // paid past the limit within the tolerance
+        assert_eq!(overdraft.facility.state.outstanding_principal, Money::from_major(480));
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(60), &time).unwrap();
+        assert_eq!(overdraft.state(), OverdraftState::Exceeded);
+        
+        let result = overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(20), &time);
+        assert!(matches!(result, Err(FacilityError::OverdraftLimitExceeded { .. })));
+        
+        let reasons: Vec<_> = overdraft.returned_items().iter().map(|item| item.reason).collect();
+        assert_eq!(reasons, vec![OverdraftReturnReason::NotOptedIn, OverdraftReturnReason::LimitExceeded]);
+        
+        let events = overdraft.facility.events.events();
+        let paid = events.iter().filter(|e| matches!(e, Event::OverdraftItemPaid { .. })).count();
+        let returned = events.iter().filter(|e| matches!(e, Event::OverdraftItemReturned { .. })).count();
+        assert_eq!((paid, returned), (3, 2));
+    }
+    
+    #[test]
+    fn test_suspension_and_reinstatement() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(500))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .decision_policy(OverdraftDecisionPolicy {
+                suspend_after_excess_items: Some(2),
+                suspend_after_negative_days: Some(30),
+                ..OverdraftDecisionPolicy::default()
+            })
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // two items over the limit in the window suspend the line
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(450), &time).unwrap();
+        assert!(overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(100), &time).is_err());
+        assert!(!overdraft.is_suspended());
+        assert!(overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(80), &time).is_err());
+        assert!(overdraft.is_suspended());
+        assert_eq!(overdraft.state(), OverdraftState::Suspended);
+        assert_eq!(overdraft.available_funds(), Money::ZERO);
+        
+        // suspended lines return any further overdrawing debit
+        let result = overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(10), &time);
+        assert!(matches!(result, Err(FacilityError::OverdraftItemReturned { reason: OverdraftReturnReason::Suspended, .. })));
+        
+        // deposits are still accepted and the state stays suspended
+        overdraft.process_account_transaction_with_time(Money::from_major(500), &time).unwrap();
+        assert_eq!(overdraft.state(), OverdraftState::Suspended);
+        
+        overdraft.reinstate_with_time(&time).unwrap();
+        assert_eq!(overdraft.state(), OverdraftState::Available);
+        assert!(overdraft.reinstate_with_time(&time).is_err());
+        
+        // an extended negative balance suspends again
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(200), &time).unwrap();
+        control.advance(chrono::Duration::days(29));
+        assert!(!overdraft.review_suspension_with_time(&time));
+        control.advance(chrono::Duration::days(1));
+        assert!(overdraft.review_suspension_with_time(&time));
+        assert!(overdraft.facility.events.events().iter().any(|e| matches!(
+            e,
+            Event::OverdraftSuspended { reason, .. } if reason == "Extended negative balance"
+        )));
+    }
+    
+    #[test]
+    fn test_linked_account_sweeps() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(500))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .linked_account(
+                LinkedAccount::savings("SAV-1".to_string(), Money::from_major(120))
+                    .transfer_fee(Money::from_major(10))
+                    .transfer_increment(Money::from_major(50))
+            )
+            .linked_account(LinkedAccount::credit_line("LOC-1".to_string(), Money::from_major(1000)))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // savings covers 100 in whole increments, the line covers the rest exactly
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(130), &time).unwrap();
+        assert_eq!(overdraft.linked_account_balance, Money::ZERO);
+        assert!(!overdraft.is_active);
+        assert_eq!(overdraft.linked_accounts()[0].available, Money::from_major(20));
+        assert_eq!(overdraft.linked_accounts()[1].available, Money::from_major(970));
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(10));
+        
+        let sweeps = overdraft.facility.events.events().iter()
+            .filter(|e| matches!(e, Event::OverdraftProtectionSweep { .. }))
+            .count();
+        assert_eq!(sweeps, 2);
+        
+        // savings cannot make a whole increment, the line is used up next
+        overdraft.set_linked_account_available("LOC-1", Money::ZERO).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(40), &time).unwrap();
+        assert!(overdraft.is_active);
+        assert_eq!(overdraft.facility.state.outstanding_principal, Money::from_major(40));
+        assert!(overdraft.set_linked_account_available("SAV-9", Money::ZERO).is_err());
+    }
+    
+    #[test]
+    fn test_deposit_waterfall() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(500))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(200), &time).unwrap();
+        overdraft.facility.state.accrued_fees = Money::from_major(30);
+        overdraft.facility.state.accrued_interest = Money::from_major(5);
+        
+        // fees, then interest, then principal
+        overdraft.process_account_transaction_with_time(Money::from_major(100), &time).unwrap();
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::ZERO);
+        assert_eq!(overdraft.facility.state.accrued_interest, Money::ZERO);
+        assert_eq!(overdraft.linked_account_balance, Money::ZERO - Money::from_major(135));
+        
+        // principal first leaves the fees owing
+        overdraft.set_deposit_waterfall(PaymentWaterfall::new(vec![
+            WaterfallComponent::Principal,
+            WaterfallComponent::Fees,
+            WaterfallComponent::Penalties,
+            WaterfallComponent::Interest,
+        ]));
+        overdraft.facility.state.accrued_fees = Money::from_major(30);
+        overdraft.process_account_transaction_with_time(Money::from_major(150), &time).unwrap();
+        assert!(!overdraft.is_active);
+        assert_eq!(overdraft.facility.state.accrued_fees, Money::from_major(15));
+        assert_eq!(overdraft.linked_account_balance, Money::ZERO);
+    }
+    
+    #[test]
+    fn test_limit_review_renews_and_reduces() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(1000))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .review_policy(OverdraftReviewPolicy::default())
+            .set_time(&time)
+            .build()
+            .unwrap();
+        let first_expiry = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(overdraft.expiry_date(), Some(first_expiry));
+        
+        // not due before the term ends
+        control.advance(chrono::Duration::days(100));
+        assert!(overdraft.review_limit_with_time(&time).unwrap().is_none());
+        
+        // occasional use and regular deposits: renewed for another term
+        overdraft.process_account_transaction_with_time(Money::from_major(3000), &time).unwrap();
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(3200), &time).unwrap();
+        control.advance(chrono::Duration::days(10));
+        overdraft.process_account_transaction_with_time(Money::from_major(500), &time).unwrap();
+        control.set(first_expiry);
+        
+        let review = overdraft.review_limit_with_time(&time).unwrap().unwrap();
+        assert_eq!(review.decision, OverdraftReviewDecision::Renew);
+        assert_eq!(review.days_overdrawn, 10);
+        assert_eq!(review.deposit_inflows, Money::from_major(3500));
+        let second_expiry = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(overdraft.expiry_date(), Some(second_expiry));
+        
+        // overdrawn by 900 for over half the year: limit cut by a quarter
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(1200), &time).unwrap();
+        control.advance(chrono::Duration::days(200));
+        overdraft.process_account_transaction_with_time(Money::from_major(1000), &time).unwrap();
+        control.set(second_expiry);
+        
+        let review = overdraft.review_limit_with_time(&time).unwrap().unwrap();
+        assert_eq!(review.days_overdrawn, 200);
+        assert_eq!(review.decision, OverdraftReviewDecision::Reduce { new_limit: Money::from_major(750) });
+        assert_eq!(overdraft.overdraft_limit(), Money::from_major(750));
+        assert!(overdraft.facility.events.events().iter().any(|e| matches!(
+            e,
+            Event::CreditLimitChanged { reason: CreditLimitChangeReason::PersistentUse, .. }
+        )));
+    }
+    
+    #[test]
+    fn test_limit_withdrawal_creates_repayment_plan() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut overdraft = OverdraftBuilder::new()
+            .overdraft_limit(Money::from_major(1000))
+            .rate(Rate::from_percentage(20))
+            .buffer_zone(Money::from_major(50))
+            .linked_account_id("ACC-123".to_string())
+            .review_policy(OverdraftReviewPolicy {
+                term_months: 6,
+                ..OverdraftReviewPolicy::default()
+            })
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        // overdrawn with no deposits for the whole term
+        overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(600), &time).unwrap();
+        control.set(Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap());
+        
+        let review = overdraft.review_limit_with_time(&time).unwrap().unwrap();
+        let effective_date = Utc.with_ymd_and_hms(2024, 7, 31, 0, 0, 0).unwrap();
+        assert_eq!(review.decision, OverdraftReviewDecision::Withdraw {
+            reason: CreditLimitChangeReason::LowDeposits,
+            effective_date,
+        });
+        assert_eq!(overdraft.scheduled_withdrawal(), Some(effective_date));
+        assert_eq!(overdraft.overdraft_limit(), Money::from_major(1000));
+        
+        // the limit goes once the notice period ends
+        control.set(effective_date);
+        assert!(overdraft.review_limit_with_time(&time).unwrap().is_none());
+        assert_eq!(overdraft.overdraft_limit(), Money::ZERO);
+        assert_eq!(overdraft.expiry_date(), None);
+        
+        let plan = overdraft.repayment_plan().unwrap().clone();
+        assert_eq!(plan.principal, Money::from_major(600));
+        assert_eq!(plan.term_months, 12);
+        assert_eq!(overdraft.facility.state.next_payment_amount, Some(plan.payments[0].payment_amount));
+        
+        // no further overdraft use
+        assert!(overdraft.process_account_transaction_with_time(Money::ZERO - Money::from_major(10), &time).is_err());
+        
+        // the plan's installments are what is due
+        let payments = plan.payments;
+        let state = &overdraft.facility.state;
+        assert_eq!(state.installments.len(), 12);
+        assert_eq!(state.minimum_payment_due, Some(payments[0].payment_amount));
+        assert_eq!(state.next_payment_due, Some(payments[0].payment_date));
+        
+        // deposits pay the plan down installment by installment
+        control.set(payments[0].payment_date);
+        overdraft.process_account_transaction_with_time(payments[0].payment_amount, &time).unwrap();
+        let state = &overdraft.facility.state;
+        assert_eq!(state.installments_paid(), 1);
+        assert_eq!(state.next_payment_due, Some(payments[1].payment_date));
+        assert_eq!(state.minimum_payment_due, Some(payments[1].payment_amount));
+        
+        let json = overdraft.to_json_pretty();
+        assert!(json.contains("\"installments_paid\": 1"));
+        assert!(json.contains("\"scheduled_withdrawal\": null"));
+    }
+    
     #[test]
     fn test_overdraft_clearing() {
         let time = SafeTimeProvider::new(TimeSource::Test(
@@ -1,17 +1,44 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Months, Utc};
 use hourglass_rs::SafeTimeProvider;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
-use crate::config::{FacilityConfig, FacilityType};
-use crate::types::RevolvingType;
+use crate::config::{
+    CommitmentFeeSchedule, CreditLineReviewPolicy, FacilityConfig, FacilityType, FeeBillingFrequency,
+    LimitDecreaseHandling, OverlimitFeeFrequency, OverlimitPolicy, UtilizationFeeTier,
+};
+use crate::types::{
+    AmortizationMethod, BalanceSegment, CreditLimitChangeReason, DrawType, RevolvingType,
+    TransactionFeeKind,
+};
 use crate::decimal::{Money, Rate};
 use crate::errors::{FacilityError, Result};
 use crate::events::Event;
 use crate::facility::Facility;
+use crate::interest::{AccrualEngine, AverageDailyBalanceEngine, DailyAccrual, AverageDailyBalanceResult, BalanceChange, InterestMethod};
+use crate::payments::{AmortizationSchedule, ScheduledPayment};
 use crate::types::FacilityStatus;
 
+/// segments in payment allocation order when rates are equal
+const SEGMENTS: [BalanceSegment; 5] = [
+    BalanceSegment::Penalty,
+    BalanceSegment::CashAdvance,
+    BalanceSegment::Purchase,
+    BalanceSegment::BalanceTransfer,
+    BalanceSegment::Promotional,
+];
+
+/// days from statement cut to payment due date
+const PAYMENT_DUE_DAYS: i64 = 25;
+
+/// projection horizon for the payoff disclosure
+const MAX_PAYOFF_MONTHS: u32 = 1_200;
+
+/// default days before an uncaptured authorization expires
+const DEFAULT_AUTHORIZATION_EXPIRY_DAYS: u32 = 7;
+
 /// utilization states for revolving facilities
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UtilizationState {
@@ -23,6 +50,136 @@ pub enum UtilizationState {
     Overlimit,   // > 100%
 }
 
+/// pending authorization hold against a revolving line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authorization {
+    pub id: Uuid,
+    pub amount: Money,
+    pub draw_type: DrawType,
+    pub authorized_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// payoff disclosure for paying only the minimum
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoffDisclosure {
+    /// months to pay off with minimum payments, none if the minimum never amortizes
+    pub months_to_payoff: Option<u32>,
+    pub total_interest: Money,
+    pub total_paid: Money,
+    /// monthly payment that pays off the balance in 36 months
+    pub payment_for_36_months: Money,
+    pub total_paid_36_months: Money,
+}
+
+/// pricing of an installment plan
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstallmentPricing {
+    /// fixed plan apr amortized into the installments
+    Apr(Rate),
+    /// fixed fee billed with each installment, principal split evenly
+    MonthlyFee(Money),
+}
+
+impl InstallmentPricing {
+    /// rate used to amortize the plan principal
+    pub fn rate(&self) -> Rate {
+        match self {
+            InstallmentPricing::Apr(rate) => *rate,
+            InstallmentPricing::MonthlyFee(_) => Rate::from_percentage(0),
+        }
+    }
+}
+
+/// lifecycle of an installment plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallmentPlanStatus {
+    Active,
+    PaidOff,
+    Cancelled,
+}
+
+/// fixed installment plan carved out of the revolving balance
+#[derive(Debug, Clone)]
+pub struct InstallmentPlan {
+    pub id: Uuid,
+    /// segment the plan was carved from and bills back into
+    pub segment: BalanceSegment,
+    pub amount: Money,
+    pub pricing: InstallmentPricing,
+    pub term_months: u32,
+    pub created_at: DateTime<Utc>,
+    pub status: InstallmentPlanStatus,
+    /// principal not yet billed to a statement
+    pub remaining_principal: Money,
+    pub installments_billed: u32,
+    pub schedule: AmortizationSchedule,
+    /// installments billed before the current schedule was generated
+    schedule_offset: u32,
+}
+
+impl InstallmentPlan {
+    /// next installment on the current schedule
+    pub fn next_installment(&self) -> Option<&ScheduledPayment> {
+        if self.status != InstallmentPlanStatus::Active {
+            return None;
+        }
+        self.schedule.get_payment(self.installments_billed - self.schedule_offset + 1)
+    }
+    
+    /// next installment including any fixed monthly fee
+    pub fn installment_amount(&self) -> Money {
+        self.next_installment()
+            .map(|p| p.principal_portion + self.charge_for(p))
+            .unwrap_or(Money::ZERO)
+    }
+    
+    /// installments left to bill
+    pub fn installments_remaining(&self) -> u32 {
+        self.term_months.saturating_sub(self.installments_billed)
+    }
+    
+    /// interest or fee billed with a scheduled installment
+    fn charge_for(&self, scheduled: &ScheduledPayment) -> Money {
+        match self.pricing {
+            InstallmentPricing::Apr(_) => scheduled.interest_portion,
+            InstallmentPricing::MonthlyFee(fee) => fee,
+        }
+    }
+}
+
+/// outcome of a credit line review
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreditLineDecision {
+    pub reason: CreditLimitChangeReason,
+    pub old_limit: Money,
+    pub new_limit: Money,
+    pub reviewed_at: DateTime<Utc>,
+    /// decreases take effect after the notice period
+    pub effective_date: DateTime<Utc>,
+}
+
+impl CreditLineDecision {
+    pub fn is_increase(&self) -> bool {
+        self.new_limit > self.old_limit
+    }
+}
+
+/// billing statement produced at cycle cut
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub cycle_start: DateTime<Utc>,
+    pub statement_date: DateTime<Utc>,
+    pub statement_balance: Money,
+    pub interest_charged: Money,
+    pub annual_fee_charged: Money,
+    pub minimum_payment: Money,
+    pub payment_due_date: DateTime<Utc>,
+    /// average daily balance results per segment (empty for daily accrual)
+    pub segment_interest: HashMap<BalanceSegment, AverageDailyBalanceResult>,
+    pub payoff_disclosure: PayoffDisclosure,
+}
+
 /// revolving facility
 pub struct RevolvingFacility {
     facility: Facility,
@@ -33,6 +190,54 @@ pub struct RevolvingFacility {
     repayment_period_ends: Option<DateTime<Utc>>,
     is_in_draw_period: bool,
     minimum_payment_percentage: Decimal,
+    segment_rates: HashMap<BalanceSegment, Rate>,
+    balance_history: HashMap<BalanceSegment, Vec<BalanceChange>>,
+    segment_fees: HashMap<BalanceSegment, Money>,
+    cycle_start: Option<DateTime<Utc>>,
+    annual_fee_periods: u32,
+    last_annual_fee: Option<AnnualFeeCharge>,
+    closed_at: Option<DateTime<Utc>>,
+    authorizations: HashMap<Uuid, Authorization>,
+    authorization_expiry_days: u32,
+    amortization_schedule: Option<AmortizationSchedule>,
+    current_payment_number: u32,
+    commitment_accrual: Option<CommitmentFeeAccrual>,
+    penalty_apr_since: Option<DateTime<Utc>>,
+    on_time_payments: u32,
+    paid_toward_minimum: Money,
+    installment_plans: Vec<InstallmentPlan>,
+    installment_due: Money,
+    on_time_streak: u32,
+    utilization_history: Vec<(DateTime<Utc>, Rate)>,
+    delinquency_dates: Vec<DateTime<Utc>>,
+    overlimit_dates: Vec<DateTime<Utc>>,
+    next_credit_line_review: Option<DateTime<Utc>>,
+    credit_line_proposal: Option<CreditLineDecision>,
+    scheduled_limit_decrease: Option<CreditLineDecision>,
+    overlimit_opt_in: bool,
+    /// cycle in which the last overlimit fee was charged
+    overlimit_fee_cycle: Option<DateTime<Utc>>,
+    /// consecutive cycles with an overlimit fee
+    overlimit_fee_cycles: u32,
+}
+
+/// daily commitment fee accrual awaiting billing
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CommitmentFeeAccrual {
+    anchor: DateTime<Utc>,
+    accrued_through: DateTime<Utc>,
+    billed_periods: u32,
+    unbilled_fee: Decimal,
+    undrawn_sum: Decimal,
+    days: u32,
+}
+
+/// most recent annual fee charge, kept for refunds on closure
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AnnualFeeCharge {
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+    amount: Money,
 }
 
 impl RevolvingFacility {
@@ -64,6 +269,33 @@ impl RevolvingFacility {
             repayment_period_ends: None,
             is_in_draw_period: true,
             minimum_payment_percentage,
+            segment_rates: HashMap::new(),
+            balance_history: HashMap::new(),
+            segment_fees: HashMap::new(),
+            cycle_start: None,
+            annual_fee_periods: 0,
+            last_annual_fee: None,
+            closed_at: None,
+            authorizations: HashMap::new(),
+            authorization_expiry_days: DEFAULT_AUTHORIZATION_EXPIRY_DAYS,
+            amortization_schedule: None,
+            current_payment_number: 0,
+            commitment_accrual: None,
+            penalty_apr_since: None,
+            on_time_payments: 0,
+            paid_toward_minimum: Money::ZERO,
+            installment_plans: Vec::new(),
+            installment_due: Money::ZERO,
+            on_time_streak: 0,
+            utilization_history: Vec::new(),
+            delinquency_dates: Vec::new(),
+            overlimit_dates: Vec::new(),
+            next_credit_line_review: None,
+            credit_line_proposal: None,
+            scheduled_limit_decrease: None,
+            overlimit_opt_in: false,
+            overlimit_fee_cycle: None,
+            overlimit_fee_cycles: 0,
         })
     }
     
@@ -90,6 +322,19 @@ impl RevolvingFacility {
         self.draw_with_time(amount, time)
     }
     
+    /// draw funds of a given type using stored time
+    pub fn draw_with_type(&mut self, amount: Money, draw_type: DrawType) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.draw_with_type_with_time(amount, draw_type, time)
+    }
+    
     /// process payment using stored time
     pub fn process_payment(&mut self, amount: Money) -> Result<()> {
         let time_ptr = self.time
@@ -117,7 +362,7 @@ impl RevolvingFacility {
     }
     
     /// accrue interest using stored time
-    pub fn accrue_interest(&mut self) -> Result<Vec<crate::interest::DailyAccrual>> {
+    pub fn accrue_interest(&mut self) -> Result<Vec<DailyAccrual>> {
         let time_ptr = self.time
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "Time provider not set. Call set_time() first".to_string(),
@@ -126,7 +371,7 @@ impl RevolvingFacility {
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "Invalid time provider reference".to_string(),
             })?;
-        self.facility.accrue_interest(time)
+        self.accrue_interest_with_time(time)
     }
     
     /// update daily status using stored time
@@ -139,26 +384,250 @@ impl RevolvingFacility {
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "Invalid time provider reference".to_string(),
             })?;
-        self.facility.update_daily_status(time)
+        self.update_daily_status_with_time(time)
+    }
+    
+    /// process scheduled repayment using stored time
+    pub fn process_scheduled_payment(&mut self) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.process_scheduled_payment_with_time(time)
+    }
+    
+    /// cut statement using stored time
+    pub fn cut_statement(&mut self) -> Result<Statement> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.cut_statement_with_time(time)
+    }
+    
+    /// assess annual fee using stored time
+    pub fn assess_annual_fee(&mut self) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.assess_annual_fee_with_time(time)
+    }
+    
+    /// close the account using stored time
+    pub fn close_account(&mut self) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.close_account_with_time(time)
+    }
+    
+    /// authorize a transaction using stored time
+    pub fn authorize(&mut self, amount: Money, draw_type: DrawType) -> Result<Uuid> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.authorize_with_time(amount, draw_type, time)
+    }
+    
+    /// increase an authorization using stored time
+    pub fn increment_authorization(&mut self, id: Uuid, additional: Money) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.increment_authorization_with_time(id, additional, time)
+    }
+    
+    /// capture an authorization using stored time
+    pub fn capture_authorization(&mut self, id: Uuid, amount: Money) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.capture_authorization_with_time(id, amount, time)
+    }
+    
+    /// reverse an authorization using stored time
+    pub fn reverse_authorization(&mut self, id: Uuid) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.reverse_authorization_with_time(id, time)
+    }
+    
+    /// expire stale authorizations using stored time
+    pub fn expire_authorizations(&mut self) -> Result<Vec<Uuid>> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        Ok(self.expire_authorizations_with_time(time))
+    }
+    
+    /// create installment plan using stored time
+    pub fn create_installment_plan(&mut self, amount: Money, pricing: InstallmentPricing, term_months: u32) -> Result<Uuid> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.create_installment_plan_with_time(amount, pricing, term_months, time)
+    }
+    
+    /// draw into an installment plan using stored time
+    pub fn draw_installment_plan(&mut self, amount: Money, pricing: InstallmentPricing, term_months: u32) -> Result<Uuid> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.draw_installment_plan_with_time(amount, pricing, term_months, time)
+    }
+    
+    /// prepay installment plan using stored time
+    pub fn prepay_installment_plan(&mut self, id: Uuid, amount: Money) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.prepay_installment_plan_with_time(id, amount, time)
+    }
+    
+    /// cancel installment plan using stored time
+    pub fn cancel_installment_plan(&mut self, id: Uuid) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.cancel_installment_plan_with_time(id, time)
+    }
+    
+    /// review credit line using stored time
+    pub fn review_credit_line(&mut self) -> Result<Option<CreditLineDecision>> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.review_credit_line_with_time(time)
+    }
+    
+    /// accept credit line proposal using stored time
+    pub fn accept_credit_line_proposal(&mut self) -> Result<Option<CreditLineDecision>> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.accept_credit_line_proposal_with_time(time)
+    }
+    
+    /// accrue daily interest, adding the penalty apr differential where it applies
+    pub fn accrue_interest_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Vec<DailyAccrual>> {
+        let since = self.facility.state.last_interest_accrual;
+        let principal = self.revolving_principal();
+        let accruals = self.facility.accrue_interest_on(principal, time_provider)?;
+        self.accrue_penalty_differential(since, time_provider);
+        Ok(accruals)
+    }
+    
+    /// update daily status, ending the draw period and triggering penalty apr when due
+    pub fn update_daily_status_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        self.check_draw_period_with_time(time_provider)?;
+        self.apply_scheduled_limit_decrease(time_provider.now())?;
+        
+        let since = self.facility.state.last_interest_accrual;
+        let principal = self.revolving_principal();
+        self.facility.update_daily_status_on(principal, time_provider)?;
+        self.accrue_penalty_differential(since, time_provider);
+        
+        self.check_penalty_apr_with_time(time_provider);
+        Ok(())
     }
     
     /// activate the facility with explicit time
     pub fn activate_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
         self.facility.state.activation_date = Some(time_provider.now());
         self.facility.state.update_status(FacilityStatus::Active, time_provider.now());
+        self.cycle_start = Some(time_provider.now());
+        self.commitment_accrual = Some(CommitmentFeeAccrual {
+            anchor: time_provider.now(),
+            accrued_through: time_provider.now(),
+            billed_periods: 0,
+            unbilled_fee: Decimal::ZERO,
+            undrawn_sum: Decimal::ZERO,
+            days: 0,
+        });
         
-        // set up draw period for heloc
-        if let FacilityType::Revolving(RevolvingType::HELOC) = &self.facility.config.facility_type {
-            if let Some(term_months) = self.facility.config.financial_terms.term_months {
-                // assume first 60% is draw period
-                let draw_months = (term_months as f64 * 0.6) as i64;
-                self.draw_period_ends = Some(
-                    time_provider.now() + chrono::Duration::days(draw_months * 30)
-                );
-                self.repayment_period_ends = Some(
-                    time_provider.now() + chrono::Duration::days(term_months as i64 * 30)
-                );
-            }
+        if let Some(policy) = &self.facility.config.limits.credit_line_review {
+            self.next_credit_line_review = Some(add_months(time_provider.now(), policy.review_frequency_months)?);
+        }
+        
+        // set up draw and repayment periods
+        let terms = &self.facility.config.financial_terms;
+        if let (Some(draw_months), Some(term_months)) = (terms.draw_period_months, terms.term_months) {
+            self.draw_period_ends = Some(add_months(time_provider.now(), draw_months)?);
+            self.repayment_period_ends = Some(add_months(time_provider.now(), term_months)?);
         }
         
         // update state
@@ -180,55 +649,63 @@ impl RevolvingFacility {
         Ok(())
     }
     
-    /// draw funds from the facility with explicit time
+    /// draw funds from the facility with explicit time (booked as a purchase)
     pub fn draw_with_time(
         &mut self,
         amount: Money,
         time_provider: &SafeTimeProvider,
     ) -> Result<Money> {
-        // validate draw
-        if !self.is_in_draw_period {
-            return Err(FacilityError::DrawPeriodEnded);
-        }
-        
-        if amount <= Money::ZERO {
-            return Err(FacilityError::InvalidDrawAmount { amount });
-        }
+        self.draw_with_type_with_time(amount, DrawType::Purchase, time_provider)
+    }
+    
+    /// draw funds of a given type with explicit time
+    pub fn draw_with_type_with_time(
+        &mut self,
+        amount: Money,
+        draw_type: DrawType,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Money> {
+        self.check_draw_period_with_time(time_provider)?;
+        self.expire_authorizations_with_time(time_provider);
+        self.validate_draw(amount)?;
+        self.check_transaction_limits(amount, time_provider.now())?;
+        self.post_draw(amount, draw_type, time_provider)
+    }
+    
+    /// post a validated draw to its segment, charging overlimit and transaction fees
+    fn post_draw(
+        &mut self,
+        amount: Money,
+        draw_type: DrawType,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Money> {
+        self.accrue_commitment_fee_until(time_provider.now());
         
-        // check minimum drawdown
-        if let Some(min) = self.facility.config.limits.minimum_drawdown {
-            if amount < min {
-                return Err(FacilityError::BelowMinimumDrawdown { 
-                    minimum: min, 
-                    requested: amount 
-                });
-            }
-        }
+        let segment = self.draw_segment(draw_type);
         
         // check if overlimit (may allow with fee)
         let is_overlimit = amount > self.available_credit;
         if is_overlimit {
-            // check if we allow overlimit
-            let total_after_draw = self.facility.state.outstanding_principal + amount;
-            let overlimit_amount = total_after_draw - self.credit_limit;
+            let overlimit_amount = self.overlimit_after(amount);
+            self.overlimit_dates.push(time_provider.now());
             
-            if overlimit_amount > self.credit_limit * dec!(0.1) {
-                // don't allow more than 10% overlimit
-                return Err(FacilityError::ExceedsCreditLimit {
-                    available: self.available_credit,
-                    requested: amount,
-                });
-            }
-            
-            // apply overlimit fee
+            // apply overlimit fee within the policy caps
             if let Some(fee) = self.facility.config.fee_config.overlimit_fee {
-                self.facility.state.accrued_fees += fee;
-                self.facility.state.total_fees_charged += fee;
+                let fees_applied = if self.overlimit_fee_allowed() {
+                    self.facility.state.charge_fee("overlimit_fee", fee);
+                    if !self.overlimit_fee_charged_this_cycle() {
+                        self.overlimit_fee_cycle = self.cycle_start;
+                        self.overlimit_fee_cycles += 1;
+                    }
+                    fee
+                } else {
+                    Money::ZERO
+                };
                 
                 self.facility.events.emit(Event::OverlimitOccurred {
                     facility_id: self.facility.id,
                     amount_over: overlimit_amount,
-                    fees_applied: fee,
+                    fees_applied,
                     timestamp: time_provider.now(),
                 });
             }
@@ -236,16 +713,9 @@ impl RevolvingFacility {
         
         // perform the draw
         self.facility.state.record_disbursement(amount);
-        self.available_credit = (self.credit_limit - self.facility.state.outstanding_principal)
-            .max(Money::ZERO);
-        
-        // update revolving state
-        if let crate::state::FacilitySpecificState::Revolving {
-            available_credit,
-            ..
-        } = &mut self.facility.state.facility_specific {
-            *available_credit = self.available_credit;
-        }
+        self.facility.state.transaction_usage.record(amount, time_provider.now());
+        self.post_to_segment(segment, amount, time_provider.now());
+        self.refresh_available_credit();
         
         // emit draw event
         self.facility.events.emit(Event::FundsDrawn {
@@ -256,493 +726,3070 @@ impl RevolvingFacility {
             timestamp: time_provider.now(),
         });
         
+        self.charge_transaction_fees(amount, draw_type, segment, time_provider.now());
+        
         Ok(amount)
     }
     
-    /// process payment and restore available credit with explicit time
-    pub fn process_payment_with_time(
+    /// segment a new draw is booked to, the penalty segment while the penalty apr applies
+    fn draw_segment(&self, draw_type: DrawType) -> BalanceSegment {
+        match self.penalty_apr_policy() {
+            Some(policy) if self.penalty_apr_since.is_some() && !policy.apply_to_existing_balances => {
+                BalanceSegment::Penalty
+            }
+            _ => draw_type.segment(),
+        }
+    }
+    
+    /// place an authorization hold that reduces available credit without posting
+    pub fn authorize_with_time(
         &mut self,
         amount: Money,
+        draw_type: DrawType,
         time_provider: &SafeTimeProvider,
-    ) -> Result<()> {
-        let principal_before = self.facility.state.outstanding_principal;
+    ) -> Result<Uuid> {
+        let now = time_provider.now();
+        self.check_draw_period_with_time(time_provider)?;
+        self.expire_authorizations_with_time(time_provider);
         
-        // process payment through standard waterfall
-        self.facility.process_payment(amount, time_provider)?;
+        self.validate_draw(amount)?;
+        self.check_transaction_limits(amount, now)?;
         
-        let principal_after = self.facility.state.outstanding_principal;
-        let principal_paid = principal_before - principal_after;
+        let authorization = Authorization {
+            id: Uuid::new_v4(),
+            amount,
+            draw_type,
+            authorized_at: now,
+            expires_at: now + chrono::Duration::days(self.authorization_expiry_days as i64),
+        };
+        let id = authorization.id;
+        let expires_at = authorization.expires_at;
+        self.authorizations.insert(id, authorization);
+        self.refresh_available_credit();
         
-        // restore available credit
-        self.available_credit = (self.available_credit + principal_paid)
-            .min(self.credit_limit);
+        self.facility.events.emit(Event::AuthorizationHeld {
+            facility_id: self.facility.id,
+            authorization_id: id,
+            amount,
+            total_held: amount,
+            expires_at,
+            timestamp: now,
+        });
         
-        // update state
-        if let crate::state::FacilitySpecificState::Revolving {
-            available_credit,
-            ..
-        } = &mut self.facility.state.facility_specific {
-            *available_credit = self.available_credit;
+        Ok(id)
+    }
+    
+    /// increase an existing hold and extend its expiry
+    pub fn increment_authorization_with_time(
+        &mut self,
+        id: Uuid,
+        additional: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
+        let now = time_provider.now();
+        self.expire_authorizations_with_time(time_provider);
+        
+        if !self.authorizations.contains_key(&id) {
+            return Err(FacilityError::AuthorizationNotFound { id });
         }
+        self.validate_draw(additional)?;
+        self.check_transaction_limits(additional, now)?;
+        
+        let expires_at = now + chrono::Duration::days(self.authorization_expiry_days as i64);
+        let authorization = self.authorizations.get_mut(&id)
+            .ok_or(FacilityError::AuthorizationNotFound { id })?;
+        authorization.amount += additional;
+        authorization.expires_at = expires_at;
+        let total_held = authorization.amount;
+        self.refresh_available_credit();
+        
+        self.facility.events.emit(Event::AuthorizationHeld {
+            facility_id: self.facility.id,
+            authorization_id: id,
+            amount: additional,
+            total_held,
+            expires_at,
+            timestamp: now,
+        });
         
         Ok(())
     }
     
-    /// calculate minimum payment
-    pub fn calculate_minimum_payment(&self) -> Money {
-        let outstanding = self.facility.state.outstanding_principal;
-        let interest = self.facility.state.accrued_interest;
-        let fees = self.facility.state.accrued_fees;
-        
-        // minimum is higher of:
-        // 1. percentage of outstanding + all interest and fees
-        // 2. absolute minimum from config
-        let percentage_based = outstanding * self.minimum_payment_percentage + interest + fees;
-        let absolute_minimum = self.facility.config.payment_config.minimum_payment
-            .unwrap_or(Money::from_major(25));
+    /// release a hold and post the captured amount, which may differ from the hold
+    pub fn capture_authorization_with_time(
+        &mut self,
+        id: Uuid,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Money> {
+        let authorization = self.authorizations.remove(&id)
+            .ok_or(FacilityError::AuthorizationNotFound { id })?;
+        self.refresh_available_credit();
         
-        // if overlimit, must pay overlimit amount
-        if outstanding > self.credit_limit {
-            let overlimit = outstanding - self.credit_limit;
-            percentage_based.max(absolute_minimum).max(overlimit + interest + fees)
-        } else {
-            percentage_based.max(absolute_minimum)
+        // reinstate the hold if the posting is rejected; anything captured
+        // beyond the hold counts against the transaction limits
+        let excess = (amount - authorization.amount).max(Money::ZERO);
+        let check = self.validate_draw(amount).and_then(|_| {
+            if excess.is_zero() {
+                Ok(())
+            } else {
+                self.check_transaction_limits(excess, time_provider.now())
+            }
+        });
+        if let Err(e) = check {
+            self.authorizations.insert(id, authorization);
+            self.refresh_available_credit();
+            return Err(e);
         }
+        
+        self.facility.events.emit(Event::AuthorizationCaptured {
+            facility_id: self.facility.id,
+            authorization_id: id,
+            authorized_amount: authorization.amount,
+            captured_amount: amount,
+            timestamp: time_provider.now(),
+        });
+        
+        self.post_draw(amount, authorization.draw_type, time_provider)
     }
     
-    /// calculate utilization rate
-    pub fn utilization_rate(&self) -> Rate {
-        if self.credit_limit.is_zero() {
-            return Rate::from_percentage(0);
+    /// reverse a hold, restoring available credit
+    pub fn reverse_authorization_with_time(
+        &mut self,
+        id: Uuid,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
+        let authorization = self.authorizations.remove(&id)
+            .ok_or(FacilityError::AuthorizationNotFound { id })?;
+        self.release_authorization(authorization, "Reversed", time_provider.now());
+        Ok(())
+    }
+    
+    /// release holds past their expiry date
+    pub fn expire_authorizations_with_time(&mut self, time_provider: &SafeTimeProvider) -> Vec<Uuid> {
+        let now = time_provider.now();
+        let mut expired: Vec<Authorization> = self.authorizations
+            .values()
+            .filter(|a| a.expires_at <= now)
+            .cloned()
+            .collect();
+        expired.sort_by_key(|a| a.authorized_at);
+        
+        let mut ids = Vec::new();
+        for authorization in expired {
+            self.authorizations.remove(&authorization.id);
+            ids.push(authorization.id);
+            self.release_authorization(authorization, "Expired", now);
         }
         
-        Rate::from_decimal(
-            self.facility.state.outstanding_principal.as_decimal() / 
-            self.credit_limit.as_decimal()
-        )
+        ids
     }
     
-    /// get utilization state
-    pub fn utilization_state(&self) -> UtilizationState {
-        let rate = self.utilization_rate();
+    /// release a removed hold and emit the event
+    fn release_authorization(&mut self, authorization: Authorization, reason: &str, timestamp: DateTime<Utc>) {
+        self.refresh_available_credit();
         
-        if rate == Rate::from_percentage(0) {
-            UtilizationState::Unused
-        } else if rate < Rate::from_percentage(30) {
-            UtilizationState::Low
-        } else if rate < Rate::from_percentage(70) {
-            UtilizationState::Moderate
-        } else if rate < Rate::from_percentage(90) {
-            UtilizationState::High
-        } else if rate <= Rate::from_percentage(100) {
-            UtilizationState::Maxed
-        } else {
-            UtilizationState::Overlimit
-        }
+        self.facility.events.emit(Event::AuthorizationReleased {
+            facility_id: self.facility.id,
+            authorization_id: authorization.id,
+            amount: authorization.amount,
+            reason: reason.to_string(),
+            timestamp,
+        });
     }
     
-    /// check if facility is overlimit
-    pub fn is_overlimit(&self) -> bool {
-        self.facility.state.outstanding_principal > self.credit_limit
+    /// total amount held by pending authorizations
+    pub fn pending_authorizations(&self) -> Money {
+        self.authorizations
+            .values()
+            .fold(Money::ZERO, |acc, a| acc + a.amount)
     }
     
-    /// charge commitment fee on undrawn amounts
-    pub fn charge_commitment_fee(&mut self) -> Result<()> {
-        let time_ptr = self.time
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Time provider not set. Call set_time() first".to_string(),
-            })?;
-        let time_provider = unsafe { time_ptr.as_ref() }
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Invalid time provider reference".to_string(),
-            })?;
-        if let Some(commitment_rate) = self.facility.config.fee_config.commitment_fee_rate {
-            let undrawn = self.available_credit;
-            if undrawn > Money::ZERO {
-                // monthly fee
-                let monthly_rate = commitment_rate.as_decimal() / dec!(12);
-                let fee = Money::from_decimal(undrawn.as_decimal() * monthly_rate);
-                
-                self.facility.state.accrued_fees += fee;
-                self.facility.state.total_fees_charged += fee;
-                
-                self.facility.events.emit(Event::CommitmentFeeCharged {
-                    facility_id: self.facility.id,
-                    undrawn_amount: undrawn,
-                    fee,
-                    timestamp: time_provider.now(),
-                });
-            }
-        }
-        
-        Ok(())
+    /// get a pending authorization
+    pub fn authorization(&self, id: Uuid) -> Option<&Authorization> {
+        self.authorizations.get(&id)
     }
     
-    /// end draw period (for heloc)
-    pub fn end_draw_period(&mut self) -> Result<()> {
-        let time_ptr = self.time
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Time provider not set. Call set_time() first".to_string(),
-            })?;
-        let time_provider = unsafe { time_ptr.as_ref() }
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Invalid time provider reference".to_string(),
-            })?;
-        if !self.is_in_draw_period {
-            return Ok(()); // already ended
-        }
-        
-        self.is_in_draw_period = false;
-        self.draw_period_ends = Some(time_provider.now());
-        
-        // change to amortizing payments
-        if let FacilityType::Revolving(RevolvingType::HELOC) = &self.facility.config.facility_type {
-            // calculate new minimum payment for amortization
-            // this would normally generate an amortization schedule
-            self.minimum_payment_percentage = dec!(0.015); // 1.5% for principal + interest
-        }
-        
-        Ok(())
+    /// carve part of the purchase balance into a fixed installment plan
+    pub fn create_installment_plan_with_time(
+        &mut self,
+        amount: Money,
+        pricing: InstallmentPricing,
+        term_months: u32,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Uuid> {
+        self.open_installment_plan(BalanceSegment::Purchase, amount, pricing, term_months, time_provider)
     }
     
-    /// change credit limit
-    pub fn change_credit_limit(&mut self, new_limit: Money) -> Result<()> {
-        let time_ptr = self.time
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Time provider not set. Call set_time() first".to_string(),
-            })?;
-        let time_provider = unsafe { time_ptr.as_ref() }
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Invalid time provider reference".to_string(),
-            })?;
-        let old_limit = self.credit_limit;
-        
-        // update limit
-        self.credit_limit = new_limit;
-        
-        // update available credit
-        self.available_credit = (new_limit - self.facility.state.outstanding_principal)
-            .max(Money::ZERO);
+    /// draw a purchase straight into a fixed installment plan
+    pub fn draw_installment_plan_with_time(
+        &mut self,
+        amount: Money,
+        pricing: InstallmentPricing,
+        term_months: u32,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Uuid> {
+        self.validate_installment_plan(amount, term_months)?;
+        let segment = self.draw_segment(DrawType::Purchase);
+        self.draw_with_type_with_time(amount, DrawType::Purchase, time_provider)?;
+        self.open_installment_plan(segment, amount, pricing, term_months, time_provider)
+    }
+    
+    /// prepay plan principal, re-amortizing the rest over the remaining installments
+    pub fn prepay_installment_plan_with_time(
+        &mut self,
+        id: Uuid,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Money> {
+        let now = time_provider.now();
+        let index = self.active_installment_plan(id)?;
         
-        // update state
-        if let crate::state::FacilitySpecificState::Revolving {
-            credit_limit,
-            available_credit,
-            ..
-        } = &mut self.facility.state.facility_specific {
-            *credit_limit = self.credit_limit;
-            *available_credit = self.available_credit;
+        if amount <= Money::ZERO {
+            return Err(FacilityError::InvalidPaymentAmount { amount });
+        }
+        if !self.facility.state.can_accept_payment() {
+            return Err(FacilityError::FacilityNotActive {
+                status: self.facility.state.status,
+            });
         }
+        self.facility.config.limits.check_payment(amount, self.facility.state.total_outstanding())?;
         
-        // emit event
-        self.facility.events.emit(Event::CreditLimitChanged {
+        self.accrue_commitment_fee_until(now);
+        let applied = amount.min(self.installment_plans[index].remaining_principal);
+        self.reduce_installment_plan(index, applied, time_provider)?;
+        self.facility.state.outstanding_principal -= applied;
+        // plan money doesn't count towards the revolving minimum payment
+        self.facility.state.total_payments_received += applied;
+        self.facility.state.payment_count += 1;
+        self.refresh_available_credit();
+        
+        self.facility.events.emit(Event::PaymentReceived {
             facility_id: self.facility.id,
-            old_limit,
-            new_limit,
-            timestamp: time_provider.now(),
+            amount: applied,
+            applied_to_fees: Money::ZERO,
+            applied_to_interest: Money::ZERO,
+            applied_to_principal: applied,
+            timestamp: now,
         });
         
-        // check if now overlimit
-        if self.is_overlimit() {
-            self.facility.events.emit(Event::OverlimitOccurred {
+        if self.facility.state.total_outstanding().is_zero() {
+            self.facility.state.update_status(FacilityStatus::Settled, now);
+            
+            self.facility.events.emit(Event::FacilitySettled {
                 facility_id: self.facility.id,
-                amount_over: self.facility.state.outstanding_principal - new_limit,
-                fees_applied: Money::ZERO,
-                timestamp: time_provider.now(),
+                settlement_amount: applied,
+                timestamp: now,
             });
         }
         
-        Ok(())
+        Ok(applied)
     }
     
-    /// get facility reference
-    pub fn facility(&self) -> &Facility {
-        &self.facility
+    /// cancel a plan, returning its unbilled principal to the revolving balance
+    pub fn cancel_installment_plan_with_time(
+        &mut self,
+        id: Uuid,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Money> {
+        let now = time_provider.now();
+        let index = self.active_installment_plan(id)?;
+        
+        let plan = &mut self.installment_plans[index];
+        let remaining = std::mem::replace(&mut plan.remaining_principal, Money::ZERO);
+        plan.status = InstallmentPlanStatus::Cancelled;
+        let segment = plan.segment;
+        
+        if let Some(balance) = self.facility.state.segment_balance_mut(segment) {
+            *balance += remaining;
+        }
+        self.record_balance_change(segment, Money::ZERO, now);
+        
+        self.facility.events.emit(Event::InstallmentPlanClosed {
+            facility_id: self.facility.id,
+            plan_id: id,
+            remaining_principal: remaining,
+            reason: "Cancelled".to_string(),
+            timestamp: now,
+        });
+        
+        Ok(remaining)
     }
     
-    /// get mutable facility reference
-    pub fn facility_mut(&mut self) -> &mut Facility {
-        &mut self.facility
+    /// get all installment plans, oldest first
+    pub fn installment_plans(&self) -> &[InstallmentPlan] {
+        &self.installment_plans
     }
     
-    /// get credit limit
-    pub fn credit_limit(&self) -> Money {
-        self.credit_limit
+    /// get an installment plan
+    pub fn installment_plan(&self, id: Uuid) -> Option<&InstallmentPlan> {
+        self.installment_plans.iter().find(|p| p.id == id)
     }
     
-    /// get available credit
-    pub fn available_credit(&self) -> Money {
-        self.available_credit
+    /// unbilled principal held in active installment plans
+    pub fn installment_plan_balance(&self) -> Money {
+        self.installment_plans
+            .iter()
+            .filter(|p| p.status == InstallmentPlanStatus::Active)
+            .fold(Money::ZERO, |acc, p| acc + p.remaining_principal)
     }
     
-    /// get json representation of current state
-    pub fn to_json_pretty(&self) -> String {
-        use super::serialization::{FacilityView, RevolvingView};
+    /// move principal out of a segment into a new plan
+    fn open_installment_plan(
+        &mut self,
+        segment: BalanceSegment,
+        amount: Money,
+        pricing: InstallmentPricing,
+        term_months: u32,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Uuid> {
+        let now = time_provider.now();
+        self.validate_installment_plan(amount, term_months)?;
         
-        let view = RevolvingView {
-            facility: FacilityView::from_facility(&self.facility),
-            credit_limit: self.credit_limit,
-            available_credit: self.available_credit,
-            utilization_rate: self.utilization_rate(),
-            is_in_draw_period: self.is_in_draw_period,
-        };
+        let available = self.facility.state.segment_balance(segment);
+        if amount > available {
+            return Err(FacilityError::InsufficientFunds {
+                available,
+                requested: amount,
+            });
+        }
         
-        serde_json::to_string_pretty(&view).unwrap_or_else(|e| format!("JSON error: {}", e))
-    }
-    
-    /// short alias for json output
-    pub fn json(&self) -> String {
-        self.to_json_pretty()
-    }
-    
-    /// approve the facility (alias for activate)
-    pub fn approve(&mut self) -> Result<()> {
-        self.activate()
-    }
-    
-    /// deny/cancel the facility
-    pub fn deny(&mut self) -> Result<()> {
-        let time_ptr = self.time
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Time provider not set. Call set_time() first".to_string(),
-            })?;
-        let time = unsafe { time_ptr.as_ref() }
-            .ok_or(FacilityError::InvalidConfiguration {
-                message: "Invalid time provider reference".to_string(),
-            })?;
+        let schedule = AmortizationSchedule::generate(
+            self.facility.id,
+            amount,
+            pricing.rate(),
+            term_months,
+            now,
+            AmortizationMethod::EqualInstallments,
+            time_provider,
+        )?;
         
-        let old_status = self.facility.state.status;
-        self.facility.state.update_status(FacilityStatus::Settled, time.now());
+        if let Some(balance) = self.facility.state.segment_balance_mut(segment) {
+            *balance -= amount;
+        }
+        self.record_balance_change(segment, Money::ZERO, now);
         
-        self.facility.events.emit(Event::StatusChanged {
+        let plan = InstallmentPlan {
+            id: Uuid::new_v4(),
+            segment,
+            amount,
+            pricing,
+            term_months,
+            created_at: now,
+            status: InstallmentPlanStatus::Active,
+            remaining_principal: amount,
+            installments_billed: 0,
+            schedule,
+            schedule_offset: 0,
+        };
+        let id = plan.id;
+        let installment_amount = plan.installment_amount();
+        self.installment_plans.push(plan);
+        
+        self.facility.events.emit(Event::InstallmentPlanCreated {
             facility_id: self.facility.id,
-            old_status,
-            new_status: FacilityStatus::Settled,
-            reason: "Denied by lender - no funds disbursed".to_string(),
-            timestamp: time.now(),
+            plan_id: id,
+            segment,
+            amount,
+            term_months,
+            installment_amount,
+            timestamp: now,
         });
         
-        Ok(())
+        Ok(id)
     }
     
-    /// make payment (alias for process_payment)
-    pub fn make_payment(&mut self, amount: Money) -> Result<()> {
-        self.process_payment(amount)
+    fn validate_installment_plan(&self, amount: Money, term_months: u32) -> Result<()> {
+        if self.closed_at.is_some() {
+            return Err(FacilityError::InvalidState {
+                current: "Closed".to_string(),
+                expected: "Open".to_string(),
+            });
+        }
+        
+        if amount <= Money::ZERO {
+            return Err(FacilityError::InvalidDrawAmount { amount });
+        }
+        
+        if term_months == 0 {
+            return Err(FacilityError::InvalidConfiguration {
+                message: "Installment plan term must be at least one month".to_string(),
+            });
+        }
+        
+        Ok(())
     }
     
-    /// disburse funds (alias for draw)
-    pub fn disburse(&mut self, amount: Money) -> Result<Money> {
-        self.draw(amount)
-    }
-}
-
-/// builder for revolving facilities
-pub struct RevolvingFacilityBuilder {
-    facility_type: Option<RevolvingType>,
-    credit_limit: Option<Money>,
-    rate: Option<Rate>,
-    minimum_percentage: Option<Decimal>,
-    commitment_fee_rate: Option<Rate>,
-    property_value: Option<Money>,
-    draw_period: Option<u32>,
-    repayment_period: Option<u32>,
-    account_number: Option<String>,
-    customer_id: Option<String>,
-    time_provider: Option<*const SafeTimeProvider>,
-}
-
-impl RevolvingFacilityBuilder {
-    pub fn new() -> Self {
-        Self {
-            facility_type: None,
-            credit_limit: None,
-            rate: None,
-            minimum_percentage: None,
-            commitment_fee_rate: None,
-            property_value: None,
-            draw_period: None,
-            repayment_period: None,
-            account_number: None,
-            customer_id: None,
-            time_provider: None,
+    fn active_installment_plan(&self, id: Uuid) -> Result<usize> {
+        let index = self.installment_plans
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or(FacilityError::InstallmentPlanNotFound { id })?;
+        
+        let status = self.installment_plans[index].status;
+        if status != InstallmentPlanStatus::Active {
+            return Err(FacilityError::InvalidState {
+                current: format!("{:?}", status),
+                expected: "Active".to_string(),
+            });
         }
+        
+        Ok(index)
     }
     
-    pub fn set_time(mut self, time: &SafeTimeProvider) -> Self {
-        self.time_provider = Some(time as *const SafeTimeProvider);
-        self
+    /// reduce plan principal outside of billing, closing the plan once it is repaid
+    fn reduce_installment_plan(
+        &mut self,
+        index: usize,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
+        let now = time_provider.now();
+        let facility_id = self.facility.id;
+        let plan = &mut self.installment_plans[index];
+        plan.remaining_principal -= amount;
+        
+        if plan.remaining_principal.is_zero() {
+            plan.status = InstallmentPlanStatus::PaidOff;
+        } else {
+            // keep the original end date with a lower installment
+            plan.schedule = AmortizationSchedule::generate(
+                facility_id,
+                plan.remaining_principal,
+                plan.pricing.rate(),
+                plan.installments_remaining(),
+                now,
+                AmortizationMethod::EqualInstallments,
+                time_provider,
+            )?;
+            plan.schedule_offset = plan.installments_billed;
+        }
+        
+        let plan_id = plan.id;
+        let remaining_principal = plan.remaining_principal;
+        let paid_off = plan.status == InstallmentPlanStatus::PaidOff;
+        
+        self.facility.events.emit(Event::InstallmentPlanPrepaid {
+            facility_id,
+            plan_id,
+            amount,
+            remaining_principal,
+            timestamp: now,
+        });
+        
+        if paid_off {
+            self.facility.events.emit(Event::InstallmentPlanClosed {
+                facility_id,
+                plan_id,
+                remaining_principal: Money::ZERO,
+                reason: "Prepaid".to_string(),
+                timestamp: now,
+            });
+        }
+        
+        Ok(())
     }
     
-    pub fn facility_type(mut self, facility_type: RevolvingType) -> Self {
-        self.facility_type = Some(facility_type);
-        self
+    /// bill the next installment of each active plan into its segment, returning principal billed
+    fn bill_installment_plans(&mut self, now: DateTime<Utc>) -> Money {
+        let mut billed = Money::ZERO;
+        
+        for index in 0..self.installment_plans.len() {
+            let plan = &mut self.installment_plans[index];
+            let Some(scheduled) = plan.next_installment().cloned() else {
+                continue;
+            };
+            
+            // the final installment clears any rounding residual
+            plan.installments_billed += 1;
+            let principal = if plan.installments_billed >= plan.term_months {
+                plan.remaining_principal
+            } else {
+                scheduled.principal_portion.min(plan.remaining_principal)
+            };
+            let charge = plan.charge_for(&scheduled);
+            plan.remaining_principal -= principal;
+            if plan.remaining_principal.is_zero() {
+                plan.status = InstallmentPlanStatus::PaidOff;
+            }
+            
+            let plan_id = plan.id;
+            let segment = plan.segment;
+            let pricing = plan.pricing;
+            let installment_number = plan.installments_billed;
+            let remaining_principal = plan.remaining_principal;
+            let paid_off = plan.status == InstallmentPlanStatus::PaidOff;
+            
+            if let Some(balance) = self.facility.state.segment_balance_mut(segment) {
+                *balance += principal;
+            }
+            self.record_balance_change(segment, Money::ZERO, now);
+            
+            match pricing {
+                InstallmentPricing::Apr(_) => {
+                    self.facility.state.accrued_interest += charge;
+                }
+                InstallmentPricing::MonthlyFee(_) => {
+                    self.facility.state.charge_fee("installment_plan_fee", charge);
+                    *self.segment_fees.entry(segment).or_insert(Money::ZERO) += charge;
+                }
+            }
+            billed += principal;
+            
+            self.facility.events.emit(Event::InstallmentBilled {
+                facility_id: self.facility.id,
+                plan_id,
+                installment_number,
+                principal,
+                charge,
+                remaining_principal,
+                timestamp: now,
+            });
+            
+            if paid_off {
+                self.facility.events.emit(Event::InstallmentPlanClosed {
+                    facility_id: self.facility.id,
+                    plan_id,
+                    remaining_principal: Money::ZERO,
+                    reason: "Paid off".to_string(),
+                    timestamp: now,
+                });
+            }
+        }
+        
+        billed
     }
     
-    pub fn credit_limit(mut self, limit: Money) -> Self {
-        self.credit_limit = Some(limit);
-        self
+    /// principal accruing at the facility rate, leaving out plan principal priced by its plan
+    fn revolving_principal(&self) -> Money {
+        self.facility.state.outstanding_principal - self.installment_plan_balance()
     }
     
-    pub fn rate(mut self, rate: Rate) -> Self {
-        self.rate = Some(rate);
-        self
+    /// validate a draw or authorization against the line, counting pending holds
+    fn validate_draw(&self, amount: Money) -> Result<()> {
+        if self.closed_at.is_some() {
+            return Err(FacilityError::InvalidState {
+                current: "Closed".to_string(),
+                expected: "Open".to_string(),
+            });
+        }
+        
+        if !self.is_in_draw_period {
+            return Err(FacilityError::DrawPeriodEnded);
+        }
+        
+        if amount <= Money::ZERO {
+            return Err(FacilityError::InvalidDrawAmount { amount });
+        }
+        
+        // overlimit draws stay within the policy tolerance
+        if amount > self.available_credit && self.overlimit_after(amount) > self.credit_limit * self.overlimit_tolerance() {
+            return Err(FacilityError::ExceedsCreditLimit {
+                available: self.available_credit,
+                requested: amount,
+            });
+        }
+        
+        Ok(())
     }
     
-    pub fn minimum_percentage(mut self, percentage: Decimal) -> Self {
-        self.minimum_percentage = Some(percentage);
-        self
+    /// check drawdown size and daily and monthly usage limits
+    fn check_transaction_limits(&self, amount: Money, now: DateTime<Utc>) -> Result<()> {
+        // pending holds use up the limits until they are captured or released
+        let mut usage = self.facility.state.transaction_usage.clone();
+        usage.drawdowns.extend(self.authorizations.values().map(|a| (a.authorized_at, a.amount)));
+        self.facility.config.limits.check_drawdown(amount, &usage, now)
     }
     
-    pub fn commitment_fee_rate(mut self, rate: Rate) -> Self {
-        self.commitment_fee_rate = Some(rate);
-        self
+    /// share of the limit a draw may exceed it by, zero without a required opt-in
+    fn overlimit_tolerance(&self) -> Decimal {
+        let policy = &self.facility.config.limits.overlimit_policy;
+        if policy.require_opt_in && !self.overlimit_opt_in {
+            return Decimal::ZERO;
+        }
+        policy.tolerance
     }
     
-    pub fn property_value(mut self, value: Money) -> Self {
-        self.property_value = Some(value);
-        self
+    /// check the fee frequency caps before charging an overlimit fee
+    fn overlimit_fee_allowed(&self) -> bool {
+        let policy = &self.facility.config.limits.overlimit_policy;
+        if policy.require_opt_in && !self.overlimit_opt_in {
+            return false;
+        }
+        
+        if self.overlimit_fee_charged_this_cycle() {
+            return policy.fee_frequency == OverlimitFeeFrequency::PerTransaction;
+        }
+        policy.max_fee_cycles.is_none_or(|max| self.overlimit_fee_cycles < max)
     }
     
-    pub fn draw_period_months(mut self, months: u32) -> Self {
-        self.draw_period = Some(months);
-        self
+    fn overlimit_fee_charged_this_cycle(&self) -> bool {
+        self.overlimit_fee_cycle.is_some_and(|cycle| Some(cycle) == self.cycle_start)
     }
     
-    pub fn repayment_period_months(mut self, months: u32) -> Self {
-        self.repayment_period = Some(months);
-        self
+    /// opt in to or out of overlimit draws and fees
+    pub fn set_overlimit_opt_in(&mut self, opt_in: bool) {
+        self.overlimit_opt_in = opt_in;
     }
     
-    pub fn account_number(mut self, account: String) -> Self {
-        self.account_number = Some(account);
-        self
+    /// check if the customer has opted in to overlimit draws
+    pub fn overlimit_opt_in(&self) -> bool {
+        self.overlimit_opt_in
     }
     
-    pub fn customer_id(mut self, customer: String) -> Self {
-        self.customer_id = Some(customer);
-        self
+    /// amount over the limit after a draw, counting pending holds
+    fn overlimit_after(&self, amount: Money) -> Money {
+        self.facility.state.outstanding_principal + self.pending_authorizations() + amount - self.credit_limit
     }
     
-    /// Build with stored time or system time if not set
-    pub fn build(self) -> Result<RevolvingFacility> {
-        if let Some(time_ptr) = self.time_provider {
-            let time = unsafe { time_ptr.as_ref() }
-                .ok_or(FacilityError::InvalidConfiguration {
-                    message: "Invalid time provider reference".to_string(),
-                })?;
-            self.build_with_time(time)
-        } else {
-            let time = SafeTimeProvider::new(hourglass_rs::TimeSource::System);
-            self.build_with_time(&time)
+    /// recompute available credit from the limit, posted principal and pending holds
+    fn refresh_available_credit(&mut self) {
+        self.available_credit = (self.credit_limit
+            - self.facility.state.outstanding_principal
+            - self.pending_authorizations())
+            .max(Money::ZERO);
+        
+        if let crate::state::FacilitySpecificState::Revolving {
+            available_credit,
+            ..
+        } = &mut self.facility.state.facility_specific {
+            *available_credit = self.available_credit;
         }
     }
     
-    /// Build with system time
-    pub fn build_now(self) -> Result<RevolvingFacility> {
-        let time = SafeTimeProvider::new(hourglass_rs::TimeSource::System);
-        self.build_with_time(&time)
+    /// charge cash advance, balance transfer and foreign transaction fees for a draw
+    fn charge_transaction_fees(
+        &mut self,
+        amount: Money,
+        draw_type: DrawType,
+        segment: BalanceSegment,
+        timestamp: DateTime<Utc>,
+    ) {
+        let fee_config = &self.facility.config.fee_config;
+        let mut fees = Vec::new();
+        
+        match draw_type {
+            DrawType::CashAdvance | DrawType::ForeignCashAdvance => {
+                if let Some(fee) = &fee_config.cash_advance_fee {
+                    fees.push((TransactionFeeKind::CashAdvance, fee.calculate(amount)));
+                }
+            }
+            DrawType::BalanceTransfer => {
+                if let Some(fee) = &fee_config.balance_transfer_fee {
+                    fees.push((TransactionFeeKind::BalanceTransfer, fee.calculate(amount)));
+                }
+            }
+            DrawType::Purchase | DrawType::ForeignPurchase => {}
+        }
+        
+        if draw_type.is_foreign() {
+            if let Some(fee) = &fee_config.foreign_transaction_fee {
+                fees.push((TransactionFeeKind::ForeignTransaction, fee.calculate(amount)));
+            }
+        }
+        
+        for (kind, fee) in fees {
+            if fee <= Money::ZERO {
+                continue;
+            }
+            
+            self.facility.state.charge_fee(kind.fee_kind(), fee);
+            *self.segment_fees.entry(segment).or_insert(Money::ZERO) += fee;
+            
+            self.facility.events.emit(Event::TransactionFeeCharged {
+                facility_id: self.facility.id,
+                kind,
+                draw_type,
+                segment,
+                transaction_amount: amount,
+                fee,
+                timestamp,
+            });
+        }
     }
     
-    /// Build with explicit time provider (for backward compatibility)
-    pub fn build_with_time(self, time_provider: &SafeTimeProvider) -> Result<RevolvingFacility> {
-        // Infer facility type based on provided values, or default to LineOfCredit
-        let facility_type = self.facility_type.unwrap_or_else(|| {
-            if self.property_value.is_some() {
-                RevolvingType::HELOC  // Home equity line of credit
-            } else if self.minimum_percentage == Some(dec!(0.02)) || self.minimum_percentage == Some(dec!(0.025)) {
-                RevolvingType::CreditCard  // Credit cards typically have 2-2.5% minimum
-            } else {
-                RevolvingType::LineOfCredit  // General purpose line of credit
+    /// process payment and restore available credit with explicit time
+    pub fn process_payment_with_time(
+        &mut self,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
+        self.accrue_commitment_fee_until(time_provider.now());
+        let principal_before = self.facility.state.outstanding_principal;
+        
+        // track payments made by the due date toward the minimum
+        if let Some(due) = self.facility.state.next_payment_due {
+            if time_provider.now() <= due {
+                self.paid_toward_minimum += amount;
             }
-        });
+        }
         
-        let credit_limit = self.credit_limit.ok_or(FacilityError::InvalidConfiguration {
-            message: "Credit limit required".to_string(),
-        })?;
+        // process payment through standard waterfall
+        let fees_before = self.facility.state.fee_balances.clone();
+        let result = self.facility.process_payment(amount, time_provider)?;
         
-        let rate = self.rate.ok_or(FacilityError::InvalidConfiguration {
-            message: "Rate required".to_string(),
-        })?;
+        // a reduce-limit overpayment strategy lowers the line
+        if let Some(new_limit) = result.overpayment.as_ref().and_then(|o| o.new_credit_limit) {
+            self.credit_limit = new_limit;
+        }
         
-        let config = match facility_type {
-            RevolvingType::CreditCard => {
-                FacilityConfig::credit_card(
-                    credit_limit,
-                    rate,
-                    self.minimum_percentage.unwrap_or(dec!(0.02)),
-                )
+        let principal_after = self.facility.state.outstanding_principal;
+        let principal_paid = principal_before - principal_after;
+        self.allocate_fee_payment(&fees_before);
+        self.allocate_principal_payment(principal_paid, time_provider)?;
+        
+        // restore available credit
+        self.refresh_available_credit();
+        
+        Ok(())
+    }
+    
+    /// cut the billing statement, charging cycle interest for average daily balance
+    pub fn cut_statement_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Statement> {
+        let now = time_provider.now();
+        let cycle_start = self.cycle_start
+            .or(self.facility.state.activation_date)
+            .unwrap_or(now);
+        
+        self.check_draw_period_with_time(time_provider)?;
+        self.charge_commitment_fee_with_time(time_provider)?;
+        let annual_fee_charged = self.assess_annual_fee_with_time(time_provider)?;
+        let interest_before = self.facility.state.accrued_interest;
+        let mut segment_interest = HashMap::new();
+        
+        match self.facility.config.interest_config.interest_method {
+            InterestMethod::AverageDailyBalance { include_new_transactions } => {
+                let engine = AverageDailyBalanceEngine::new(
+                    self.facility.config.interest_config.day_count_convention,
+                    include_new_transactions,
+                );
+                
+                for segment in SEGMENTS {
+                    let history = match self.balance_history.get(&segment) {
+                        Some(history) if !history.is_empty() => history,
+                        _ => continue,
+                    };
+                    
+                    let balances = engine.daily_balances(history, cycle_start, now);
+                    let result = engine.calculate(&balances, self.segment_rate(segment), now);
+                    
+                    if result.interest_amount > Money::ZERO {
+                        self.facility.state.accrued_interest += result.interest_amount;
+                        
+                        self.facility.events.emit(Event::CycleInterestCharged {
+                            facility_id: self.facility.id,
+                            segment,
+                            average_daily_balance: result.average_daily_balance,
+                            days: result.days,
+                            amount: result.interest_amount,
+                            timestamp: now,
+                        });
+                    }
+                    
+                    segment_interest.insert(segment, result);
+                }
+                
+                self.facility.state.last_interest_accrual = now;
             }
-            RevolvingType::LineOfCredit => {
-                FacilityConfig::line_of_credit(
-                    credit_limit,
-                    rate,
-                    self.commitment_fee_rate.unwrap_or(Rate::from_decimal(dec!(0.005))),
-                )
+            InterestMethod::DailyAccrual => {
+                self.accrue_interest_with_time(time_provider)?;
             }
-            RevolvingType::HELOC => {
-                let property_value = self.property_value.ok_or(FacilityError::InvalidConfiguration {
-                    message: "Property value required for HELOC".to_string(),
-                })?;
-                
-                FacilityConfig::heloc(
-                    credit_limit,
-                    rate,
-                    property_value,
-                    self.draw_period.unwrap_or(120), // 10 years default
-                    self.repayment_period.unwrap_or(240), // 20 years default
-                )
+        }
+        
+        self.installment_due = self.bill_installment_plans(now);
+        
+        // closing balances open the next cycle
+        for segment in SEGMENTS {
+            let balance = self.facility.state.segment_balance(segment);
+            self.balance_history.insert(segment, vec![BalanceChange {
+                timestamp: now,
+                balance,
+                new_transactions: Money::ZERO,
+            }]);
+        }
+        
+        self.review_closing_cycle(now);
+        if !self.is_overlimit() {
+            self.overlimit_fee_cycles = 0;
+        }
+        self.utilization_history.push((now, self.utilization_rate()));
+        self.apply_scheduled_limit_decrease(now)?;
+        if self.next_credit_line_review.is_some_and(|review| now >= review) {
+            self.review_credit_line_with_time(time_provider)?;
+        }
+        
+        let statement_balance = self.facility.state.total_outstanding();
+        
+        // amortizing repayment is due on the schedule, not the statement cycle
+        let scheduled = self.amortization_schedule.as_ref()
+            .and_then(|schedule| schedule.get_payment(self.current_payment_number + 1));
+        let (minimum_payment, payment_due_date) = match scheduled {
+            Some(next) => {
+                self.facility.state.next_payment_amount = Some(next.payment_amount);
+                (next.payment_amount.min(statement_balance), next.payment_date)
             }
+            None => (
+                self.calculate_minimum_payment().min(statement_balance),
+                now + chrono::Duration::days(PAYMENT_DUE_DAYS),
+            ),
         };
         
-        let account_number = self.account_number.unwrap_or_else(|| {
-            format!("REV-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
-        });
+        self.facility.state.minimum_payment_due = Some(minimum_payment);
+        self.facility.state.next_payment_due = Some(payment_due_date);
+        
+        if let crate::state::FacilitySpecificState::Revolving {
+            statement_balance: state_balance,
+            statement_date,
+            ..
+        } = &mut self.facility.state.facility_specific {
+            *state_balance = statement_balance;
+            *statement_date = Some(now);
+        }
+        
+        self.cycle_start = Some(now);
+        
+        self.facility.events.emit(Event::StatementGenerated {
+            facility_id: self.facility.id,
+            statement_balance,
+            minimum_payment,
+            payment_due_date,
+            timestamp: now,
+        });
+        
+        Ok(Statement {
+            cycle_start,
+            statement_date: now,
+            statement_balance,
+            interest_charged: self.facility.state.accrued_interest - interest_before,
+            annual_fee_charged,
+            minimum_payment,
+            payment_due_date,
+            segment_interest,
+            payoff_disclosure: self.payoff_disclosure(),
+        })
+    }
+    
+    /// charge annual fees for every anniversary period started since activation
+    pub fn assess_annual_fee_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Money> {
+        let now = time_provider.now();
+        let (Some(annual_fee), Some(activation_date)) = (
+            self.facility.config.fee_config.annual_fee,
+            self.facility.state.activation_date,
+        ) else {
+            return Ok(Money::ZERO);
+        };
+        
+        if self.closed_at.is_some() {
+            return Ok(Money::ZERO);
+        }
+        
+        let policy = self.facility.config.fee_config.annual_fee_policy.clone().unwrap_or_default();
+        let period_months = policy.billing_frequency.months();
+        let periods_per_year = 12 / period_months;
+        let period_fee = annual_fee / Decimal::from(periods_per_year);
+        
+        let mut total = Money::ZERO;
+        loop {
+            let period_start = add_months(activation_date, period_months * self.annual_fee_periods)?;
+            if period_start > now {
+                break;
+            }
+            let period_end = add_months(activation_date, period_months * (self.annual_fee_periods + 1))?;
+            
+            let waived = policy.waive_first_year && self.annual_fee_periods < periods_per_year;
+            let amount = if waived { Money::ZERO } else { period_fee };
+            
+            if amount > Money::ZERO {
+                self.facility.state.charge_fee("annual_fee", amount);
+                *self.segment_fees.entry(BalanceSegment::Purchase).or_insert(Money::ZERO) += amount;
+                self.last_annual_fee = Some(AnnualFeeCharge {
+                    period_start,
+                    period_end,
+                    amount,
+                });
+                total += amount;
+            }
+            
+            self.facility.events.emit(Event::AnnualFeeAssessed {
+                facility_id: self.facility.id,
+                amount,
+                period_start,
+                period_end,
+                waived,
+                timestamp: now,
+            });
+            
+            self.annual_fee_periods += 1;
+        }
+        
+        Ok(total)
+    }
+    
+    /// close the account to new draws, refunding a prorated annual fee within the refund window
+    pub fn close_account_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Money> {
+        let now = time_provider.now();
+        if self.closed_at.is_some() {
+            return Ok(Money::ZERO);
+        }
+        
+        let refund_window = self.facility.config.fee_config.annual_fee_policy
+            .as_ref()
+            .and_then(|p| p.refund_window_days);
+        
+        let mut refund = Money::ZERO;
+        if let (Some(window), Some(charge)) = (refund_window, self.last_annual_fee) {
+            if now < charge.period_start + chrono::Duration::days(window as i64) && now < charge.period_end {
+                let days_unused = (charge.period_end - now).num_days();
+                let period_days = (charge.period_end - charge.period_start).num_days();
+                refund = charge.amount * Decimal::from(days_unused) / Decimal::from(period_days);
+                
+                // cancel the unpaid annual fee first, then principal; whatever
+                // is left is owed back to the customer
+                let state = &mut self.facility.state;
+                let unpaid_fee = state.fee_balances.get("annual_fee").copied().unwrap_or(Money::ZERO);
+                let to_fees = refund.min(unpaid_fee).min(state.accrued_fees);
+                state.accrued_fees -= to_fees;
+                if to_fees == unpaid_fee {
+                    state.fee_balances.remove("annual_fee");
+                } else if let Some(balance) = state.fee_balances.get_mut("annual_fee") {
+                    *balance -= to_fees;
+                }
+                let to_principal = (refund - to_fees).min(state.outstanding_principal);
+                state.outstanding_principal -= to_principal;
+                state.total_fees_charged -= refund;
+                let unapplied = refund - to_fees - to_principal;
+                self.reduce_segment_fees(BalanceSegment::Purchase, to_fees);
+                self.allocate_principal_payment(to_principal, time_provider)?;
+                
+                self.facility.events.emit(Event::AnnualFeeRefunded {
+                    facility_id: self.facility.id,
+                    amount: refund,
+                    unapplied,
+                    days_unused: days_unused as u32,
+                    timestamp: now,
+                });
+            }
+        }
+        
+        self.closed_at = Some(now);
+        self.last_annual_fee = None;
+        
+        if self.facility.state.total_outstanding().is_zero() {
+            let old_status = self.facility.state.status;
+            self.facility.state.update_status(FacilityStatus::Settled, now);
+            
+            self.facility.events.emit(Event::StatusChanged {
+                facility_id: self.facility.id,
+                old_status,
+                new_status: FacilityStatus::Settled,
+                reason: "Account closed by customer".to_string(),
+                timestamp: now,
+            });
+        }
+        
+        Ok(refund)
+    }
+    
+    /// check if the account has been closed
+    pub fn is_closed(&self) -> bool {
+        self.closed_at.is_some()
+    }
+    
+    /// get annual rate for a segment, falling back to the facility rate
+    pub fn segment_rate(&self, segment: BalanceSegment) -> Rate {
+        if let (Some(policy), Some(penalty_rate)) = (self.penalty_apr_policy(), self.penalty_rate()) {
+            let repriced = policy.apply_to_existing_balances || segment == BalanceSegment::Penalty;
+            if self.penalty_apr_since.is_some() && repriced {
+                return penalty_rate;
+            }
+        }
+        
+        self.segment_rates
+            .get(&segment)
+            .copied()
+            .unwrap_or(self.facility.config.financial_terms.interest_rate)
+    }
+    
+    /// check if the penalty apr is in effect
+    pub fn is_penalty_apr_active(&self) -> bool {
+        self.penalty_apr_since.is_some()
+    }
+    
+    fn penalty_apr_policy(&self) -> Option<&crate::config::PenaltyAprPolicy> {
+        self.facility.config.interest_config.penalty_apr.as_ref()
+    }
+    
+    fn penalty_rate(&self) -> Option<Rate> {
+        self.facility.config.interest_config.default_rate
+    }
+    
+    /// apply the penalty apr once the account reaches the trigger dpd
+    fn check_penalty_apr_with_time(&mut self, time_provider: &SafeTimeProvider) {
+        let (Some(policy), Some(penalty_rate)) = (self.penalty_apr_policy(), self.penalty_rate()) else {
+            return;
+        };
+        
+        let days_past_due = self.facility.state.days_past_due;
+        if self.penalty_apr_since.is_some() || days_past_due < policy.trigger_days_past_due {
+            return;
+        }
+        
+        let scope = if policy.apply_to_existing_balances {
+            "all balances"
+        } else {
+            "new transactions"
+        };
+        
+        self.penalty_apr_since = Some(time_provider.now());
+        self.on_time_payments = 0;
+        
+        self.facility.events.emit(Event::InterestRateChanged {
+            facility_id: self.facility.id,
+            old_rate: self.facility.config.financial_terms.interest_rate,
+            new_rate: penalty_rate,
+            reason: format!("Penalty APR applied to {} at {} days past due", scope, days_past_due),
+            timestamp: time_provider.now(),
+        });
+    }
+    
+    /// review the closing cycle for an on-time minimum payment
+    fn review_closing_cycle(&mut self, now: DateTime<Utc>) {
+        let paid = std::mem::replace(&mut self.paid_toward_minimum, Money::ZERO);
+        
+        let (Some(minimum), Some(due)) = (
+            self.facility.state.minimum_payment_due,
+            self.facility.state.next_payment_due,
+        ) else {
+            return;
+        };
+        
+        if due > now {
+            return;
+        }
+        
+        let on_time = paid >= minimum;
+        if on_time {
+            self.on_time_streak += 1;
+        } else {
+            self.on_time_streak = 0;
+            self.delinquency_dates.push(due);
+        }
+        
+        self.review_penalty_apr_cure(on_time, now);
+    }
+    
+    /// count on-time payments toward curing the penalty apr
+    fn review_penalty_apr_cure(&mut self, on_time: bool, now: DateTime<Utc>) {
+        if self.penalty_apr_since.is_none() {
+            return;
+        }
+        
+        if !on_time {
+            self.on_time_payments = 0;
+            return;
+        }
+        
+        self.on_time_payments += 1;
+        let (Some(policy), Some(penalty_rate)) = (self.penalty_apr_policy(), self.penalty_rate()) else {
+            return;
+        };
+        if self.on_time_payments < policy.cure_after_on_time_payments {
+            return;
+        }
+        
+        // penalty balances return to the purchase segment
+        let penalty_balance = self.facility.state.segment_balance(BalanceSegment::Penalty);
+        if penalty_balance > Money::ZERO {
+            if let Some(balance) = self.facility.state.segment_balance_mut(BalanceSegment::Penalty) {
+                *balance = Money::ZERO;
+            }
+            self.record_balance_change(BalanceSegment::Penalty, Money::ZERO, now);
+            if let Some(balance) = self.facility.state.segment_balance_mut(BalanceSegment::Purchase) {
+                *balance += penalty_balance;
+            }
+            self.record_balance_change(BalanceSegment::Purchase, Money::ZERO, now);
+        }
+        if let Some(fees) = self.segment_fees.remove(&BalanceSegment::Penalty) {
+            *self.segment_fees.entry(BalanceSegment::Purchase).or_insert(Money::ZERO) += fees;
+        }
+        
+        let cured_after = self.on_time_payments;
+        self.penalty_apr_since = None;
+        self.on_time_payments = 0;
+        
+        self.facility.events.emit(Event::InterestRateChanged {
+            facility_id: self.facility.id,
+            old_rate: penalty_rate,
+            new_rate: self.facility.config.financial_terms.interest_rate,
+            reason: format!("Penalty APR cured after {} consecutive on-time payments", cured_after),
+            timestamp: now,
+        });
+    }
+    
+    /// accrue the penalty over standard rate difference on repriced principal under daily accrual
+    fn accrue_penalty_differential(&mut self, since: DateTime<Utc>, time_provider: &SafeTimeProvider) {
+        if self.penalty_apr_since.is_none() {
+            return;
+        }
+        // the whole balance already accrues at the default rate
+        if self.facility.in_default_rate() {
+            return;
+        }
+        if let InterestMethod::AverageDailyBalance { .. } = self.facility.config.interest_config.interest_method {
+            return;
+        }
+        let (Some(policy), Some(penalty_rate)) = (self.penalty_apr_policy(), self.penalty_rate()) else {
+            return;
+        };
+        
+        let principal = if policy.apply_to_existing_balances {
+            self.revolving_principal()
+        } else {
+            self.facility.state.segment_balance(BalanceSegment::Penalty)
+        };
+        let standard_rate = self.facility.config.financial_terms.interest_rate;
+        if principal.is_zero() || penalty_rate <= standard_rate {
+            return;
+        }
+        
+        let engine = AccrualEngine::new(self.facility.config.interest_config.day_count_convention);
+        let differential = Rate::from_decimal(penalty_rate.as_decimal() - standard_rate.as_decimal());
+        for accrual in engine.accrue_daily(principal, differential, since, time_provider) {
+            self.facility.book_interest(accrual.interest_amount, accrual.date);
+        }
+    }
+    
+    /// set annual rate for a segment
+    pub fn set_segment_rate(&mut self, segment: BalanceSegment, rate: Rate) {
+        self.segment_rates.insert(segment, rate);
+    }
+    
+    /// get principal balance of a segment
+    pub fn segment_balance(&self, segment: BalanceSegment) -> Money {
+        self.facility.state.segment_balance(segment)
+    }
+    
+    /// get unpaid transaction fees booked to a segment
+    pub fn segment_fees(&self, segment: BalanceSegment) -> Money {
+        self.segment_fees.get(&segment).copied().unwrap_or(Money::ZERO)
+    }
+    
+    /// segments ordered by rate, highest first
+    fn segments_by_rate(&self) -> Vec<BalanceSegment> {
+        let mut segments = SEGMENTS.to_vec();
+        segments.sort_by_key(|s| std::cmp::Reverse(self.segment_rate(*s)));
+        segments
+    }
+    
+    /// release segment fee balances for the itemized fees repaid since
+    /// `fees_before`; late, overlimit and commitment fees aren't carried on a segment
+    fn allocate_fee_payment(&mut self, fees_before: &BTreeMap<String, Money>) {
+        for (kind, before) in fees_before {
+            let after = self.facility.state.fee_balances.get(kind).copied().unwrap_or(Money::ZERO);
+            let paid = (*before - after).max(Money::ZERO);
+            let segment = match kind.as_str() {
+                "cash_advance_fee" => BalanceSegment::CashAdvance,
+                "balance_transfer_fee" => BalanceSegment::BalanceTransfer,
+                "foreign_transaction_fee" | "installment_plan_fee" | "annual_fee" => BalanceSegment::Purchase,
+                _ => continue,
+            };
+            self.reduce_segment_fees(segment, paid);
+        }
+    }
+    
+    /// reduce segment fee balances, `segment` first, then highest rate first
+    fn reduce_segment_fees(&mut self, segment: BalanceSegment, amount: Money) {
+        let mut remaining = amount;
+        let segments = std::iter::once(segment)
+            .chain(self.segments_by_rate().into_iter().filter(|s| *s != segment))
+            .collect::<Vec<_>>();
+        for segment in segments {
+            if remaining <= Money::ZERO {
+                break;
+            }
+            
+            if let Some(fees) = self.segment_fees.get_mut(&segment) {
+                let applied = remaining.min(*fees);
+                *fees -= applied;
+                remaining -= applied;
+            }
+        }
+    }
+    
+    /// post a new transaction to a segment and record the balance change
+    fn post_to_segment(&mut self, segment: BalanceSegment, amount: Money, timestamp: DateTime<Utc>) {
+        if let Some(balance) = self.facility.state.segment_balance_mut(segment) {
+            *balance += amount;
+        }
+        self.record_balance_change(segment, amount, timestamp);
+    }
+    
+    /// allocate principal repaid to segments highest rate first, then to plans oldest first
+    fn allocate_principal_payment(&mut self, amount: Money, time_provider: &SafeTimeProvider) -> Result<()> {
+        let timestamp = time_provider.now();
+        let mut remaining = amount;
+        for segment in self.segments_by_rate() {
+            if remaining <= Money::ZERO {
+                break;
+            }
+            
+            let Some(balance) = self.facility.state.segment_balance_mut(segment) else {
+                return Ok(());
+            };
+            let applied = remaining.min(*balance);
+            if applied > Money::ZERO {
+                *balance -= applied;
+                remaining -= applied;
+                self.record_balance_change(segment, Money::ZERO, timestamp);
+            }
+        }
+        
+        // principal beyond the revolving balance prepays installment plans
+        for index in 0..self.installment_plans.len() {
+            if remaining <= Money::ZERO {
+                break;
+            }
+            
+            let plan = &self.installment_plans[index];
+            if plan.status != InstallmentPlanStatus::Active {
+                continue;
+            }
+            let applied = remaining.min(plan.remaining_principal);
+            self.reduce_installment_plan(index, applied, time_provider)?;
+            remaining -= applied;
+        }
+        
+        Ok(())
+    }
+    
+    /// append the current segment balance to the cycle history
+    fn record_balance_change(&mut self, segment: BalanceSegment, new_transactions: Money, timestamp: DateTime<Utc>) {
+        let balance = self.facility.state.segment_balance(segment);
+        self.balance_history
+            .entry(segment)
+            .or_default()
+            .push(BalanceChange {
+                timestamp,
+                balance,
+                new_transactions,
+            });
+    }
+    
+    /// calculate minimum payment
+    pub fn calculate_minimum_payment(&self) -> Money {
+        // amortizing repayment follows the schedule
+        if let Some(schedule) = &self.amortization_schedule {
+            if let Some(next) = schedule.get_payment(self.current_payment_number + 1) {
+                return next.payment_amount + self.facility.state.accrued_fees;
+            }
+        }
+        
+        let outstanding = self.facility.state.outstanding_principal;
+        let interest = self.facility.state.accrued_interest;
+        let fees = self.facility.state.accrued_fees;
+        let plan_balance = self.installment_plan_balance();
+        if plan_balance.is_zero() && self.installment_due.is_zero() {
+            return self.minimum_payment_for(outstanding, interest, fees);
+        }
+        
+        // billed installments are due in full on top of the revolving minimum
+        let installments = self.installment_due.min(outstanding);
+        let revolving = (outstanding - plan_balance - installments).max(Money::ZERO);
+        let revolving_due = revolving + interest + fees;
+        if revolving_due.is_zero() {
+            return installments;
+        }
+        
+        self.minimum_payment_for(revolving, interest, fees).min(revolving_due) + installments
+    }
+    
+    /// minimum payment formula for a given principal, interest and fees
+    fn minimum_payment_for(&self, outstanding: Money, interest: Money, fees: Money) -> Money {
+        // minimum is higher of:
+        // 1. percentage of outstanding + all interest and fees
+        // 2. absolute minimum from config
+        let percentage_based = outstanding * self.minimum_payment_percentage + interest + fees;
+        let absolute_minimum = self.facility.config.payment_config.minimum_payment
+            .unwrap_or(Money::from_major(25));
+        
+        // if overlimit, must pay overlimit amount
+        if outstanding > self.credit_limit {
+            let overlimit = outstanding - self.credit_limit;
+            percentage_based.max(absolute_minimum).max(overlimit + interest + fees)
+        } else {
+            percentage_based.max(absolute_minimum)
+        }
+    }
+    
+    /// project paying only the minimum each month at current segment rates with no new transactions.
+    /// installment plans repay on their own schedule and are left out
+    pub fn payoff_disclosure(&self) -> PayoffDisclosure {
+        let state = &self.facility.state;
+        let mut segments: Vec<(Rate, Money)> = self.segments_by_rate()
+            .into_iter()
+            .map(|s| (self.segment_rate(s), state.segment_balance(s)))
+            .filter(|(_, balance)| *balance > Money::ZERO)
+            .collect();
+        
+        // principal not tracked in a segment or a plan is carried at the facility rate
+        let plans = self.installment_plan_balance();
+        let segmented = segments.iter().fold(Money::ZERO, |acc, (_, b)| acc + *b);
+        if state.outstanding_principal > segmented + plans {
+            segments.push((
+                self.facility.config.financial_terms.interest_rate,
+                state.outstanding_principal - segmented - plans,
+            ));
+            segments.sort_by_key(|(rate, _)| std::cmp::Reverse(*rate));
+        }
+        
+        let balance = state.total_outstanding() - plans;
+        let monthly_interest_on = |segments: &[(Rate, Money)]| {
+            segments.iter().fold(Money::ZERO, |acc, (rate, b)| acc + *b * (rate.as_decimal() / dec!(12)))
+        };
+        
+        // 36 month payment at the balance weighted rate
+        let principal = segments.iter().fold(Money::ZERO, |acc, (_, b)| acc + *b);
+        let blended_rate = if principal.is_zero() {
+            self.facility.config.financial_terms.interest_rate
+        } else {
+            Rate::from_decimal(monthly_interest_on(&segments).as_decimal() * dec!(12) / principal.as_decimal())
+        };
+        let payment_for_36_months = crate::payments::overpayment::calculate_emi(balance, blended_rate, 36);
+        
+        let mut interest = state.accrued_interest;
+        let mut fees = state.accrued_fees;
+        let mut total_interest = Money::ZERO;
+        let mut total_paid = Money::ZERO;
+        let mut months = 0;
+        
+        while segments.iter().any(|(_, b)| *b > Money::ZERO) || (interest + fees) > Money::ZERO {
+            if months >= MAX_PAYOFF_MONTHS {
+                return PayoffDisclosure {
+                    months_to_payoff: None,
+                    total_interest,
+                    total_paid,
+                    payment_for_36_months,
+                    total_paid_36_months: payment_for_36_months * Decimal::from(36),
+                };
+            }
+            
+            // interest for the month is billed before the payment
+            if months > 0 {
+                let charged = monthly_interest_on(&segments).round_dp(2);
+                interest += charged;
+                total_interest += charged;
+            }
+            
+            let principal = segments.iter().fold(Money::ZERO, |acc, (_, b)| acc + *b);
+            let payment = self.minimum_payment_for(principal, interest, fees)
+                .min(principal + interest + fees);
+            total_paid += payment;
+            
+            // fees and interest first, then principal highest rate first
+            let mut remaining = payment - fees - interest;
+            fees = Money::ZERO;
+            interest = Money::ZERO;
+            for (_, balance) in segments.iter_mut() {
+                let applied = remaining.min(*balance);
+                *balance -= applied;
+                remaining -= applied;
+            }
+            
+            months += 1;
+        }
+        
+        PayoffDisclosure {
+            months_to_payoff: Some(months),
+            total_interest,
+            total_paid,
+            payment_for_36_months,
+            total_paid_36_months: payment_for_36_months * Decimal::from(36),
+        }
+    }
+    
+    /// calculate utilization rate
+    pub fn utilization_rate(&self) -> Rate {
+        if self.credit_limit.is_zero() {
+            return Rate::from_percentage(0);
+        }
+        
+        Rate::from_decimal(
+            self.facility.state.outstanding_principal.as_decimal() / 
+            self.credit_limit.as_decimal()
+        )
+    }
+    
+    /// get utilization state
+    pub fn utilization_state(&self) -> UtilizationState {
+        let rate = self.utilization_rate();
+        
+        if rate == Rate::from_percentage(0) {
+            UtilizationState::Unused
+        } else if rate < Rate::from_percentage(30) {
+            UtilizationState::Low
+        } else if rate < Rate::from_percentage(70) {
+            UtilizationState::Moderate
+        } else if rate < Rate::from_percentage(90) {
+            UtilizationState::High
+        } else if rate <= Rate::from_percentage(100) {
+            UtilizationState::Maxed
+        } else {
+            UtilizationState::Overlimit
+        }
+    }
+    
+    /// check if facility is overlimit
+    pub fn is_overlimit(&self) -> bool {
+        self.facility.state.outstanding_principal > self.credit_limit
+    }
+    
+    /// bill commitment fees using stored time
+    pub fn charge_commitment_fee(&mut self) -> Result<Money> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time_provider = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.charge_commitment_fee_with_time(time_provider)
+    }
+    
+    /// accrue commitment fees to date and bill every billing period ended in arrears
+    pub fn charge_commitment_fee_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<Money> {
+        let now = time_provider.now();
+        let period_months = self.facility.config.fee_config.commitment_fee_schedule
+            .as_ref()
+            .map(|s| s.billing_frequency.months())
+            .unwrap_or(1);
+        
+        let mut total = Money::ZERO;
+        while let Some(accrual) = self.commitment_accrual {
+            let period_start = add_months(accrual.anchor, period_months * accrual.billed_periods)?;
+            let period_end = add_months(accrual.anchor, period_months * (accrual.billed_periods + 1))?;
+            if period_end > now {
+                break;
+            }
+            
+            self.accrue_commitment_fee_until(period_end);
+            let Some(mut accrual) = self.commitment_accrual else {
+                break;
+            };
+            
+            let fee = Money::from_decimal(accrual.unbilled_fee);
+            if fee > Money::ZERO {
+                let average_undrawn = if accrual.days > 0 {
+                    Money::from_decimal(accrual.undrawn_sum / Decimal::from(accrual.days))
+                } else {
+                    Money::ZERO
+                };
+                
+                self.facility.state.charge_fee("commitment_fee", fee);
+                total += fee;
+                
+                self.facility.events.emit(Event::CommitmentFeeCharged {
+                    facility_id: self.facility.id,
+                    undrawn_amount: average_undrawn,
+                    fee,
+                    period_start,
+                    period_end,
+                    timestamp: now,
+                });
+            }
+            
+            accrual.billed_periods += 1;
+            accrual.unbilled_fee = Decimal::ZERO;
+            accrual.undrawn_sum = Decimal::ZERO;
+            accrual.days = 0;
+            self.commitment_accrual = Some(accrual);
+        }
+        
+        self.accrue_commitment_fee_until(now);
+        Ok(total)
+    }
+    
+    /// commitment fees accrued but not yet billed
+    pub fn unbilled_commitment_fee(&self) -> Money {
+        self.commitment_accrual
+            .map(|a| Money::from_decimal(a.unbilled_fee))
+            .unwrap_or(Money::ZERO)
+    }
+    
+    /// current commitment fee rate, from the utilization grid when configured
+    pub fn commitment_fee_rate(&self) -> Option<Rate> {
+        let grid: &[UtilizationFeeTier] = self.facility.config.fee_config.commitment_fee_schedule
+            .as_ref()
+            .map(|s| s.utilization_grid.as_slice())
+            .unwrap_or(&[]);
+        
+        if grid.is_empty() {
+            return self.facility.config.fee_config.commitment_fee_rate;
+        }
+        
+        let utilization = self.utilization_rate();
+        grid.iter()
+            .find(|tier| utilization <= tier.max_utilization)
+            .or(grid.last())
+            .map(|tier| tier.rate)
+    }
+    
+    /// accrue commitment fees for each whole day on the undrawn line up to a date
+    fn accrue_commitment_fee_until(&mut self, until: DateTime<Utc>) {
+        let Some(mut accrual) = self.commitment_accrual else {
+            return;
+        };
+        
+        let days = (until - accrual.accrued_through).num_days();
+        if days <= 0 {
+            return;
+        }
+        
+        if let Some(rate) = self.commitment_fee_rate() {
+            let undrawn = (self.credit_limit - self.facility.state.outstanding_principal).max(Money::ZERO);
+            let engine = AccrualEngine::new(self.facility.config.interest_config.day_count_convention);
+            
+            for day in 0..days {
+                let date = accrual.accrued_through + chrono::Duration::days(day);
+                let year_basis = Decimal::from(engine.year_basis(date.year()));
+                accrual.unbilled_fee += undrawn.as_decimal() * rate.as_decimal() / year_basis;
+            }
+            
+            accrual.undrawn_sum += undrawn.as_decimal() * Decimal::from(days);
+            accrual.days += days as u32;
+        }
+        
+        accrual.accrued_through += chrono::Duration::days(days);
+        self.commitment_accrual = Some(accrual);
+    }
+    
+    /// end draw period using stored time
+    pub fn end_draw_period(&mut self) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time_provider = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.end_draw_period_with_time(time_provider)
+    }
+    
+    /// end the draw period once its configured end date has passed
+    pub fn check_draw_period_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        if let Some(ends) = self.draw_period_ends {
+            if self.is_in_draw_period && time_provider.now() >= ends {
+                self.end_draw_period_with_time(time_provider)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// end draw period, converting a heloc to an amortizing repayment schedule
+    pub fn end_draw_period_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        if !self.is_in_draw_period {
+            return Ok(()); // already ended
+        }
+        
+        let now = time_provider.now();
+        self.is_in_draw_period = false;
+        if self.draw_period_ends.is_none_or(|ends| ends > now) {
+            self.draw_period_ends = Some(now);
+        }
+        
+        // pending holds can no longer be captured
+        let mut pending: Vec<Authorization> = self.authorizations.drain().map(|(_, a)| a).collect();
+        pending.sort_by_key(|a| a.authorized_at);
+        for authorization in pending {
+            self.release_authorization(authorization, "Draw period ended", now);
+        }
+        
+        let terms = &self.facility.config.financial_terms;
+        let repayment_months = match (&self.facility.config.facility_type, terms.draw_period_months, terms.term_months) {
+            (FacilityType::Revolving(RevolvingType::HELOC), Some(draw), Some(term)) => term.saturating_sub(draw),
+            _ => 0,
+        };
+        
+        if repayment_months == 0 || self.facility.state.outstanding_principal.is_zero() {
+            self.facility.events.emit(Event::DrawPeriodEnded {
+                facility_id: self.facility.id,
+                outstanding_principal: self.facility.state.outstanding_principal,
+                repayment_months: 0,
+                scheduled_payment: None,
+                timestamp: now,
+            });
+            return Ok(());
+        }
+        
+        // amortize the outstanding balance over the repayment period
+        self.facility.config.financial_terms.amortization_method = AmortizationMethod::EqualInstallments;
+        let schedule = AmortizationSchedule::generate(
+            self.facility.id,
+            self.facility.state.outstanding_principal,
+            self.facility.config.financial_terms.interest_rate,
+            repayment_months,
+            now,
+            AmortizationMethod::EqualInstallments,
+            time_provider,
+        )?;
+        
+        let first_payment = schedule.get_payment(1).cloned();
+        if let Some(first) = &first_payment {
+            self.facility.state.next_payment_due = Some(first.payment_date);
+            self.facility.state.next_payment_amount = Some(first.payment_amount);
+            self.facility.state.minimum_payment_due = Some(first.payment_amount);
+        }
+        
+        self.repayment_period_ends = Some(add_months(now, repayment_months)?);
+        self.amortization_schedule = Some(schedule);
+        self.current_payment_number = 0;
+        
+        self.facility.events.emit(Event::DrawPeriodEnded {
+            facility_id: self.facility.id,
+            outstanding_principal: self.facility.state.outstanding_principal,
+            repayment_months,
+            scheduled_payment: first_payment.map(|p| p.payment_amount),
+            timestamp: now,
+        });
+        
+        Ok(())
+    }
+    
+    /// process the next scheduled repayment after heloc conversion
+    pub fn process_scheduled_payment_with_time(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        let schedule = self.amortization_schedule.as_ref()
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "No amortization schedule".to_string(),
+            })?;
+        
+        let payment_number = self.current_payment_number + 1;
+        let scheduled = schedule.get_payment(payment_number)
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: format!("No payment {} in schedule", payment_number),
+            })?
+            .clone();
+        let next = schedule.get_payment(payment_number + 1).cloned();
+        let term_months = schedule.term_months;
+        
+        // accrue interest up to payment date
+        self.facility.accrue_interest(time_provider)?;
+        
+        // the final payment clears any residual balance
+        let amount = if next.is_none() {
+            scheduled.payment_amount.max(self.facility.state.total_outstanding())
+        } else {
+            scheduled.payment_amount
+        };
+        self.process_payment_with_time(amount, time_provider)?;
+        self.current_payment_number = payment_number;
+        
+        // update next payment due
+        if let Some(next) = next {
+            self.facility.state.next_payment_due = Some(next.payment_date);
+            self.facility.state.next_payment_amount = Some(next.payment_amount);
+            self.facility.state.minimum_payment_due = Some(next.payment_amount);
+        } else {
+            self.facility.state.next_payment_due = None;
+            self.facility.state.next_payment_amount = None;
+            self.facility.state.minimum_payment_due = None;
+        }
+        
+        if payment_number >= term_months && self.facility.state.status == FacilityStatus::Settled {
+            self.facility.events.emit(Event::FacilityMatured {
+                facility_id: self.facility.id,
+                final_payment: amount,
+                timestamp: time_provider.now(),
+            });
+        }
+        
+        Ok(())
+    }
+    
+    /// get repayment schedule generated at heloc conversion
+    pub fn schedule(&self) -> Option<&AmortizationSchedule> {
+        self.amortization_schedule.as_ref()
+    }
+    
+    /// get draw period end date
+    pub fn draw_period_ends(&self) -> Option<DateTime<Utc>> {
+        self.draw_period_ends
+    }
+    
+    /// get repayment period end date
+    pub fn repayment_period_ends(&self) -> Option<DateTime<Utc>> {
+        self.repayment_period_ends
+    }
+    
+    /// change credit limit
+    pub fn change_credit_limit(&mut self, new_limit: Money) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time_provider = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.set_credit_limit(new_limit, CreditLimitChangeReason::Manual, time_provider.now())
+    }
+    
+    /// review payment, utilization, delinquency and overlimit history against the review policy
+    pub fn review_credit_line_with_time(
+        &mut self,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Option<CreditLineDecision>> {
+        let now = time_provider.now();
+        let Some(policy) = self.facility.config.limits.credit_line_review.clone() else {
+            return Ok(None);
+        };
+        
+        self.next_credit_line_review = Some(add_months(now, policy.review_frequency_months)?);
+        self.apply_scheduled_limit_decrease(now)?;
+        if self.closed_at.is_some() {
+            return Ok(None);
+        }
+        
+        let Some(decision) = self.evaluate_credit_line(&policy, now) else {
+            return Ok(None);
+        };
+        
+        if policy.auto_apply {
+            self.apply_credit_line_decision(decision.clone(), now)?;
+        } else {
+            self.credit_line_proposal = Some(decision.clone());
+        }
+        
+        Ok(Some(decision))
+    }
+    
+    /// apply the pending proposal, starting the notice period for decreases from acceptance
+    pub fn accept_credit_line_proposal_with_time(
+        &mut self,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Option<CreditLineDecision>> {
+        let now = time_provider.now();
+        let Some(mut decision) = self.credit_line_proposal.take() else {
+            return Ok(None);
+        };
+        
+        if !decision.is_increase() {
+            let notice_days = self.facility.config.limits.credit_line_review
+                .as_ref()
+                .map(|p| p.decrease_notice_days)
+                .unwrap_or(0);
+            decision.effective_date = now + chrono::Duration::days(notice_days as i64);
+        }
+        
+        self.apply_credit_line_decision(decision.clone(), now)?;
+        Ok(Some(decision))
+    }
+    
+    /// discard the pending proposal
+    pub fn decline_credit_line_proposal(&mut self) -> Option<CreditLineDecision> {
+        self.credit_line_proposal.take()
+    }
+    
+    /// get the proposal awaiting acceptance
+    pub fn credit_line_proposal(&self) -> Option<&CreditLineDecision> {
+        self.credit_line_proposal.as_ref()
+    }
+    
+    /// get the decrease waiting out its notice period
+    pub fn scheduled_limit_decrease(&self) -> Option<&CreditLineDecision> {
+        self.scheduled_limit_decrease.as_ref()
+    }
+    
+    /// get consecutive cycles with the minimum paid on time
+    pub fn on_time_streak(&self) -> u32 {
+        self.on_time_streak
+    }
+    
+    /// decide on a new limit, decreases taking precedence over increases
+    fn evaluate_credit_line(&self, policy: &CreditLineReviewPolicy, now: DateTime<Utc>) -> Option<CreditLineDecision> {
+        let window_start = now.checked_sub_months(Months::new(policy.lookback_months)).unwrap_or(now);
+        let delinquencies = self.delinquency_dates.iter().filter(|d| **d >= window_start).count() as u32;
+        let overlimits = self.overlimit_dates.iter().filter(|d| **d >= window_start).count() as u32;
+        
+        let samples: Vec<Rate> = self.utilization_history
+            .iter()
+            .filter(|(date, _)| *date >= window_start)
+            .map(|(_, rate)| *rate)
+            .collect();
+        let average_utilization = if samples.is_empty() {
+            self.utilization_rate()
+        } else {
+            let total = samples.iter().fold(Decimal::ZERO, |acc, r| acc + r.as_decimal());
+            Rate::from_decimal(total / Decimal::from(samples.len()))
+        };
+        
+        // change in average utilization from the earlier to the later half of the lookback
+        let utilization_trend = if samples.len() < 2 {
+            Decimal::ZERO
+        } else {
+            let (earlier, later) = samples.split_at(samples.len() / 2);
+            let mean = |half: &[Rate]| {
+                half.iter().fold(Decimal::ZERO, |acc, r| acc + r.as_decimal()) / Decimal::from(half.len())
+            };
+            mean(later) - mean(earlier)
+        };
+        
+        let old_limit = self.credit_limit;
+        let decreased = old_limit * (Decimal::ONE - policy.decrease_percentage);
+        let increased = old_limit * (Decimal::ONE + policy.increase_percentage);
+        
+        let (reason, target) = if delinquencies > policy.max_delinquencies {
+            (CreditLimitChangeReason::Delinquency, decreased)
+        } else if overlimits > policy.max_overlimit_occurrences {
+            (CreditLimitChangeReason::Overlimit, decreased)
+        } else if policy.max_utilization_rise.is_some_and(|m| utilization_trend > m.as_decimal()) {
+            (CreditLimitChangeReason::RisingUtilization, decreased)
+        } else if self.on_time_streak >= policy.min_on_time_payments
+            && average_utilization >= policy.increase_utilization_threshold
+        {
+            (CreditLimitChangeReason::PaymentHistory, increased)
+        } else if policy.low_utilization_threshold.is_some_and(|t| average_utilization <= t)
+            && utilization_trend <= Decimal::ZERO
+        {
+            (CreditLimitChangeReason::LowUtilization, decreased)
+        } else {
+            return None;
+        };
+        
+        let new_limit = if target > old_limit {
+            target.round_dp(0).min(policy.maximum_limit)
+        } else {
+            // never cut the line below the balance already drawn
+            target.round_dp(0).max(policy.minimum_limit).max(self.drawn_balance())
+        };
+        
+        if (target > old_limit && new_limit <= old_limit) || (target <= old_limit && new_limit >= old_limit) {
+            return None;
+        }
+        
+        let effective_date = if new_limit < old_limit {
+            now + chrono::Duration::days(policy.decrease_notice_days as i64)
+        } else {
+            now
+        };
+        
+        Some(CreditLineDecision {
+            reason,
+            old_limit,
+            new_limit,
+            reviewed_at: now,
+            effective_date,
+        })
+    }
+    
+    /// apply increases now and hold decreases until their notice period ends
+    fn apply_credit_line_decision(&mut self, decision: CreditLineDecision, now: DateTime<Utc>) -> Result<()> {
+        if decision.is_increase() || decision.effective_date <= now {
+            self.scheduled_limit_decrease = None;
+            return self.set_credit_limit(decision.new_limit, decision.reason, now);
+        }
+        
+        self.facility.events.emit(Event::CreditLimitDecreaseScheduled {
+            facility_id: self.facility.id,
+            old_limit: decision.old_limit,
+            new_limit: decision.new_limit,
+            reason: decision.reason,
+            effective_date: decision.effective_date,
+            timestamp: now,
+        });
+        
+        self.scheduled_limit_decrease = Some(decision);
+        Ok(())
+    }
+    
+    /// apply a scheduled decrease once its notice period has passed
+    fn apply_scheduled_limit_decrease(&mut self, now: DateTime<Utc>) -> Result<()> {
+        if let Some(decision) = self.scheduled_limit_decrease.take_if(|d| d.effective_date <= now) {
+            // reviews never cut the line below the balance drawn during the notice period
+            let new_limit = decision.new_limit.max(self.drawn_balance());
+            self.set_credit_limit(new_limit, decision.reason, now)?;
+        }
+        
+        Ok(())
+    }
+    
+    /// outstanding principal rounded up to whole units
+    fn drawn_balance(&self) -> Money {
+        Money::from_decimal(self.facility.state.outstanding_principal.as_decimal().ceil())
+    }
+    
+    /// set the credit limit per the overlimit policy and emit the change with its reason
+    fn set_credit_limit(&mut self, new_limit: Money, reason: CreditLimitChangeReason, now: DateTime<Utc>) -> Result<()> {
+        let balance = self.facility.state.outstanding_principal;
+        let new_limit = if new_limit < balance {
+            match self.facility.config.limits.overlimit_policy.limit_decrease {
+                LimitDecreaseHandling::AllowOverlimit => new_limit,
+                LimitDecreaseHandling::FloorAtBalance => self.drawn_balance(),
+                LimitDecreaseHandling::Reject => {
+                    return Err(FacilityError::CreditLimitBelowBalance {
+                        limit: new_limit,
+                        balance,
+                    });
+                }
+            }
+        } else {
+            new_limit
+        };
+        
+        self.accrue_commitment_fee_until(now);
+        let old_limit = self.credit_limit;
+        
+        // update limit
+        self.credit_limit = new_limit;
+        
+        // update available credit
+        if let crate::state::FacilitySpecificState::Revolving {
+            credit_limit,
+            ..
+        } = &mut self.facility.state.facility_specific {
+            *credit_limit = self.credit_limit;
+        }
+        self.refresh_available_credit();
+        
+        // emit event
+        self.facility.events.emit(Event::CreditLimitChanged {
+            facility_id: self.facility.id,
+            old_limit,
+            new_limit,
+            reason,
+            timestamp: now,
+        });
+        
+        // check if now overlimit
+        if self.is_overlimit() {
+            self.facility.events.emit(Event::OverlimitOccurred {
+                facility_id: self.facility.id,
+                amount_over: self.facility.state.outstanding_principal - new_limit,
+                fees_applied: Money::ZERO,
+                timestamp: now,
+            });
+        }
+        
+        Ok(())
+    }
+    
+    /// get facility reference
+    pub fn facility(&self) -> &Facility {
+        &self.facility
+    }
+    
+    /// get mutable facility reference
+    pub fn facility_mut(&mut self) -> &mut Facility {
+        &mut self.facility
+    }
+    
+    /// get credit limit
+    pub fn credit_limit(&self) -> Money {
+        self.credit_limit
+    }
+    
+    /// get available credit
+    pub fn available_credit(&self) -> Money {
+        self.available_credit
+    }
+    
+    /// get json representation of current state
+    pub fn to_json_pretty(&self) -> String {
+        use super::serialization::{FacilityView, RevolvingView};
+        
+        let view = RevolvingView {
+            facility: FacilityView::from_facility(&self.facility),
+            credit_limit: self.credit_limit,
+            available_credit: self.available_credit,
+            pending_authorizations: self.pending_authorizations(),
+            installment_plan_balance: self.installment_plan_balance(),
+            utilization_rate: self.utilization_rate(),
+            is_in_draw_period: self.is_in_draw_period,
+        };
+        
+        serde_json::to_string_pretty(&view).unwrap_or_else(|e| format!("JSON error: {}", e))
+    }
+    
+    /// short alias for json output
+    pub fn json(&self) -> String {
+        self.to_json_pretty()
+    }
+    
+    /// approve the facility (alias for activate)
+    pub fn approve(&mut self) -> Result<()> {
+        self.activate()
+    }
+    
+    /// deny/cancel the facility
+    pub fn deny(&mut self) -> Result<()> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        
+        let old_status = self.facility.state.status;
+        self.facility.state.update_status(FacilityStatus::Settled, time.now());
+        
+        self.facility.events.emit(Event::StatusChanged {
+            facility_id: self.facility.id,
+            old_status,
+            new_status: FacilityStatus::Settled,
+            reason: "Denied by lender - no funds disbursed".to_string(),
+            timestamp: time.now(),
+        });
+        
+        Ok(())
+    }
+    
+    /// make payment (alias for process_payment)
+    pub fn make_payment(&mut self, amount: Money) -> Result<()> {
+        self.process_payment(amount)
+    }
+    
+    /// disburse funds (alias for draw)
+    pub fn disburse(&mut self, amount: Money) -> Result<Money> {
+        self.draw(amount)
+    }
+}
+
+/// add calendar months to a date
+pub(crate) fn add_months(date: DateTime<Utc>, months: u32) -> Result<DateTime<Utc>> {
+    date.checked_add_months(Months::new(months))
+        .ok_or(FacilityError::InvalidDate {
+            message: format!("cannot add {} months to {}", months, date),
+        })
+}
+
+/// builder for revolving facilities
+pub struct RevolvingFacilityBuilder {
+    facility_type: Option<RevolvingType>,
+    credit_limit: Option<Money>,
+    rate: Option<Rate>,
+    minimum_percentage: Option<Decimal>,
+    commitment_fee_rate: Option<Rate>,
+    property_value: Option<Money>,
+    draw_period: Option<u32>,
+    repayment_period: Option<u32>,
+    account_number: Option<String>,
+    customer_id: Option<String>,
+    interest_method: Option<InterestMethod>,
+    segment_rates: HashMap<BalanceSegment, Rate>,
+    authorization_expiry_days: Option<u32>,
+    commitment_fee_schedule: Option<CommitmentFeeSchedule>,
+    credit_line_review: Option<CreditLineReviewPolicy>,
+    overlimit_policy: Option<OverlimitPolicy>,
+    time_provider: Option<*const SafeTimeProvider>,
+}
+
+impl Default for RevolvingFacilityBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RevolvingFacilityBuilder {
+    pub fn new() -> Self {
+        Self {
+            facility_type: None,
+            credit_limit: None,
+            rate: None,
+            minimum_percentage: None,
+            commitment_fee_rate: None,
+            property_value: None,
+            draw_period: None,
+            repayment_period: None,
+            account_number: None,
+            customer_id: None,
+            interest_method: None,
+            segment_rates: HashMap::new(),
+            authorization_expiry_days: None,
+            commitment_fee_schedule: None,
+            credit_line_review: None,
+            overlimit_policy: None,
+            time_provider: None,
+        }
+    }
+    
+    pub fn set_time(mut self, time: &SafeTimeProvider) -> Self {
+        self.time_provider = Some(time as *const SafeTimeProvider);
+        self
+    }
+    
+    pub fn facility_type(mut self, facility_type: RevolvingType) -> Self {
+        self.facility_type = Some(facility_type);
+        self
+    }
+    
+    pub fn credit_limit(mut self, limit: Money) -> Self {
+        self.credit_limit = Some(limit);
+        self
+    }
+    
+    pub fn rate(mut self, rate: Rate) -> Self {
+        self.rate = Some(rate);
+        self
+    }
+    
+    pub fn minimum_percentage(mut self, percentage: Decimal) -> Self {
+        self.minimum_percentage = Some(percentage);
+        self
+    }
+    
+    pub fn commitment_fee_rate(mut self, rate: Rate) -> Self {
+        self.commitment_fee_rate = Some(rate);
+        self
+    }
+    
+    pub fn property_value(mut self, value: Money) -> Self {
+        self.property_value = Some(value);
+        self
+    }
+    
+    pub fn draw_period_months(mut self, months: u32) -> Self {
+        self.draw_period = Some(months);
+        self
+    }
+    
+    pub fn repayment_period_months(mut self, months: u32) -> Self {
+        self.repayment_period = Some(months);
+        self
+    }
+    
+    pub fn account_number(mut self, account: String) -> Self {
+        self.account_number = Some(account);
+        self
+    }
+    
+    pub fn customer_id(mut self, customer: String) -> Self {
+        self.customer_id = Some(customer);
+        self
+    }
+    
+    pub fn interest_method(mut self, method: InterestMethod) -> Self {
+        self.interest_method = Some(method);
+        self
+    }
+    
+    pub fn segment_rate(mut self, segment: BalanceSegment, rate: Rate) -> Self {
+        self.segment_rates.insert(segment, rate);
+        self
+    }
+    
+    pub fn authorization_expiry_days(mut self, days: u32) -> Self {
+        self.authorization_expiry_days = Some(days);
+        self
+    }
+    
+    pub fn commitment_fee_billing(mut self, frequency: FeeBillingFrequency) -> Self {
+        self.commitment_fee_schedule.get_or_insert_with(CommitmentFeeSchedule::default)
+            .billing_frequency = frequency;
+        self
+    }
+    
+    pub fn commitment_fee_grid(mut self, grid: Vec<UtilizationFeeTier>) -> Self {
+        self.commitment_fee_schedule.get_or_insert_with(CommitmentFeeSchedule::default)
+            .utilization_grid = grid;
+        self
+    }
+    
+    pub fn credit_line_review(mut self, policy: CreditLineReviewPolicy) -> Self {
+        self.credit_line_review = Some(policy);
+        self
+    }
+    
+    pub fn overlimit_policy(mut self, policy: OverlimitPolicy) -> Self {
+        self.overlimit_policy = Some(policy);
+        self
+    }
+    
+    /// Build with stored time or system time if not set
+    pub fn build(self) -> Result<RevolvingFacility> {
+        if let Some(time_ptr) = self.time_provider {
+            let time = unsafe { time_ptr.as_ref() }
+                .ok_or(FacilityError::InvalidConfiguration {
+                    message: "Invalid time provider reference".to_string(),
+                })?;
+            self.build_with_time(time)
+        } else {
+            let time = SafeTimeProvider::new(hourglass_rs::TimeSource::System);
+            self.build_with_time(&time)
+        }
+    }
+    
+    /// Build with system time
+    pub fn build_now(self) -> Result<RevolvingFacility> {
+        let time = SafeTimeProvider::new(hourglass_rs::TimeSource::System);
+        self.build_with_time(&time)
+    }
+    
+    /// Build with explicit time provider (for backward compatibility)
+    pub fn build_with_time(self, time_provider: &SafeTimeProvider) -> Result<RevolvingFacility> {
+        // Infer facility type based on provided values, or default to LineOfCredit
+        let facility_type = self.facility_type.unwrap_or_else(|| {
+            if self.property_value.is_some() {
+                RevolvingType::HELOC  // Home equity line of credit
+            } else if self.minimum_percentage == Some(dec!(0.02)) || self.minimum_percentage == Some(dec!(0.025)) {
+                RevolvingType::CreditCard  // Credit cards typically have 2-2.5% minimum
+            } else {
+                RevolvingType::LineOfCredit  // General purpose line of credit
+            }
+        });
+        
+        let credit_limit = self.credit_limit.ok_or(FacilityError::InvalidConfiguration {
+            message: "Credit limit required".to_string(),
+        })?;
+        
+        let rate = self.rate.ok_or(FacilityError::InvalidConfiguration {
+            message: "Rate required".to_string(),
+        })?;
+        
+        let mut config = match facility_type {
+            RevolvingType::CreditCard => {
+                FacilityConfig::credit_card(
+                    credit_limit,
+                    rate,
+                    self.minimum_percentage.unwrap_or(dec!(0.02)),
+                )
+            }
+            RevolvingType::LineOfCredit => {
+                FacilityConfig::line_of_credit(
+                    credit_limit,
+                    rate,
+                    self.commitment_fee_rate.unwrap_or(Rate::from_decimal(dec!(0.005))),
+                )
+            }
+            RevolvingType::HELOC => {
+                let property_value = self.property_value.ok_or(FacilityError::InvalidConfiguration {
+                    message: "Property value required for HELOC".to_string(),
+                })?;
+                
+                FacilityConfig::heloc(
+                    credit_limit,
+                    rate,
+                    property_value,
+                    self.draw_period.unwrap_or(120), // 10 years default
+                    self.repayment_period.unwrap_or(240), // 20 years default
+                )
+            }
+        };
+        
+        if let Some(method) = self.interest_method {
+            config.interest_config.interest_method = method;
+        }
+        
+        if let Some(schedule) = self.commitment_fee_schedule {
+            config.fee_config.commitment_fee_schedule = Some(schedule);
+        }
+        
+        if let Some(policy) = self.credit_line_review {
+            config.limits.credit_line_review = Some(policy);
+        }
+        
+        if let Some(policy) = self.overlimit_policy {
+            config.limits.overlimit_policy = policy;
+        }
+        
+        let account_number = self.account_number.unwrap_or_else(|| {
+            format!("REV-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
+        });
+        
+        let customer_id = self.customer_id.unwrap_or_else(|| {
+            format!("CUST-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
+        });
+        
+        let facility = Facility::originate(config, account_number, customer_id, time_provider)?;
+        
+        let mut revolving = RevolvingFacility::new(facility)?;
+        revolving.segment_rates = self.segment_rates;
+        if let Some(days) = self.authorization_expiry_days {
+            revolving.authorization_expiry_days = days;
+        }
+        
+        // If time was set in builder, pass it to the facility
+        if let Some(time_ptr) = self.time_provider {
+            revolving.time = Some(time_ptr);
+        }
+        
+        Ok(revolving)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hourglass_rs::TimeSource;
+    use chrono::TimeZone;
+    
+    #[test]
+    fn test_credit_card_creation() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .minimum_percentage(dec!(0.02))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        
+        assert_eq!(card.credit_limit, Money::from_major(10_000));
+        assert_eq!(card.available_credit, Money::from_major(10_000));
+        assert_eq!(card.utilization_rate(), Rate::from_percentage(0));
+    }
+    
+    #[test]
+    fn test_draw_and_repay_cycle() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        
+        // draw funds
+        let drawn = card.draw(Money::from_major(3_000)).unwrap();
+        assert_eq!(drawn, Money::from_major(3_000));
+        assert_eq!(card.available_credit, Money::from_major(7_000));
+        assert_eq!(card.utilization_rate(), Rate::from_percentage(30));
+        
+        // accrue interest for a month
+        control.advance(chrono::Duration::days(30));
+        card.facility_mut().accrue_interest(&time).unwrap();
+        
+        // make payment
+        card.process_payment(Money::from_major(1_000)).unwrap();
+        
+        // available credit increased
+        assert!(card.available_credit > Money::from_major(7_000));
+        
+        // can redraw
+        card.draw(Money::from_major(500)).unwrap();
+        assert!(card.available_credit >= Money::from_major(7_400)); // roughly 7500 minus some interest
+    }
+    
+    #[test]
+    fn test_utilization_states() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        let control = time.test_control().unwrap();
+        
+        // test different utilization levels
+        assert_eq!(card.utilization_state(), UtilizationState::Unused);
+        
+        card.draw(Money::from_major(2_000)).unwrap();
+        assert_eq!(card.utilization_state(), UtilizationState::Low);
+        
+        card.draw(Money::from_major(3_000)).unwrap();
+        assert_eq!(card.utilization_state(), UtilizationState::Moderate);
+        
+        // next day, within the daily transaction limit
+        control.advance(chrono::Duration::days(1));
+        card.draw(Money::from_major(3_000)).unwrap();
+        assert_eq!(card.utilization_state(), UtilizationState::High);
+        
+        card.draw(Money::from_major(1_500)).unwrap();
+        assert_eq!(card.utilization_state(), UtilizationState::Maxed);
+    }
+    
+    #[test]
+    fn test_overlimit_handling() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(1_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        
+        // max out the card
+        card.draw(Money::from_major(1_000)).unwrap();
+        assert_eq!(card.available_credit, Money::ZERO);
+        
+        // try to go overlimit (should work with fee)
+        card.draw(Money::from_major(50)).unwrap();
+        assert!(card.is_overlimit());
+        assert_eq!(card.utilization_state(), UtilizationState::Overlimit);
+        
+        // check that overlimit fee was applied
+        assert!(card.facility.state.accrued_fees > Money::ZERO);
+        
+        // try to exceed 10% overlimit (should fail)
+        let result = card.draw(Money::from_major(100));
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_minimum_payment_calculation() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .minimum_percentage(dec!(0.02))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
+        
+        // accrue interest
+        control.advance(chrono::Duration::days(30));
+        card.facility_mut().accrue_interest(&time).unwrap();
+        
+        let minimum = card.calculate_minimum_payment();
+        
+        // should be at least 2% of balance + interest
+        let expected = Money::from_major(20) + card.facility.state.accrued_interest;
+        assert!(minimum >= expected);
+        
+        // should also be at least $25
+        assert!(minimum >= Money::from_major(25));
+    }
+    
+    #[test]
+    fn test_commitment_fee() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut loc = RevolvingFacility::builder()
+            .facility_type(RevolvingType::LineOfCredit)
+            .credit_limit(Money::from_major(500_000))
+            .rate(Rate::from_percentage(8))
+            .commitment_fee_rate(Rate::from_decimal(dec!(0.005)))
+            .set_time(&time).build()
+            .unwrap();
+        
+        loc.activate().unwrap();
+        
+        // draw partial amount
+        loc.draw(Money::from_major(200_000)).unwrap();
+        
+        // get initial fees (origination fee)
+        let initial_fees = loc.facility.state.accrued_fees;
+        
+        // nothing is billed before the period ends
+        control.advance(chrono::Duration::days(15));
+        assert_eq!(loc.charge_commitment_fee().unwrap(), Money::ZERO);
+        
+        // draw again mid-month, reducing the undrawn amount to $200k
+        loc.draw(Money::from_major(100_000)).unwrap();
+        
+        control.advance(chrono::Duration::days(16));
+        let fee = loc.charge_commitment_fee().unwrap();
+        
+        // 15 days on $300k and 16 days on $200k at 0.5% actual/360
+        let expected = Money::from_decimal(
+            (dec!(300_000) * dec!(15) + dec!(200_000) * dec!(16)) * dec!(0.005) / dec!(360)
+        );
+        assert_eq!(fee.round_dp(6), expected.round_dp(6));
+        assert_eq!(loc.facility.state.accrued_fees - initial_fees, fee);
+        
+        // calling again does not double-charge
+        assert_eq!(loc.charge_commitment_fee().unwrap(), Money::ZERO);
+    }
+    
+    #[test]
+    fn test_commitment_fee_utilization_grid_quarterly() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut loc = RevolvingFacility::builder()
+            .facility_type(RevolvingType::LineOfCredit)
+            .credit_limit(Money::from_major(1_000_000))
+            .rate(Rate::from_percentage(8))
+            .commitment_fee_billing(FeeBillingFrequency::Quarterly)
+            .commitment_fee_grid(vec![
+                UtilizationFeeTier { max_utilization: Rate::from_percentage(33), rate: Rate::from_bps(50) },
+                UtilizationFeeTier { max_utilization: Rate::from_percentage(66), rate: Rate::from_bps(35) },
+                UtilizationFeeTier { max_utilization: Rate::from_percentage(100), rate: Rate::from_bps(25) },
+            ])
+            .set_time(&time).build()
+            .unwrap();
+        
+        loc.activate().unwrap();
+        loc.draw(Money::from_major(500_000)).unwrap();
+        assert_eq!(loc.commitment_fee_rate(), Some(Rate::from_bps(35)));
+        
+        // monthly calls do not bill before the quarter ends
+        control.advance(chrono::Duration::days(31));
+        assert_eq!(loc.charge_commitment_fee().unwrap(), Money::ZERO);
+        assert!(loc.unbilled_commitment_fee() > Money::ZERO);
+        
+        // quarter ends 2024-04-01 after 91 days
+        control.advance(chrono::Duration::days(60));
+        let fee = loc.charge_commitment_fee().unwrap();
+        let expected = Money::from_decimal(dec!(500_000) * dec!(0.0035) / dec!(360) * dec!(91));
+        assert_eq!(fee.round_dp(6), expected.round_dp(6));
+    }
+    
+    #[test]
+    fn test_credit_limit_change() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        card.draw(Money::from_major(4_000)).unwrap();
+        
+        // increase limit
+        card.change_credit_limit(Money::from_major(15_000)).unwrap();
+        assert_eq!(card.credit_limit, Money::from_major(15_000));
+        assert_eq!(card.available_credit, Money::from_major(11_000));
+        
+        // decrease limit below outstanding (triggers overlimit)
+        card.change_credit_limit(Money::from_major(3_000)).unwrap();
+        assert!(card.is_overlimit());
+        assert_eq!(card.available_credit, Money::ZERO);
+    }
+    
+    #[test]
+    fn test_heloc_draw_period() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut heloc = RevolvingFacility::builder()
+            .facility_type(RevolvingType::HELOC)
+            .credit_limit(Money::from_major(100_000))
+            .rate(Rate::from_percentage(6))
+            .property_value(Money::from_major(400_000))
+            .draw_period_months(120)
+            .repayment_period_months(240)
+            .set_time(&time).build()
+            .unwrap();
+        
+        heloc.activate().unwrap();
+        
+        // can draw during draw period
+        assert!(heloc.is_in_draw_period);
+        heloc.draw(Money::from_major(50_000)).unwrap();
+        
+        // end draw period
+        heloc.end_draw_period().unwrap();
+        assert!(!heloc.is_in_draw_period);
+        
+        // cannot draw after draw period ends
+        let result = heloc.draw(Money::from_major(10_000));
+        assert!(result.is_err());
+        
+        // minimum payment follows the 240 month repayment schedule
+        let schedule = heloc.schedule().unwrap();
+        assert_eq!(schedule.term_months, 240);
+        assert_eq!(schedule.principal, Money::from_major(50_000));
+        let minimum = heloc.calculate_minimum_payment();
+        let scheduled = schedule.get_payment(1).unwrap().payment_amount;
+        assert_eq!(minimum, scheduled + heloc.facility.state.accrued_fees);
+        assert!(scheduled > Money::from_major(350));
+    }
+    
+    #[test]
+    fn test_heloc_automatic_conversion_and_repayment() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut heloc = RevolvingFacility::builder()
+            .facility_type(RevolvingType::HELOC)
+            .credit_limit(Money::from_major(100_000))
+            .rate(Rate::from_percentage(6))
+            .property_value(Money::from_major(400_000))
+            .draw_period_months(12)
+            .repayment_period_months(24)
+            .set_time(&time).build()
+            .unwrap();
+        
+        heloc.activate().unwrap();
+        assert_eq!(
+            heloc.draw_period_ends(),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            heloc.repayment_period_ends(),
+            Some(Utc.with_ymd_and_hms(2027, 1, 1, 0, 0, 0).unwrap())
+        );
+        heloc.draw(Money::from_major(24_000)).unwrap();
+        
+        // draw period ends on its configured date
+        control.advance(chrono::Duration::days(366));
+        heloc.update_daily_status().unwrap();
+        assert!(!heloc.is_in_draw_period);
+        assert!(heloc.draw(Money::from_major(1_000)).is_err());
+        
+        let schedule = heloc.schedule().unwrap().clone();
+        assert_eq!(schedule.term_months, 24);
+        
+        // statements bill the scheduled payment on its date
+        control.advance(chrono::Duration::days(10));
+        let statement = heloc.cut_statement().unwrap();
+        assert_eq!(statement.minimum_payment, schedule.payments[0].payment_amount);
+        assert_eq!(statement.payment_due_date, schedule.payments[0].payment_date);
+        assert_eq!(heloc.facility.state.next_payment_due, Some(schedule.payments[0].payment_date));
+        
+        // follow the schedule to payoff
+        for payment in &schedule.payments {
+            control.set(payment.payment_date);
+            heloc.process_scheduled_payment().unwrap();
+        }
+        
+        assert_eq!(heloc.facility.state.status, FacilityStatus::Settled);
+        assert_eq!(heloc.facility.state.outstanding_principal, Money::ZERO);
+    }
+    
+    #[test]
+    fn test_average_daily_balance_statement() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .interest_method(InterestMethod::AverageDailyBalance { include_new_transactions: true })
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
+        
+        control.advance(chrono::Duration::days(15));
+        card.draw(Money::from_major(2_000)).unwrap();
+        
+        // no interest accrues daily under average daily balance
+        control.advance(chrono::Duration::days(15));
+        assert!(card.accrue_interest().unwrap().is_empty());
+        assert_eq!(card.facility.state.accrued_interest, Money::ZERO);
+        
+        let statement = card.cut_statement().unwrap();
+        
+        // $1,000 for 15 days then $3,000 for 15 days at 18%
+        let purchase = &statement.segment_interest[&BalanceSegment::Purchase];
+        assert_eq!(purchase.days, 30);
+        assert!(purchase.average_daily_balance > Money::from_major(2_000));
+        assert_eq!(statement.interest_charged.round_dp(2), Money::from_str_exact("29.75").unwrap());
+        assert_eq!(card.facility.state.accrued_interest, statement.interest_charged);
+        assert_eq!(
+            statement.payment_due_date,
+            Utc.with_ymd_and_hms(2024, 2, 25, 0, 0, 0).unwrap()
+        );
+        assert!(statement.minimum_payment >= Money::from_major(25));
+    }
+    
+    #[test]
+    fn test_transaction_fees_by_draw_type() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        
+        // cash advance: greater of $10 or 5%
+        card.draw_with_type(Money::from_major(100), DrawType::CashAdvance).unwrap();
+        assert_eq!(card.segment_fees(BalanceSegment::CashAdvance), Money::from_major(10));
+        assert_eq!(card.segment_balance(BalanceSegment::CashAdvance), Money::from_major(100));
+        
+        // balance transfer: 3% with a cap
+        card.facility_mut().config.fee_config.balance_transfer_fee.as_mut().unwrap().cap =
+            Some(Money::from_major(50));
+        card.draw_with_type(Money::from_major(2_000), DrawType::BalanceTransfer).unwrap();
+        assert_eq!(card.segment_fees(BalanceSegment::BalanceTransfer), Money::from_major(50));
+        
+        // foreign purchase: 3%, no fee on domestic purchases
+        card.draw(Money::from_major(500)).unwrap();
+        card.draw_with_type(Money::from_major(1_000), DrawType::ForeignPurchase).unwrap();
+        assert_eq!(card.segment_fees(BalanceSegment::Purchase), Money::from_major(30));
+        assert_eq!(card.segment_balance(BalanceSegment::Purchase), Money::from_major(1_500));
+        
+        assert_eq!(card.facility.state.accrued_fees, Money::from_major(90));
+        let fee_events = card.facility.events.events().iter()
+            .filter(|e| matches!(e, Event::TransactionFeeCharged { .. }))
+            .count();
+        assert_eq!(fee_events, 3);
+    }
+    
+    #[test]
+    fn test_fee_payments_release_segment_fees_by_type() {
+        use crate::payments::{PaymentWaterfall, WaterfallComponent};
+        
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        card.facility_mut().config.payment_config.waterfall = PaymentWaterfall::new(vec![
+            WaterfallComponent::FeeType("late_fee".to_string()),
+            WaterfallComponent::Fees,
+            WaterfallComponent::Interest,
+            WaterfallComponent::Principal,
+        ]);
+        card.activate().unwrap();
+        
+        card.draw_with_type(Money::from_major(100), DrawType::CashAdvance).unwrap();
+        card.facility_mut().state.charge_fee("late_fee", Money::from_major(39));
+        assert_eq!(card.facility.state.fee_balances.get("cash_advance_fee"), Some(&Money::from_major(10)));
+        
+        // paying the late fee leaves the cash advance fee on its segment
+        card.process_payment(Money::from_major(39)).unwrap();
+        assert_eq!(card.facility.state.accrued_fees, Money::from_major(10));
+        assert_eq!(card.segment_fees(BalanceSegment::CashAdvance), Money::from_major(10));
+        
+        card.process_payment(Money::from_major(25)).unwrap();
+        assert_eq!(card.facility.state.accrued_fees, Money::ZERO);
+        assert_eq!(card.segment_fees(BalanceSegment::CashAdvance), Money::ZERO);
+    }
+    
+    #[test]
+    fn test_annual_fee_anniversary_and_refund() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        
+        // first year is billed at the first statement
+        control.advance(chrono::Duration::days(30));
+        let statement = card.cut_statement().unwrap();
+        assert_eq!(statement.annual_fee_charged, Money::from_major(95));
+        
+        // nothing more until the anniversary
+        control.advance(chrono::Duration::days(300));
+        assert_eq!(card.assess_annual_fee().unwrap(), Money::ZERO);
+        
+        control.advance(chrono::Duration::days(40));
+        assert_eq!(card.assess_annual_fee().unwrap(), Money::from_major(95));
+        
+        // closing 9 days after the anniversary refunds the unused share
+        control.advance(chrono::Duration::days(5));
+        let refund = card.close_account().unwrap();
+        let expected = Money::from_major(95) * (dec!(356) / dec!(365));
+        assert_eq!(refund, expected);
+        assert!(card.is_closed());
+        assert!(card.draw(Money::from_major(100)).is_err());
+        
+        // the refund cancels the unpaid annual fee
+        let remaining_fee = Money::from_major(190) - refund;
+        assert_eq!(card.facility.state.fee_balances.get("annual_fee"), Some(&remaining_fee));
+        assert_eq!(card.segment_fees(BalanceSegment::Purchase), remaining_fee);
+    }
+    
+    #[test]
+    fn test_annual_fee_refund_beyond_balance_is_returned() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        card.activate().unwrap();
+        card.draw(Money::from_major(20)).unwrap();
+        
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
+        let outstanding = card.facility.state.total_outstanding();
+        card.process_payment(outstanding - Money::from_major(10)).unwrap();
+        
+        // pay the anniversary fee in full, then close within the refund window
+        control.advance(chrono::Duration::days(340));
+        assert_eq!(card.assess_annual_fee().unwrap(), Money::from_major(95));
+        let state = &card.facility.state;
+        card.process_payment(state.total_outstanding() - state.outstanding_principal).unwrap();
+        assert_eq!(card.segment_fees(BalanceSegment::Purchase), Money::ZERO);
+        
+        control.advance(chrono::Duration::days(5));
+        let principal = card.facility.state.outstanding_principal;
+        let refund = card.close_account().unwrap();
+        assert!(refund > principal);
+        assert!(card.facility.events.events().iter().any(|e| matches!(
+            e,
+            Event::AnnualFeeRefunded { amount, unapplied, .. } if *amount == refund && *unapplied == refund - principal
+        )));
+        assert_eq!(card.facility.state.status, FacilityStatus::Settled);
+    }
+    
+    #[test]
+    fn test_monthly_annual_fee_with_first_year_waived() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.facility_mut().config.fee_config.annual_fee_policy = Some(crate::config::AnnualFeePolicy {
+            billing_frequency: FeeBillingFrequency::Monthly,
+            waive_first_year: true,
+            refund_window_days: None,
+        });
+        card.activate().unwrap();
+        
+        // months 1-12 waived, months 13 and 14 billed
+        control.advance(chrono::Duration::days(400));
+        let charged = card.assess_annual_fee().unwrap();
+        assert_eq!(charged, Money::from_major(95) / dec!(12) * dec!(2));
+        
+        let waived = card.facility.events.events().iter()
+            .filter(|e| matches!(e, Event::AnnualFeeAssessed { waived: true, .. }))
+            .count();
+        assert_eq!(waived, 12);
+    }
+    
+    #[test]
+    fn test_authorization_hold_and_capture() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        
+        // hold reduces available credit but is not posted
+        let id = card.authorize(Money::from_major(500), DrawType::Purchase).unwrap();
+        card.increment_authorization(id, Money::from_major(100)).unwrap();
+        assert_eq!(card.pending_authorizations(), Money::from_major(600));
+        assert_eq!(card.available_credit(), Money::from_major(9_400));
+        assert_eq!(card.facility.state.outstanding_principal, Money::ZERO);
+        
+        // held amounts accrue no interest
+        control.advance(chrono::Duration::days(2));
+        card.accrue_interest().unwrap();
+        assert_eq!(card.facility.state.accrued_interest, Money::ZERO);
+        
+        // capture for less than the hold
+        card.capture_authorization(id, Money::from_major(550)).unwrap();
+        assert_eq!(card.pending_authorizations(), Money::ZERO);
+        assert_eq!(card.facility.state.outstanding_principal, Money::from_major(550));
+        assert_eq!(card.available_credit(), Money::from_major(9_450));
+        assert!(card.capture_authorization(id, Money::from_major(550)).is_err());
+        
+        // reversal restores available credit
+        let id = card.authorize(Money::from_major(1_000), DrawType::Purchase).unwrap();
+        card.reverse_authorization(id).unwrap();
+        assert_eq!(card.available_credit(), Money::from_major(9_450));
+    }
+    
+    #[test]
+    fn test_authorization_expiry_and_overlimit() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(1_000))
+            .rate(Rate::from_percentage(18))
+            .authorization_expiry_days(5)
+            .set_time(&time).build()
+            .unwrap();
+        
+        card.activate().unwrap();
+        card.authorize(Money::from_major(900), DrawType::Purchase).unwrap();
+        
+        // holds count toward the overlimit tolerance
+        assert!(card.draw(Money::from_major(250)).is_err());
+        assert!(card.authorize(Money::from_major(250), DrawType::Purchase).is_err());
+        
+        // expired holds are released
+        control.advance(chrono::Duration::days(5));
+        let expired = card.expire_authorizations().unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(card.available_credit(), Money::from_major(1_000));
+        card.draw(Money::from_major(250)).unwrap();
+    }
+    
+    #[test]
+    fn test_penalty_apr_trigger_and_cure() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+        
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
         
-        let customer_id = self.customer_id.unwrap_or_else(|| {
-            format!("CUST-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
-        });
+        card.facility_mut().config.interest_config.penalty_apr.as_mut().unwrap()
+            .cure_after_on_time_payments = 2;
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
         
-        let facility = Facility::originate(config, account_number, customer_id, time_provider)?;
+        // first statement is due 2024-02-25 and goes unpaid
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
         
-        let mut revolving = RevolvingFacility::new(facility)?;
+        control.set(Utc.with_ymd_and_hms(2024, 4, 24, 0, 0, 0).unwrap());
+        card.update_daily_status().unwrap();
+        assert!(!card.is_penalty_apr_active());
         
-        // If time was set in builder, pass it to the facility
-        if let Some(time_ptr) = self.time_provider {
-            revolving.time = Some(time_ptr);
+        // 60 days past due triggers the penalty apr for new transactions
+        control.advance(chrono::Duration::days(1));
+        card.update_daily_status().unwrap();
+        assert!(card.is_penalty_apr_active());
+        assert_eq!(card.segment_rate(BalanceSegment::Penalty), Rate::from_percentage(29));
+        assert_eq!(card.segment_rate(BalanceSegment::Purchase), Rate::from_percentage(18));
+        
+        card.draw(Money::from_major(100)).unwrap();
+        assert_eq!(card.segment_balance(BalanceSegment::Penalty), Money::from_major(100));
+        
+        // the new balance accrues at the penalty rate
+        let interest_before = card.facility.state.accrued_interest;
+        control.advance(chrono::Duration::days(10));
+        card.accrue_interest().unwrap();
+        let principal = card.facility.state.outstanding_principal.as_decimal();
+        let expected = Money::from_decimal(
+            (principal * dec!(0.18) + dec!(100) * dec!(0.11)) / dec!(365) * dec!(10)
+        );
+        assert_eq!(
+            (card.facility.state.accrued_interest - interest_before).round_dp(4),
+            expected.round_dp(4)
+        );
+        
+        // two on-time minimum payments cure the penalty apr
+        for _ in 0..2 {
+            let statement = card.cut_statement().unwrap();
+            control.advance(chrono::Duration::days(20));
+            card.process_payment(statement.minimum_payment).unwrap();
+            control.advance(chrono::Duration::days(10));
         }
+        assert!(card.is_penalty_apr_active());
+        card.cut_statement().unwrap();
         
-        Ok(revolving)
+        assert!(!card.is_penalty_apr_active());
+        assert_eq!(card.segment_balance(BalanceSegment::Penalty), Money::ZERO);
+        
+        let reasons: Vec<String> = card.facility.events.events().iter()
+            .filter_map(|e| match e {
+                Event::InterestRateChanged { reason, .. } => Some(reason.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reasons.len(), 2);
+        assert!(reasons[0].contains("60 days past due"));
+        assert!(reasons[1].contains("2 consecutive on-time payments"));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use hourglass_rs::TimeSource;
-    use chrono::TimeZone;
     
     #[test]
-    fn test_credit_card_creation() {
+    fn test_default_cures_once_payment_brings_account_current() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
+        let control = time.test_control().unwrap();
         
-        let card = RevolvingFacility::builder()
+        let mut card = RevolvingFacility::builder()
             .facility_type(RevolvingType::CreditCard)
             .credit_limit(Money::from_major(10_000))
             .rate(Rate::from_percentage(18))
-            .minimum_percentage(dec!(0.02))
-            .set_time(&time)
-            .build()
+            .set_time(&time).build()
             .unwrap();
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
         
-        assert_eq!(card.credit_limit, Money::from_major(10_000));
-        assert_eq!(card.available_credit, Money::from_major(10_000));
-        assert_eq!(card.utilization_rate(), Rate::from_percentage(0));
+        // first statement is due 2024-02-25 and goes unpaid for 95 days
+        control.advance(chrono::Duration::days(30));
+        let statement = card.cut_statement().unwrap();
+        control.set(Utc.with_ymd_and_hms(2024, 5, 30, 0, 0, 0).unwrap());
+        card.update_daily_status().unwrap();
+        assert_eq!(card.facility.state.status, FacilityStatus::Default);
+        
+        let state = &card.facility.state;
+        card.process_payment(statement.minimum_payment + state.accrued_interest + state.accrued_fees).unwrap();
+        card.update_daily_status().unwrap();
+        assert_eq!(card.facility.state.days_past_due, 0);
+        assert_eq!(card.facility.state.delinquency_bucket, crate::types::DelinquencyBucket::Current);
+        assert_eq!(card.facility.state.status, FacilityStatus::Active);
+        assert!(!card.facility.state.non_accrual);
+        card.draw(Money::from_major(100)).unwrap();
     }
     
     #[test]
-    fn test_draw_and_repay_cycle() {
+    fn test_late_payment_clears_delinquency_bucket_with_status() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
@@ -754,32 +3801,68 @@ mod tests {
             .rate(Rate::from_percentage(18))
             .set_time(&time).build()
             .unwrap();
-        
         card.activate().unwrap();
+        card.draw(Money::from_major(5_000)).unwrap();
         
-        // draw funds
-        let drawn = card.draw(Money::from_major(3_000)).unwrap();
-        assert_eq!(drawn, Money::from_major(3_000));
-        assert_eq!(card.available_credit, Money::from_major(7_000));
-        assert_eq!(card.utilization_rate(), Rate::from_percentage(30));
-        
-        // accrue interest for a month
         control.advance(chrono::Duration::days(30));
-        card.facility_mut().accrue_interest(&time).unwrap();
+        let statement = card.cut_statement().unwrap();
+        control.set(statement.payment_due_date + chrono::Duration::days(40));
+        card.update_daily_status().unwrap();
+        assert_eq!(card.facility.state.delinquency_bucket, crate::types::DelinquencyBucket::Days30To59);
         
-        // make payment
-        card.process_payment(Money::from_major(1_000)).unwrap();
+        // a late payment below the minimum still brings the status back, and
+        // the bucket follows it
+        let partial = Money::from_major(30);
+        assert!(partial < statement.minimum_payment);
+        card.process_payment(partial).unwrap();
+        card.update_daily_status().unwrap();
+        assert_eq!(card.facility.state.status, FacilityStatus::Active);
+        assert_eq!(card.facility.state.days_past_due, 0);
+        assert_eq!(card.facility.state.delinquency_bucket, crate::types::DelinquencyBucket::Current);
+    }
+    
+    #[test]
+    fn test_default_rate_replaces_penalty_apr() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
         
-        // available credit increased
-        assert!(card.available_credit > Money::from_major(7_000));
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
         
-        // can redraw
-        card.draw(Money::from_major(500)).unwrap();
-        assert!(card.available_credit >= Money::from_major(7_400)); // roughly 7500 minus some interest
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
+        
+        // penalty apr at 60 days past due, default at 90
+        control.set(Utc.with_ymd_and_hms(2024, 4, 25, 0, 0, 0).unwrap());
+        card.update_daily_status().unwrap();
+        card.draw(Money::from_major(100)).unwrap();
+        control.set(Utc.with_ymd_and_hms(2024, 5, 30, 0, 0, 0).unwrap());
+        card.update_daily_status().unwrap();
+        assert!(card.is_penalty_apr_active());
+        assert_eq!(card.facility.state.status, FacilityStatus::Default);
+        
+        let interest_before = card.facility.state.accrued_interest;
+        control.advance(chrono::Duration::days(1));
+        card.update_daily_status().unwrap();
+        let default_rate = card.facility.config.interest_config.default_rate.unwrap();
+        let principal = card.facility.state.outstanding_principal.as_decimal();
+        let expected = Money::from_decimal(principal * default_rate.as_decimal() / dec!(365));
+        assert_eq!(
+            (card.facility.state.accrued_interest - interest_before).round_dp(4),
+            expected.round_dp(4)
+        );
     }
     
     #[test]
-    fn test_utilization_states() {
+    fn test_minimum_payment_payoff_disclosure() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
@@ -788,61 +3871,146 @@ mod tests {
             .facility_type(RevolvingType::CreditCard)
             .credit_limit(Money::from_major(10_000))
             .rate(Rate::from_percentage(18))
+            .minimum_percentage(dec!(0.02))
             .set_time(&time).build()
             .unwrap();
         
         card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
         
-        // test different utilization levels
-        assert_eq!(card.utilization_state(), UtilizationState::Unused);
+        // 2% plus interest with a $25 floor
+        let disclosure = card.payoff_disclosure();
+        assert_eq!(disclosure.months_to_payoff, Some(54));
+        assert_eq!(disclosure.total_interest.round_dp(2), Money::from_str_exact("420.89").unwrap());
+        assert_eq!(disclosure.total_paid.round_dp(2), Money::from_str_exact("1420.89").unwrap());
         
-        card.draw(Money::from_major(2_000)).unwrap();
-        assert_eq!(card.utilization_state(), UtilizationState::Low);
+        // 36 month payoff costs less
+        assert_eq!(disclosure.payment_for_36_months.round_dp(2), Money::from_str_exact("36.15").unwrap());
+        assert!(disclosure.total_paid_36_months < disclosure.total_paid);
         
-        card.draw(Money::from_major(3_000)).unwrap();
-        assert_eq!(card.utilization_state(), UtilizationState::Moderate);
+        // installment plan balances keep their own schedule
+        card.draw(Money::from_major(2_000)).unwrap();
+        card.create_installment_plan(
+            Money::from_major(2_000),
+            InstallmentPricing::Apr(Rate::from_percentage(9)),
+            12,
+        ).unwrap();
+        assert_eq!(card.payoff_disclosure(), disclosure);
+    }
+    
+    #[test]
+    fn test_installment_plan_billing_prepay_and_cancel() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
         
-        card.draw(Money::from_major(3_000)).unwrap();
-        assert_eq!(card.utilization_state(), UtilizationState::High);
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(10_000))
+            .rate(Rate::from_percentage(18))
+            .set_time(&time).build()
+            .unwrap();
         
+        card.activate().unwrap();
         card.draw(Money::from_major(1_500)).unwrap();
-        assert_eq!(card.utilization_state(), UtilizationState::Maxed);
+        
+        // carve $1,200 of the purchase balance into 12 installments with a $5 monthly fee
+        let plan_id = card.create_installment_plan(
+            Money::from_major(1_200),
+            InstallmentPricing::MonthlyFee(Money::from_major(5)),
+            12,
+        ).unwrap();
+        assert_eq!(card.segment_balance(BalanceSegment::Purchase), Money::from_major(300));
+        assert_eq!(card.installment_plan_balance(), Money::from_major(1_200));
+        assert_eq!(card.available_credit(), Money::from_major(8_500));
+        
+        // can't carve more than the purchase balance
+        assert!(card.create_installment_plan(
+            Money::from_major(500),
+            InstallmentPricing::MonthlyFee(Money::from_major(5)),
+            6,
+        ).is_err());
+        
+        // plan principal is not charged the revolving apr
+        control.advance(chrono::Duration::days(30));
+        let statement = card.cut_statement().unwrap();
+        let revolving_interest = Money::from_major(300) * (dec!(0.18) / dec!(365) * dec!(30));
+        assert_eq!(statement.interest_charged.round_dp(2), revolving_interest.round_dp(2));
+        
+        let plan = card.installment_plan(plan_id).unwrap();
+        assert_eq!(plan.installments_billed, 1);
+        assert_eq!(plan.remaining_principal, Money::from_major(1_100));
+        assert_eq!(card.segment_balance(BalanceSegment::Purchase), Money::from_major(400));
+        
+        // installment is due in full on top of the revolving minimum (annual fee + $5 plan fee)
+        let fees = card.facility().state.accrued_fees;
+        assert_eq!(fees, Money::from_major(100));
+        let revolving_minimum = Money::from_major(300) * dec!(0.02) + statement.interest_charged + fees;
+        assert_eq!(statement.minimum_payment, revolving_minimum + Money::from_major(100));
+        
+        // prepayment lowers the remaining installments over the same term,
+        // without curing the missed minimum payment
+        control.advance(chrono::Duration::days(30));
+        card.update_daily_status().unwrap();
+        let days_past_due = card.facility().state.days_past_due;
+        assert!(days_past_due > 0);
+        let applied = card.prepay_installment_plan(plan_id, Money::from_major(550)).unwrap();
+        assert_eq!(applied, Money::from_major(550));
+        assert_eq!(card.facility().state.days_past_due, days_past_due);
+        assert_eq!(card.facility().state.last_payment_date, None);
+        let plan = card.installment_plan(plan_id).unwrap();
+        assert_eq!(plan.remaining_principal, Money::from_major(550));
+        assert_eq!(plan.next_installment().unwrap().principal_portion.round_dp(2), Money::from_major(50));
+        assert_eq!(card.facility().state.outstanding_principal, Money::from_major(950));
+        
+        // cancelling returns the unbilled principal to the purchase balance
+        let returned = card.cancel_installment_plan(plan_id).unwrap();
+        assert_eq!(returned, Money::from_major(550));
+        assert_eq!(card.segment_balance(BalanceSegment::Purchase), Money::from_major(950));
+        assert_eq!(card.installment_plan(plan_id).unwrap().status, InstallmentPlanStatus::Cancelled);
+        assert!(card.prepay_installment_plan(plan_id, Money::from_major(10)).is_err());
     }
     
     #[test]
-    fn test_overlimit_handling() {
+    fn test_installment_plan_apr_and_payoff() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
+        let control = time.test_control().unwrap();
         
         let mut card = RevolvingFacility::builder()
             .facility_type(RevolvingType::CreditCard)
-            .credit_limit(Money::from_major(1_000))
+            .credit_limit(Money::from_major(10_000))
             .rate(Rate::from_percentage(18))
             .set_time(&time).build()
             .unwrap();
         
         card.activate().unwrap();
+        let plan_id = card.draw_installment_plan(
+            Money::from_major(1_000),
+            InstallmentPricing::Apr(Rate::from_percentage(12)),
+            6,
+        ).unwrap();
+        assert_eq!(card.segment_balance(BalanceSegment::Purchase), Money::ZERO);
         
-        // max out the card
-        card.draw(Money::from_major(1_000)).unwrap();
-        assert_eq!(card.available_credit, Money::ZERO);
-        
-        // try to go overlimit (should work with fee)
-        card.draw(Money::from_major(50)).unwrap();
-        assert!(card.is_overlimit());
-        assert_eq!(card.utilization_state(), UtilizationState::Overlimit);
+        // first installment bills one month of plan interest only
+        control.advance(chrono::Duration::days(30));
+        let statement = card.cut_statement().unwrap();
+        assert_eq!(statement.interest_charged, Money::from_major(10));
         
-        // check that overlimit fee was applied
-        assert!(card.facility.state.accrued_fees > Money::ZERO);
+        // paying the full balance retires the plan
+        let balance = card.facility().state.total_outstanding();
+        card.process_payment(balance).unwrap();
         
-        // try to exceed 10% overlimit (should fail)
-        let result = card.draw(Money::from_major(100));
-        assert!(result.is_err());
+        let plan = card.installment_plan(plan_id).unwrap();
+        assert_eq!(plan.status, InstallmentPlanStatus::PaidOff);
+        assert_eq!(card.installment_plan_balance(), Money::ZERO);
+        assert_eq!(card.facility().state.outstanding_principal, Money::ZERO);
     }
     
     #[test]
-    fn test_minimum_payment_calculation() {
+    fn test_credit_line_increase_after_on_time_streak() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
@@ -850,118 +4018,266 @@ mod tests {
         
         let mut card = RevolvingFacility::builder()
             .facility_type(RevolvingType::CreditCard)
-            .credit_limit(Money::from_major(10_000))
+            .credit_limit(Money::from_major(5_000))
             .rate(Rate::from_percentage(18))
-            .minimum_percentage(dec!(0.02))
+            .credit_line_review(CreditLineReviewPolicy::default())
             .set_time(&time).build()
             .unwrap();
         
         card.activate().unwrap();
-        card.draw(Money::from_major(1_000)).unwrap();
+        card.draw(Money::from_major(2_000)).unwrap();
         
-        // accrue interest
+        // six on-time minimum payments at around 40% utilization
+        for _ in 0..6 {
+            control.advance(chrono::Duration::days(30));
+            let statement = card.cut_statement().unwrap();
+            card.process_payment(statement.minimum_payment).unwrap();
+        }
+        assert_eq!(card.credit_limit(), Money::from_major(5_000));
+        
+        // the review falls due at the seventh statement
         control.advance(chrono::Duration::days(30));
-        card.facility_mut().accrue_interest(&time).unwrap();
+        card.cut_statement().unwrap();
+        assert_eq!(card.on_time_streak(), 6);
+        assert_eq!(card.credit_limit(), Money::from_major(6_000));
         
-        let minimum = card.calculate_minimum_payment();
+        let reasons: Vec<CreditLimitChangeReason> = card.facility.events.events().iter()
+            .filter_map(|e| match e {
+                Event::CreditLimitChanged { reason, .. } => Some(*reason),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reasons, vec![CreditLimitChangeReason::PaymentHistory]);
+    }
+    
+    #[test]
+    fn test_credit_line_reduced_when_utilization_rises() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
         
-        // should be at least 2% of balance + interest
-        let expected = Money::from_major(20) + card.facility.state.accrued_interest;
-        assert!(minimum >= expected);
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(5_000))
+            .rate(Rate::from_percentage(18))
+            .credit_line_review(CreditLineReviewPolicy::default())
+            .set_time(&time).build()
+            .unwrap();
         
-        // should also be at least $25
-        assert!(minimum >= Money::from_major(25));
+        card.activate().unwrap();
+        card.draw(Money::from_major(500)).unwrap();
+        
+        // six on-time payments, with use jumping from 10% to 50% halfway through
+        for cycle in 0..6 {
+            if cycle == 3 {
+                card.draw(Money::from_major(2_000)).unwrap();
+            }
+            control.advance(chrono::Duration::days(30));
+            let statement = card.cut_statement().unwrap();
+            card.process_payment(statement.minimum_payment).unwrap();
+        }
+        
+        // the average alone would earn an increase, the trend cuts the line instead
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
+        assert_eq!(card.on_time_streak(), 6);
+        assert_eq!(card.credit_limit(), Money::from_major(5_000));
+        
+        let decision = card.scheduled_limit_decrease().unwrap();
+        assert_eq!(decision.reason, CreditLimitChangeReason::RisingUtilization);
+        assert_eq!(decision.new_limit, Money::from_major(3_750));
     }
     
     #[test]
-    fn test_commitment_fee() {
+    fn test_credit_line_decrease_proposal_with_notice() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
+        let control = time.test_control().unwrap();
         
-        let mut loc = RevolvingFacility::builder()
-            .facility_type(RevolvingType::LineOfCredit)
-            .credit_limit(Money::from_major(500_000))
-            .rate(Rate::from_percentage(8))
-            .commitment_fee_rate(Rate::from_decimal(dec!(0.005)))
+        let policy = CreditLineReviewPolicy {
+            auto_apply: false,
+            ..CreditLineReviewPolicy::default()
+        };
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(1_000))
+            .rate(Rate::from_percentage(18))
+            .credit_line_review(policy)
             .set_time(&time).build()
             .unwrap();
         
-        loc.activate().unwrap();
+        card.activate().unwrap();
+        card.draw(Money::from_major(400)).unwrap();
         
-        // draw partial amount
-        loc.draw(Money::from_major(200_000)).unwrap();
+        // minimum missed for the first cycle
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
+        control.advance(chrono::Duration::days(30));
+        card.cut_statement().unwrap();
+        assert_eq!(card.on_time_streak(), 0);
         
-        // get initial fees (origination fee)
-        let initial_fees = loc.facility.state.accrued_fees;
+        // the decrease is proposed, not applied
+        let decision = card.review_credit_line().unwrap().unwrap();
+        assert_eq!(decision.reason, CreditLimitChangeReason::Delinquency);
+        assert_eq!(decision.new_limit, Money::from_major(750));
+        assert_eq!(card.credit_limit(), Money::from_major(1_000));
+        assert!(card.credit_line_proposal().is_some());
+        
+        // accepting starts the notice period
+        card.accept_credit_line_proposal().unwrap();
+        assert!(card.credit_line_proposal().is_none());
+        assert_eq!(card.credit_limit(), Money::from_major(1_000));
+        let effective = card.scheduled_limit_decrease().unwrap().effective_date;
+        assert_eq!(effective, time.now() + chrono::Duration::days(45));
         
-        // charge commitment fee on undrawn
-        loc.charge_commitment_fee().unwrap();
+        control.advance(chrono::Duration::days(44));
+        card.update_daily_status().unwrap();
+        assert_eq!(card.credit_limit(), Money::from_major(1_000));
         
-        // fee should be on $300k undrawn at 0.5% annual / 12
-        let expected_commitment_fee = Money::from_decimal(dec!(300_000) * dec!(0.005) / dec!(12));
-        assert_eq!(loc.facility.state.accrued_fees - initial_fees, expected_commitment_fee);
+        control.advance(chrono::Duration::days(1));
+        card.update_daily_status().unwrap();
+        assert_eq!(card.credit_limit(), Money::from_major(750));
+        assert!(card.scheduled_limit_decrease().is_none());
     }
     
     #[test]
-    fn test_credit_limit_change() {
+    fn test_transaction_and_payment_limits() {
         let time = SafeTimeProvider::new(TimeSource::Test(
-            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+            Utc.with_ymd_and_hms(2024, 1, 15, 9, 0, 0).unwrap()
         ));
+        let control = time.test_control().unwrap();
         
         let mut card = RevolvingFacility::builder()
             .facility_type(RevolvingType::CreditCard)
-            .credit_limit(Money::from_major(10_000))
+            .credit_limit(Money::from_major(20_000))
             .rate(Rate::from_percentage(18))
             .set_time(&time).build()
             .unwrap();
+        card.facility_mut().config.limits.monthly_transaction_limit = Some(Money::from_major(8_000));
         
         card.activate().unwrap();
         card.draw(Money::from_major(4_000)).unwrap();
         
-        // increase limit
-        card.change_credit_limit(Money::from_major(15_000)).unwrap();
-        assert_eq!(card.credit_limit, Money::from_major(15_000));
-        assert_eq!(card.available_credit, Money::from_major(11_000));
+        // $5,000 daily limit leaves $1,000 of headroom
+        match card.draw(Money::from_major(1_500)) {
+            Err(FacilityError::DailyTransactionLimitExceeded { remaining, .. }) => {
+                assert_eq!(remaining, Money::from_major(1_000));
+            }
+            other => panic!("expected daily limit error, got {:?}", other),
+        }
         
-        // decrease limit below outstanding (triggers overlimit)
-        card.change_credit_limit(Money::from_major(3_000)).unwrap();
-        assert!(card.is_overlimit());
-        assert_eq!(card.available_credit, Money::ZERO);
+        // holds are checked against the same headroom
+        assert!(card.authorize(Money::from_major(1_500), DrawType::Purchase).is_err());
+        
+        // as are increments and captures beyond the hold
+        let hold = card.authorize(Money::from_major(800), DrawType::Purchase).unwrap();
+        assert!(matches!(
+            card.increment_authorization(hold, Money::from_major(1_200)),
+            Err(FacilityError::DailyTransactionLimitExceeded { .. })
+        ));
+        assert!(matches!(
+            card.capture_authorization(hold, Money::from_major(2_000)),
+            Err(FacilityError::DailyTransactionLimitExceeded { .. })
+        ));
+        card.reverse_authorization(hold).unwrap();
+        
+        // pending holds count towards the limits until released
+        let hold = card.authorize(Money::from_major(600), DrawType::Purchase).unwrap();
+        match card.authorize(Money::from_major(600), DrawType::Purchase) {
+            Err(FacilityError::DailyTransactionLimitExceeded { remaining, .. }) => {
+                assert_eq!(remaining, Money::from_major(400));
+            }
+            other => panic!("expected daily limit error, got {:?}", other),
+        }
+        assert!(card.draw(Money::from_major(500)).is_err());
+        card.reverse_authorization(hold).unwrap();
+        
+        // the next calendar day resets daily usage but not monthly usage
+        control.advance(chrono::Duration::hours(16));
+        match card.draw(Money::from_major(5_000)) {
+            Err(FacilityError::MonthlyTransactionLimitExceeded { remaining, .. }) => {
+                assert_eq!(remaining, Money::from_major(4_000));
+            }
+            other => panic!("expected monthly limit error, got {:?}", other),
+        }
+        card.draw(Money::from_major(4_000)).unwrap();
+        
+        // a new month resets monthly usage
+        control.set(Utc.with_ymd_and_hms(2024, 2, 1, 9, 0, 0).unwrap());
+        card.draw(Money::from_major(5_000)).unwrap();
+        
+        // payments below the $25 minimum are rejected
+        match card.process_payment(Money::from_major(10)) {
+            Err(FacilityError::PaymentBelowMinimum { minimum, .. }) => {
+                assert_eq!(minimum, Money::from_major(25));
+            }
+            other => panic!("expected minimum payment error, got {:?}", other),
+        }
+        card.process_payment(Money::from_major(25)).unwrap();
     }
     
     #[test]
-    fn test_heloc_draw_period() {
+    fn test_opt_in_overlimit_policy() {
         let time = SafeTimeProvider::new(TimeSource::Test(
             Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
         ));
+        let control = time.test_control().unwrap();
         
-        let mut heloc = RevolvingFacility::builder()
-            .facility_type(RevolvingType::HELOC)
-            .credit_limit(Money::from_major(100_000))
-            .rate(Rate::from_percentage(6))
-            .property_value(Money::from_major(400_000))
-            .draw_period_months(120)
-            .repayment_period_months(240)
+        let mut card = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(1_000))
+            .rate(Rate::from_percentage(18))
+            .overlimit_policy(OverlimitPolicy::opt_in())
             .set_time(&time).build()
             .unwrap();
         
-        heloc.activate().unwrap();
+        card.activate().unwrap();
+        card.draw(Money::from_major(1_000)).unwrap();
         
-        // can draw during draw period
-        assert!(heloc.is_in_draw_period);
-        heloc.draw(Money::from_major(50_000)).unwrap();
+        // overlimit draws are declined until the customer opts in
+        assert!(card.draw(Money::from_major(50)).is_err());
+        card.set_overlimit_opt_in(true);
         
-        // end draw period
-        heloc.end_draw_period().unwrap();
-        assert!(!heloc.is_in_draw_period);
+        // one fee per cycle, for at most three consecutive cycles
+        card.draw(Money::from_major(20)).unwrap();
+        card.draw(Money::from_major(20)).unwrap();
+        for _ in 0..3 {
+            control.advance(chrono::Duration::days(30));
+            card.cut_statement().unwrap();
+            card.draw(Money::from_major(20)).unwrap();
+        }
         
-        // cannot draw after draw period ends
-        let result = heloc.draw(Money::from_major(10_000));
-        assert!(result.is_err());
+        let fees: Vec<Money> = card.facility.events.events().iter()
+            .filter_map(|e| match e {
+                Event::OverlimitOccurred { fees_applied, .. } => Some(*fees_applied),
+                _ => None,
+            })
+            .collect();
+        let charged = fees.iter().filter(|f| **f > Money::ZERO).count();
+        assert_eq!(fees.len(), 5);
+        assert_eq!(charged, 3);
         
-        // minimum payment increases for amortization
-        let minimum = heloc.calculate_minimum_payment();
-        assert!(minimum > Money::from_major(750)); // 1.5% of balance
+        // a decrease below the balance stops at the balance
+        card.change_credit_limit(Money::from_major(500)).unwrap();
+        assert_eq!(card.credit_limit(), Money::from_major(1_100));
+        
+        let mut strict = RevolvingFacility::builder()
+            .facility_type(RevolvingType::CreditCard)
+            .credit_limit(Money::from_major(1_000))
+            .rate(Rate::from_percentage(18))
+            .overlimit_policy(OverlimitPolicy::strict())
+            .set_time(&time).build()
+            .unwrap();
+        
+        strict.activate().unwrap();
+        strict.draw(Money::from_major(1_000)).unwrap();
+        assert!(strict.draw(Money::from_major(20)).is_err());
+        assert!(matches!(
+            strict.change_credit_limit(Money::from_major(900)),
+            Err(FacilityError::CreditLimitBelowBalance { .. })
+        ));
+        assert_eq!(strict.credit_limit(), Money::from_major(1_000));
     }
-}
\ No newline at end of file
+}
@@ -2,8 +2,9 @@
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 use crate::decimal::{Money, Rate};
-use crate::types::{FacilityStatus, FacilityId};
+use crate::types::{DelinquencyBucket, FacilityStatus, FacilityId};
 use crate::facility::Facility;
+use crate::payments::SuspenseDeposit;
 use rust_decimal::Decimal;
 
 /// serializable view of a facility's state
@@ -44,6 +45,13 @@ pub struct PaymentView {
     pub payment_count: u32,
     pub missed_payment_count: u32,
     pub days_past_due: u32,
+    pub arrears_amount: Money,
+    pub installments_past_due: u32,
+    pub delinquency_bucket: DelinquencyBucket,
+    pub non_accrual: bool,
+    pub suspended_interest: Money,
+    pub suspense_balance: Money,
+    pub suspense_deposits: Vec<SuspenseDeposit>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -85,6 +93,13 @@ impl FacilityView {
                 payment_count: facility.state.payment_count,
                 missed_payment_count: facility.state.missed_payment_count,
                 days_past_due: facility.state.days_past_due,
+                arrears_amount: facility.state.arrears_amount,
+                installments_past_due: facility.state.installments_past_due,
+                delinquency_bucket: facility.state.delinquency_bucket,
+                non_accrual: facility.state.non_accrual,
+                suspended_interest: facility.state.suspended_interest,
+                suspense_balance: facility.suspense.balance,
+                suspense_deposits: facility.suspense.deposits.clone(),
             },
             metadata: MetadataView {
                 facility_type: format!("{:?}", facility.config.facility_type),
@@ -115,6 +130,8 @@ pub struct RevolvingView {
     pub facility: FacilityView,
     pub credit_limit: Money,
     pub available_credit: Money,
+    pub pending_authorizations: Money,
+    pub installment_plan_balance: Money,
     pub utilization_rate: Rate,
     pub is_in_draw_period: bool,
 }
@@ -136,7 +153,20 @@ pub struct OverdraftView {
     pub facility: FacilityView,
     pub overdraft_limit: Money,
     pub buffer_zone: Money,
+    pub linked_account_id: String,
     pub linked_account_balance: Money,
     pub is_active: bool,
     pub available_funds: Money,
+    pub expiry_date: Option<DateTime<Utc>>,
+    pub scheduled_withdrawal: Option<DateTime<Utc>>,
+    pub repayment_plan: Option<RepaymentPlanView>,
+}
+
+/// repayment plan for an overdraft whose limit was withdrawn
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RepaymentPlanView {
+    pub principal: Money,
+    pub term_months: u32,
+    pub monthly_payment: Money,
+    pub installments_paid: u32,
 }
\ No newline at end of file
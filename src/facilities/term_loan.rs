@@ -1,15 +1,17 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use hourglass_rs::SafeTimeProvider;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::config::{FacilityConfig, FacilityType};
+use crate::config::{FacilityConfig, FacilityType, PrepaymentPenalty};
 use crate::types::TermLoanType;
 use crate::decimal::{Money, Rate};
 use crate::errors::{FacilityError, Result};
 use crate::events::Event;
 use crate::facility::Facility;
-use crate::payments::AmortizationSchedule;
-use crate::types::FacilityStatus;
+use crate::payments::{AmortizationSchedule, RecalculationStrategy};
+use crate::state::{FacilitySpecificState, Installment};
+use crate::types::{FacilityStatus, OverpaymentStrategy};
 
 /// term loan facility
 pub struct TermLoan {
@@ -17,6 +19,22 @@ pub struct TermLoan {
     time: Option<*const SafeTimeProvider>,
     amortization_schedule: Option<AmortizationSchedule>,
     current_payment_number: u32,
+    /// loan year the free prepayment allowance is being tracked for
+    prepayment_year: u32,
+    /// principal prepaid during the current loan year
+    prepaid_this_year: Money,
+}
+
+/// amount required to pay the loan off in full
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayoffQuote {
+    pub principal: Money,
+    pub interest: Money,
+    pub fees: Money,
+    pub penalties: Money,
+    pub prepayment_penalty: Money,
+    pub total: Money,
+    pub quoted_at: DateTime<Utc>,
 }
 
 impl TermLoan {
@@ -37,6 +55,8 @@ impl TermLoan {
             time: None,
             amortization_schedule: None,
             current_payment_number: 0,
+            prepayment_year: 0,
+            prepaid_this_year: Money::ZERO,
         })
     }
 
@@ -69,13 +89,7 @@ impl TermLoan {
         self.generate_schedule(time)?;
         
         // set first payment due
-        if let Some(schedule) = &self.amortization_schedule {
-            if let Some(first_payment) = schedule.get_payment(1) {
-                self.facility.state.next_payment_due = Some(first_payment.payment_date);
-                self.facility.state.next_payment_amount = Some(first_payment.payment_amount);
-                self.facility.state.minimum_payment_due = Some(first_payment.payment_amount);
-            }
-        }
+        self.refresh_next_payment(time.now());
         
         Ok(())
     }
@@ -111,7 +125,266 @@ impl TermLoan {
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "Invalid time provider reference".to_string(),
             })?;
-        self.facility.process_payment(amount, time)
+        self.process_payment_with_time(amount, time)
+    }
+
+    /// process payment with explicit time, assessing any prepayment penalty on
+    /// principal paid beyond the scheduled installment
+    pub fn process_payment_with_time(
+        &mut self,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<crate::payments::PaymentResult> {
+        self.apply_payment(amount, None, time_provider)
+    }
+
+    /// pay more than the installment using stored time, overriding the
+    /// configured overpayment strategy for this payment
+    pub fn make_overpayment(
+        &mut self,
+        amount: Money,
+        strategy: OverpaymentStrategy,
+    ) -> Result<crate::payments::PaymentResult> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.make_overpayment_with_time(amount, strategy, time)
+    }
+
+    /// pay more than the installment with explicit time, overriding the
+    /// configured overpayment strategy for this payment
+    pub fn make_overpayment_with_time(
+        &mut self,
+        amount: Money,
+        strategy: OverpaymentStrategy,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<crate::payments::PaymentResult> {
+        self.apply_payment(amount, Some(strategy), time_provider)
+    }
+
+    /// apply a payment, settling the current installment when it is covered and
+    /// re-amortizing the live schedule after an overpayment
+    fn apply_payment(
+        &mut self,
+        amount: Money,
+        overpayment_strategy: Option<OverpaymentStrategy>,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<crate::payments::PaymentResult> {
+        let scheduled = self.facility.state.minimum_payment_due.unwrap_or(Money::ZERO);
+
+        let mut result = self.facility.process_payment_with_strategy(amount, overpayment_strategy, time_provider)?;
+
+        // the penalty is owed on principal paid beyond the installments due,
+        // on top of the payment rather than out of it
+        let application = &result.application;
+        let installments_paid = application.to_interest + application.to_principal;
+        let prepaid = (installments_paid - installments_paid.min(scheduled)).min(application.to_principal);
+        if prepaid > Money::ZERO {
+            self.assess_prepayment_penalty(prepaid, time_provider.now());
+        }
+
+        if self.amortization_schedule.is_none() {
+            return Ok(result);
+        }
+        let paid_before = self.current_payment_number;
+        self.current_payment_number = self.facility.state.installments_paid();
+        let installment_paid = self.current_payment_number > paid_before;
+
+        if let Some(overpayment) = result.overpayment.as_mut() {
+            let recalculation = match overpayment.strategy {
+                OverpaymentStrategy::ReduceEmi => Some(RecalculationStrategy::ReduceEmi),
+                OverpaymentStrategy::ReduceTerm => Some(RecalculationStrategy::ReduceTerm),
+                OverpaymentStrategy::ReducePrincipal | OverpaymentStrategy::ReduceLimit => None,
+            };
+            if let (Some(recalculation), Some(schedule)) = (recalculation, self.amortization_schedule.as_mut()) {
+                schedule.recalculate_from_balance(
+                    self.facility.state.outstanding_principal,
+                    self.current_payment_number,
+                    recalculation,
+                    time_provider,
+                )?;
+                overpayment.new_emi = schedule.get_payment(self.current_payment_number + 1)
+                    .map(|next| next.payment_amount);
+                overpayment.new_term_months = Some(schedule.term_months - self.current_payment_number);
+                self.facility.state.replace_installments_after(
+                    self.current_payment_number,
+                    schedule_installments(schedule, self.current_payment_number),
+                );
+            }
+        }
+
+        if installment_paid || result.overpayment.is_some() {
+            self.refresh_next_payment(time_provider.now());
+            let term_months = self.amortization_schedule.as_ref().map_or(0, |s| s.term_months);
+            if self.current_payment_number >= term_months {
+                self.handle_maturity(time_provider)?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// point the next payment due and term loan state at the live schedule
+    /// and its installments
+    fn refresh_next_payment(&mut self, now: DateTime<Utc>) {
+        let Some(schedule) = &self.amortization_schedule else {
+            return;
+        };
+        self.current_payment_number = self.facility.state.installments_paid();
+        let next = schedule.get_payment(self.current_payment_number + 1);
+        let state = &mut self.facility.state;
+
+        state.refresh_installments(now);
+
+        if let FacilitySpecificState::TermLoan { scheduled_payment, remaining_term_months, .. } =
+            &mut state.facility_specific
+        {
+            *remaining_term_months = schedule.term_months.saturating_sub(self.current_payment_number);
+            if let Some(next) = next {
+                *scheduled_payment = next.payment_amount;
+            }
+        }
+    }
+
+    /// quote the payoff amount using stored time
+    pub fn payoff_quote(&self) -> Result<PayoffQuote> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        Ok(self.payoff_quote_with_time(time))
+    }
+
+    /// quote the payoff amount with explicit time
+    /// note: interest is included as accrued, accrue first for an up to date quote
+    pub fn payoff_quote_with_time(&self, time_provider: &SafeTimeProvider) -> PayoffQuote {
+        let now = time_provider.now();
+        let state = &self.facility.state;
+        let prepayment_penalty = self.prepayment_penalty_for(state.outstanding_principal, now).1;
+
+        PayoffQuote {
+            principal: state.outstanding_principal,
+            interest: state.accrued_interest,
+            fees: state.accrued_fees,
+            penalties: state.accrued_penalties,
+            prepayment_penalty,
+            total: state.total_outstanding() + prepayment_penalty,
+            quoted_at: now,
+        }
+    }
+
+    /// pay the loan off in full using stored time
+    pub fn pay_off(&mut self) -> Result<crate::payments::PaymentResult> {
+        let time_ptr = self.time
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Time provider not set. Call set_time() first".to_string(),
+            })?;
+        let time = unsafe { time_ptr.as_ref() }
+            .ok_or(FacilityError::InvalidConfiguration {
+                message: "Invalid time provider reference".to_string(),
+            })?;
+        self.pay_off_with_time(time)
+    }
+
+    /// pay the loan off in full with explicit time, including any prepayment penalty
+    pub fn pay_off_with_time(
+        &mut self,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<crate::payments::PaymentResult> {
+        self.facility.accrue_interest(time_provider)?;
+
+        let principal = self.facility.state.outstanding_principal;
+        self.assess_prepayment_penalty(principal, time_provider.now());
+
+        let payoff = self.facility.state.total_outstanding();
+        self.facility.process_payment(payoff, time_provider)
+    }
+
+    /// configured prepayment penalty, if any
+    pub fn prepayment_penalty(&self) -> Option<&PrepaymentPenalty> {
+        self.facility.config.fee_config.prepayment_penalty.as_ref()
+    }
+
+    /// whole months elapsed since origination
+    fn months_since_origination(&self, now: DateTime<Utc>) -> u32 {
+        let start = self.facility.config.financial_terms.origination_date;
+        let mut months = (now.year() - start.year()) * 12 + now.month() as i32 - start.month() as i32;
+        if now.day() < start.day() {
+            months -= 1;
+        }
+        months.max(0) as u32
+    }
+
+    /// chargeable prepayment and penalty after the free allowance for the loan year
+    fn prepayment_penalty_for(&self, prepaid: Money, now: DateTime<Utc>) -> (Money, Money) {
+        let policy = match self.prepayment_penalty() {
+            Some(policy) => policy,
+            None => return (Money::ZERO, Money::ZERO),
+        };
+
+        let months = self.months_since_origination(now);
+        let used = if months / 12 == self.prepayment_year {
+            self.prepaid_this_year
+        } else {
+            Money::ZERO
+        };
+        let allowance = policy.yearly_allowance(self.facility.state.original_commitment);
+        let free = (allowance - allowance.min(used)).min(prepaid);
+        let chargeable = prepaid - free;
+
+        let remaining: Vec<Money> = self.amortization_schedule.as_ref()
+            .map(|schedule| {
+                schedule.payments.iter()
+                    .filter(|p| p.payment_number > self.current_payment_number)
+                    .map(|p| p.payment_amount)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let penalty = policy.calculate(
+            chargeable,
+            self.facility.state.outstanding_principal,
+            months,
+            self.facility.config.financial_terms.interest_rate,
+            &remaining,
+        );
+
+        (chargeable, penalty)
+    }
+
+    /// charge the prepayment penalty as a fee and track the free allowance
+    fn assess_prepayment_penalty(&mut self, prepaid: Money, now: DateTime<Utc>) -> Money {
+        let (chargeable, penalty) = self.prepayment_penalty_for(prepaid, now);
+
+        let year = self.months_since_origination(now) / 12;
+        if year != self.prepayment_year {
+            self.prepayment_year = year;
+            self.prepaid_this_year = Money::ZERO;
+        }
+        self.prepaid_this_year += prepaid;
+
+        if penalty > Money::ZERO {
+            self.facility.state.charge_fee("prepayment_penalty", penalty);
+
+            self.facility.events.emit(Event::PrepaymentPenaltyAssessed {
+                facility_id: self.facility.id,
+                prepaid_amount: chargeable,
+                months_since_origination: self.months_since_origination(now),
+                penalty,
+                timestamp: now,
+            });
+        }
+
+        penalty
     }
 
     /// accrue interest using stored time
@@ -168,13 +441,7 @@ impl TermLoan {
         self.generate_schedule(time_provider)?;
 
         // set first payment due
-        if let Some(schedule) = &self.amortization_schedule {
-            if let Some(first_payment) = schedule.get_payment(1) {
-                self.facility.state.next_payment_due = Some(first_payment.payment_date);
-                self.facility.state.next_payment_amount = Some(first_payment.payment_amount);
-                self.facility.state.minimum_payment_due = Some(first_payment.payment_amount);
-            }
-        }
+        self.refresh_next_payment(time_provider.now());
 
         Ok(disbursed)
     }
@@ -191,6 +458,7 @@ impl TermLoan {
             time_provider,
         )?;
 
+        self.facility.state.replace_installments_after(0, schedule_installments(&schedule, 0));
         self.amortization_schedule = Some(schedule);
         Ok(())
     }
@@ -205,33 +473,26 @@ impl TermLoan {
                 message: "No amortization schedule".to_string(),
             })?;
 
-        self.current_payment_number += 1;
+        let payment_number = self.current_payment_number + 1;
 
-        let scheduled = schedule.get_payment(self.current_payment_number)
+        let scheduled = schedule.get_payment(payment_number)
             .ok_or(FacilityError::InvalidConfiguration {
-                message: format!("No payment {} in schedule", self.current_payment_number),
+                message: format!("No payment {} in schedule", payment_number),
             })?;
+        let payment_amount = scheduled.payment_amount;
+        let term_months = schedule.term_months;
 
         // accrue interest up to payment date
         self.facility.accrue_interest(time_provider)?;
 
         // process the payment
-        let _result = self.facility.process_payment(scheduled.payment_amount, time_provider)?;
+        let _result = self.facility.process_payment(payment_amount, time_provider)?;
 
         // update next payment due
-        if let Some(next) = schedule.get_payment(self.current_payment_number + 1) {
-            self.facility.state.next_payment_due = Some(next.payment_date);
-            self.facility.state.next_payment_amount = Some(next.payment_amount);
-            self.facility.state.minimum_payment_due = Some(next.payment_amount);
-        } else {
-            // last payment made
-            self.facility.state.next_payment_due = None;
-            self.facility.state.next_payment_amount = None;
-            self.facility.state.minimum_payment_due = None;
-        }
+        self.refresh_next_payment(time_provider.now());
 
         // check if this was the final payment
-        if self.current_payment_number >= schedule.term_months {
+        if self.current_payment_number >= term_months {
             self.handle_maturity(time_provider)?;
         }
 
@@ -363,6 +624,15 @@ impl TermLoan {
     }
 }
 
+/// installments for the scheduled payments after `after_number`
+pub(crate) fn schedule_installments(schedule: &AmortizationSchedule, after_number: u32) -> Vec<Installment> {
+    schedule.payments
+        .iter()
+        .filter(|p| p.payment_number > after_number)
+        .map(|p| Installment::new(p.payment_number, p.payment_date, p.payment_amount))
+        .collect()
+}
+
 /// builder for term loans
 pub struct TermLoanBuilder {
     loan_type: Option<TermLoanType>,
@@ -375,9 +645,16 @@ pub struct TermLoanBuilder {
     balloon_percentage: Option<rust_decimal::Decimal>,
     account_number: Option<String>,
     customer_id: Option<String>,
+    prepayment_penalty: Option<PrepaymentPenalty>,
     time_provider: Option<*const SafeTimeProvider>,
 }
 
+impl Default for TermLoanBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl TermLoanBuilder {
     pub fn new() -> Self {
         Self {
@@ -391,6 +668,7 @@ impl TermLoanBuilder {
             balloon_percentage: None,
             account_number: None,
             customer_id: None,
+            prepayment_penalty: None,
             time_provider: None,
         }
     }
@@ -450,6 +728,12 @@ impl TermLoanBuilder {
         self
     }
 
+    /// override the prepayment penalty from the loan type preset
+    pub fn prepayment_penalty(mut self, penalty: PrepaymentPenalty) -> Self {
+        self.prepayment_penalty = Some(penalty);
+        self
+    }
+
     /// Build with stored time or system time if not set
     pub fn build(self) -> Result<TermLoan> {
         if let Some(time_ptr) = self.time_provider {
@@ -499,7 +783,7 @@ impl TermLoanBuilder {
 
         let origination_date = self.origination_date.unwrap_or_else(|| time_provider.now());
 
-        let config = match loan_type {
+        let mut config = match loan_type {
             TermLoanType::Mortgage => {
                 let property_value = self.property_value.ok_or(FacilityError::InvalidConfiguration {
                     message: "Property value required for mortgage".to_string(),
@@ -531,6 +815,10 @@ impl TermLoanBuilder {
             }
         };
 
+        if let Some(penalty) = self.prepayment_penalty {
+            config.fee_config.prepayment_penalty = Some(penalty);
+        }
+
         let account_number = self.account_number.unwrap_or_else(|| {
             format!("ACC-{}", Uuid::new_v4().to_string()[..8].to_uppercase())
         });
@@ -557,6 +845,7 @@ mod tests {
     use super::*;
     use hourglass_rs::TimeSource;
     use chrono::TimeZone;
+    use crate::payments::PartialPaymentStrategy;
 
     #[test]
     fn test_mortgage_creation() {
@@ -653,6 +942,80 @@ mod tests {
         assert_eq!(loan.facility.state.payment_count, 1);
     }
 
+    #[test]
+    fn test_partial_payments_held_in_suspense() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::AutoLoan)
+            .amount(Money::from_major(20_000))
+            .rate(Rate::from_percentage(6))
+            .term_months(48)
+            .vehicle_value(Money::from_major(25_000))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+
+        let installment = loan.facility.state.minimum_payment_due.unwrap();
+        let part = Money::from_major(200);
+
+        // two partial payments are parked without touching the balance
+        let result = loan.process_payment(part).unwrap();
+        assert_eq!(result.amount_applied, Money::ZERO);
+        assert_ne!(result.payment_id, loan.facility.id);
+        assert_eq!(loan.facility.suspense.deposits[0].reference, format!("payment-{}", result.payment_id));
+        loan.process_payment(part).unwrap();
+        assert_eq!(loan.facility.state.suspense_balance, Money::from_major(400));
+        assert_eq!(loan.facility.suspense.deposits.len(), 2);
+        assert_eq!(loan.facility.state.outstanding_principal, Money::from_major(20_000));
+        assert_eq!(loan.facility.state.payment_count, 0);
+
+        // the payment that completes the installment releases the held funds
+        let result = loan.process_payment(installment - Money::from_major(400)).unwrap();
+        assert_eq!(result.amount_applied, installment);
+        assert_eq!(loan.facility.state.suspense_balance, Money::ZERO);
+        assert!(loan.facility.suspense.deposits.is_empty());
+        assert_eq!(loan.facility.state.payment_count, 1);
+
+        let events = loan.facility.events.events();
+        let held = events.iter().filter(|e| matches!(e, Event::PaymentHeldInSuspense { .. })).count();
+        assert_eq!(held, 2);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::SuspenseReleased { amount, .. } if *amount == Money::from_major(400)
+        )));
+        assert!(loan.to_json_pretty().contains("suspense_balance"));
+    }
+
+    #[test]
+    fn test_partial_payment_reject_and_apply_immediately() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(10_000))
+            .rate(Rate::from_percentage(12))
+            .term_months(12)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+
+        // personal loans apply partial payments straight away
+        let result = loan.process_payment(Money::from_major(100)).unwrap();
+        assert_eq!(result.amount_applied, Money::from_major(100));
+        assert_eq!(loan.facility.state.suspense_balance, Money::ZERO);
+
+        loan.facility.config.payment_config.partial_payment_strategy = PartialPaymentStrategy::Reject;
+        let result = loan.process_payment(Money::from_major(100));
+        assert!(matches!(result, Err(FacilityError::PaymentBelowMinimum { .. })));
+    }
+
     #[test]
     fn test_missed_payment_handling() {
         let start_date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
@@ -760,4 +1123,588 @@ mod tests {
         // verify penalties applied after grace period
         assert!(loan.facility.state.accrued_penalties > Money::ZERO);
     }
+
+    #[test]
+    fn test_prepayment_penalty_steps_down_and_quotes_payoff() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::Mortgage)
+            .amount(Money::from_major(300_000))
+            .rate(Rate::from_percentage(4))
+            .term_months(360)
+            .property_value(Money::from_major(400_000))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+
+        // 2% in the first year on principal paid beyond the installment
+        control.advance(chrono::Duration::days(91));
+        let state = &loan.facility.state;
+        let due = state.minimum_payment_due.unwrap() + state.accrued_fees + state.accrued_penalties;
+        let fees_before = state.total_fees_charged;
+
+        // a payment that is turned away books no penalty and keeps the allowance
+        loan.facility.config.limits.maximum_payment = Some(due);
+        assert!(loan.process_payment(due + Money::from_major(10_000)).is_err());
+        assert_eq!(loan.facility.state.total_fees_charged, fees_before);
+        assert_eq!(loan.prepaid_this_year, Money::ZERO);
+        loan.facility.config.limits.maximum_payment = None;
+
+        let result = loan.process_payment(due + Money::from_major(10_000)).unwrap();
+        assert_eq!(result.overpayment.unwrap().amount_applied, Money::from_major(10_000));
+        assert_eq!(loan.facility.state.total_fees_charged - fees_before, Money::from_major(200));
+        assert!(loan.facility.events.events().iter().any(|e| matches!(e,
+            Event::PrepaymentPenaltyAssessed { penalty, months_since_origination: 3, .. }
+                if *penalty == Money::from_major(200)
+        )));
+
+        // steps down to 1% in the second year
+        control.set(Utc.with_ymd_and_hms(2025, 2, 1, 0, 0, 0).unwrap());
+        let quote = loan.payoff_quote().unwrap();
+        assert_eq!(quote.prepayment_penalty, quote.principal.percentage(rust_decimal_macros::dec!(1)).round_dp(2));
+        assert_eq!(
+            quote.total,
+            quote.principal + quote.interest + quote.fees + quote.penalties + quote.prepayment_penalty
+        );
+
+        // no penalty once the window has passed
+        control.set(Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(loan.payoff_quote().unwrap().prepayment_penalty, Money::ZERO);
+        loan.pay_off().unwrap();
+        assert!(loan.facility.state.total_outstanding().is_zero());
+        assert_eq!(loan.facility.state.status, FacilityStatus::Settled);
+    }
+
+    #[test]
+    fn test_prepayment_penalty_alternatives() {
+        use crate::config::{PrepaymentPenaltyMethod, StepDown};
+        use rust_decimal_macros::dec;
+
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+
+        // free 20% per year before the percentage applies
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(50_000))
+            .rate(Rate::from_percentage(6))
+            .term_months(60)
+            .prepayment_penalty(PrepaymentPenalty {
+                penalty_percentage: dec!(2),
+                penalty_months: 36,
+                step_down: vec![StepDown { after_months: 24, penalty_percentage: dec!(1) }],
+                method: PrepaymentPenaltyMethod::Percentage,
+                free_allowance_percentage: Some(dec!(20)),
+            })
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        let fees_before = loan.facility.state.total_fees_charged;
+        let due = loan.facility.state.accrued_fees + loan.facility.state.minimum_payment_due.unwrap();
+        loan.process_payment(due + Money::from_major(7_500)).unwrap();
+        assert_eq!(loan.facility.state.total_fees_charged, fees_before);
+        let due = loan.facility.state.minimum_payment_due.unwrap();
+        loan.process_payment(due + Money::from_major(5_000)).unwrap();
+        // 2,500 of the allowance remained, the other 2,500 is charged at 2%
+        assert_eq!(loan.facility.state.total_fees_charged - fees_before, Money::from_major(50));
+
+        // yield maintenance and make-whole against twelve remaining payments of 1,000
+        let remaining = vec![Money::from_major(1_000); 12];
+        let mut penalty = PrepaymentPenalty {
+            penalty_percentage: dec!(2),
+            penalty_months: 36,
+            step_down: vec![],
+            method: PrepaymentPenaltyMethod::YieldMaintenance { treasury_rate: Rate::ZERO },
+            free_allowance_percentage: None,
+        };
+        let prepaid = Money::from_major(10_000);
+        assert_eq!(
+            penalty.calculate(prepaid, prepaid, 6, Rate::from_percentage(6), &remaining),
+            Money::from_major(600)
+        );
+        penalty.method = PrepaymentPenaltyMethod::YieldMaintenance { treasury_rate: Rate::from_percentage(7) };
+        assert_eq!(penalty.calculate(prepaid, prepaid, 6, Rate::from_percentage(6), &remaining), Money::ZERO);
+
+        penalty.method = PrepaymentPenaltyMethod::MakeWhole { discount_rate: Rate::ZERO };
+        assert_eq!(
+            penalty.calculate(prepaid, prepaid, 6, Rate::from_percentage(6), &remaining),
+            Money::from_major(2_000)
+        );
+        assert_eq!(penalty.calculate(prepaid, prepaid, 36, Rate::from_percentage(6), &remaining), Money::ZERO);
+    }
+
+    #[test]
+    fn test_overpayments_reshape_live_schedule() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(12_000))
+            .rate(Rate::from_percentage(12))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        // keep the origination fee out of the waterfall
+        loan.facility.state.accrued_fees = Money::ZERO;
+
+        // personal loans shorten the term by default
+        control.advance(chrono::Duration::days(31));
+        let installment = loan.facility.state.minimum_payment_due.unwrap();
+        let result = loan.process_payment(installment + Money::from_major(3_000)).unwrap();
+        let overpayment = result.overpayment.unwrap();
+        assert_eq!(overpayment.strategy, OverpaymentStrategy::ReduceTerm);
+        assert_eq!(loan.current_payment_number, 1);
+
+        let schedule = loan.schedule().unwrap();
+        assert!(schedule.term_months < 24);
+        assert_eq!(schedule.payments.len() as u32, schedule.term_months);
+        assert_eq!(schedule.payments[1].payment_number, 2);
+        assert_eq!(schedule.payments[1].payment_date, Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap());
+        let remaining = schedule.term_months - 1;
+        assert_eq!(overpayment.new_term_months, Some(remaining));
+        assert!(matches!(
+            loan.facility.state.facility_specific,
+            FacilitySpecificState::TermLoan { remaining_term_months, .. } if remaining_term_months == remaining
+        ));
+
+        // the customer can ask for a lower installment instead
+        control.advance(chrono::Duration::days(29));
+        let installment = loan.facility.state.next_payment_amount.unwrap();
+        let result = loan.make_overpayment(installment + Money::from_major(2_000), OverpaymentStrategy::ReduceEmi).unwrap();
+        assert_eq!(result.overpayment.unwrap().strategy, OverpaymentStrategy::ReduceEmi);
+        assert!(loan.facility.state.next_payment_amount.unwrap() < installment);
+        assert_eq!(loan.schedule().unwrap().term_months - loan.current_payment_number, remaining - 1);
+        assert!(loan.facility.events.events().iter().any(|e| matches!(
+            e,
+            Event::OverpaymentReceived { strategy: OverpaymentStrategy::ReduceEmi, .. }
+        )));
+    }
+
+    #[test]
+    fn test_mortgage_waterfall_and_principal_payments() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::Mortgage)
+            .amount(Money::from_major(200_000))
+            .rate(Rate::from_percentage(5))
+            .term_months(240)
+            .property_value(Money::from_major(300_000))
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        assert_eq!(
+            loan.facility.state.fee_balances.get("origination_fee"),
+            Some(&Money::from_major(2_000))
+        );
+
+        // escrow is collected with the installment ahead of fees
+        loan.facility.bill_escrow(Money::from_major(300));
+        let installment = loan.facility.state.minimum_payment_due.unwrap();
+        let result = loan.process_payment(installment + Money::from_major(300)).unwrap();
+        assert_eq!(result.application.to_escrow, Money::from_major(300));
+        assert!(result.overpayment.is_none());
+        assert_eq!(loan.facility.state.escrow_due, Money::ZERO);
+
+        // principal-only payments skip the outstanding fees
+        let fees = loan.facility.state.accrued_fees;
+        let principal = loan.facility.state.outstanding_principal;
+        let result = loan.facility.process_principal_payment(Money::from_major(5_000), &time).unwrap();
+        assert_eq!(result.application.to_principal, Money::from_major(5_000));
+        assert_eq!(loan.facility.state.accrued_fees, fees);
+        assert_eq!(loan.facility.state.outstanding_principal, principal - Money::from_major(5_000));
+        assert_eq!(result.overpayment.unwrap().amount_applied, Money::from_major(5_000));
+    }
+
+    #[test]
+    fn test_delinquency_tracks_oldest_unpaid_installment() {
+        use crate::state::InstallmentStatus;
+
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        assert_eq!(loan.facility.state.installments.len(), 24);
+
+        // two installments missed
+        control.set(Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap());
+        loan.update_daily_status().unwrap();
+        let installment = loan.facility.state.installments[0].amount;
+        assert_eq!(loan.facility.state.installments_past_due, 2);
+        assert_eq!(loan.facility.state.arrears_amount, installment + installment);
+        assert_eq!(loan.facility.state.days_past_due, 31);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Delinquent);
+
+        // one installment's worth clears the oldest, DPD moves to the next
+        loan.process_payment(installment).unwrap();
+        assert_eq!(loan.facility.state.installments[0].status, InstallmentStatus::Paid);
+        assert_eq!(loan.facility.state.installments_past_due, 1);
+        assert_eq!(loan.facility.state.days_past_due, 2);
+        assert_eq!(loan.current_payment_number, 1);
+
+        // a partial payment leaves the installment past due
+        let half = Money::from_decimal(installment.as_decimal() / rust_decimal_macros::dec!(2));
+        loan.process_payment(half).unwrap();
+        assert_eq!(loan.facility.state.installments[1].status, InstallmentStatus::PartiallyPaid);
+        assert_eq!(loan.facility.state.arrears_amount, installment - half);
+        assert_eq!(loan.facility.state.days_past_due, 2);
+
+        loan.process_payment(installment - half).unwrap();
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.arrears_amount, Money::ZERO);
+        assert_eq!(loan.facility.state.days_past_due, 0);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Active);
+        assert!(loan.to_json_pretty().contains("installments_past_due"));
+    }
+
+    #[test]
+    fn test_delinquency_buckets_escalate_to_charge_off() {
+        use crate::types::DelinquencyBucket;
+
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        let first_due = loan.facility.state.installments[0].due_date;
+
+        control.set(first_due + chrono::Duration::days(35));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days30To59);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Delinquent);
+        assert!(!loan.facility.state.non_accrual);
+
+        // 90 days past due: default and non-accrual
+        control.set(first_due + chrono::Duration::days(95));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days90To119);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Default);
+        assert!(loan.facility.state.non_accrual);
+
+        // interest stays owed but is held out of income
+        let interest = loan.facility.state.accrued_interest;
+        let suspended = loan.facility.state.suspended_interest;
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        let accrued = loan.facility.state.accrued_interest - interest;
+        assert!(accrued > Money::ZERO);
+        assert_eq!(loan.facility.state.suspended_interest - suspended, accrued);
+
+        // catching up one installment moves the bucket but not the status
+        let installment = loan.facility.state.installments[0].amount;
+        loan.process_payment(installment).unwrap();
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days60To89);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Default);
+        assert!(loan.facility.state.non_accrual);
+
+        let second_due = loan.facility.state.installments[1].due_date;
+        control.set(second_due + chrono::Duration::days(120));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days120Plus);
+        assert_eq!(loan.facility.state.status, FacilityStatus::ChargedOff);
+        assert_eq!(loan.facility.state.write_off_amount, Some(loan.facility.state.total_outstanding()));
+
+        let events = loan.facility.events.events();
+        let buckets: Vec<_> = events.iter().filter_map(|e| match e {
+            Event::DelinquencyBucketChanged { new_bucket, .. } => Some(*new_bucket),
+            _ => None,
+        }).collect();
+        assert_eq!(buckets, vec![
+            DelinquencyBucket::Days30To59,
+            DelinquencyBucket::Days90To119,
+            DelinquencyBucket::Days60To89,
+            DelinquencyBucket::Days120Plus,
+        ]);
+        assert!(events.iter().any(|e| matches!(e, Event::FacilityChargedOff { .. })));
+    }
+
+    #[test]
+    fn test_late_fee_per_installment_without_pyramiding() {
+        use crate::config::LateFeeMethod;
+
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+
+        let fees = &mut loan.facility.config.fee_config;
+        fees.late_fee = Some(Money::from_major(35));
+        fees.late_fee_percentage = Some(rust_decimal_macros::dec!(5));
+        fees.late_fee_method = LateFeeMethod::LesserOf;
+        assert_eq!(fees.late_fee_for(Money::from_major(200)), Some(Money::from_major(10)));
+        fees.late_fee_method = LateFeeMethod::GreaterOf;
+        assert_eq!(fees.late_fee_for(Money::from_major(200)), Some(Money::from_major(35)));
+        fees.late_fee_cap = Some(Money::from_major(25));
+        assert_eq!(fees.late_fee_for(Money::from_major(200)), Some(Money::from_major(25)));
+
+        loan.originate_and_disburse().unwrap();
+        let installments = loan.facility.state.installments.clone();
+        let late_fees = |loan: &TermLoan| loan.facility.events.events().iter()
+            .filter(|e| matches!(e, Event::LateFeeApplied { .. }))
+            .count();
+
+        // two missed installments, one late fee each
+        control.set(installments[1].due_date + chrono::Duration::days(15));
+        loan.update_daily_status().unwrap();
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        assert_eq!(late_fees(&loan), 2);
+        assert_eq!(
+            loan.facility.state.fee_balances.get("late_fee"),
+            Some(&Money::from_major(50))
+        );
+
+        // the catch-up payment goes to both installments ahead of the late
+        // fees, which stay owed
+        let principal = loan.facility.state.outstanding_principal;
+        let result = loan.process_payment(installments[0].amount + installments[1].amount).unwrap();
+        assert_eq!(loan.facility.state.installments_past_due, 0);
+        assert_eq!(result.application.to_fees, Money::ZERO);
+        assert_eq!(
+            loan.facility.state.fee_balances.get("late_fee"),
+            Some(&Money::from_major(50))
+        );
+        assert_eq!(principal - loan.facility.state.outstanding_principal, result.application.to_principal);
+        assert_eq!(
+            result.application.to_interest + result.application.to_principal,
+            installments[0].amount + installments[1].amount
+        );
+
+        control.set(installments[2].due_date - chrono::Duration::days(1));
+        loan.process_payment(installments[2].amount).unwrap();
+        control.set(installments[2].due_date + chrono::Duration::days(20));
+        loan.update_daily_status().unwrap();
+        assert_eq!(late_fees(&loan), 2);
+        assert_eq!(loan.facility.state.status, FacilityStatus::Active);
+    }
+
+    #[test]
+    fn test_delinquency_bucket_boundaries_follow_policy() {
+        use crate::types::DelinquencyBucket;
+
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.facility.config.delinquency_policy.bucket_starts = [1, 15, 30, 45, 60];
+        loan.originate_and_disburse().unwrap();
+        let first_due = loan.facility.state.installments[0].due_date;
+
+        control.set(first_due + chrono::Duration::days(10));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days1To29);
+
+        control.set(first_due + chrono::Duration::days(20));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days30To59);
+
+        control.set(first_due + chrono::Duration::days(61));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.delinquency_bucket, DelinquencyBucket::Days120Plus);
+    }
+
+    #[test]
+    fn test_non_accrual_interest_is_recognised_once_cured() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        let first_due = loan.facility.state.installments[0].due_date;
+
+        control.set(first_due + chrono::Duration::days(95));
+        loan.update_daily_status().unwrap();
+        assert!(loan.facility.state.non_accrual);
+        control.advance(chrono::Duration::days(10));
+        loan.update_daily_status().unwrap();
+        let suspended = loan.facility.state.suspended_interest;
+        assert!(suspended > Money::ZERO);
+
+        // bringing the loan current recognises what was held in suspense
+        let state = &loan.facility.state;
+        let due = state.arrears_amount + state.accrued_interest + state.accrued_fees + state.accrued_penalties;
+        loan.process_payment(due).unwrap();
+        loan.update_daily_status().unwrap();
+        assert!(!loan.facility.state.non_accrual);
+        assert_eq!(loan.facility.state.suspended_interest, Money::ZERO);
+
+        let events = loan.facility.events.events();
+        let held: Money = events.iter().filter_map(|e| match e {
+            Event::InterestSuspended { amount, .. } => Some(*amount),
+            _ => None,
+        }).fold(Money::ZERO, |total, amount| total + amount);
+        assert_eq!(held, suspended);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::NonAccrualStatusChanged { non_accrual: false, recognised_interest, .. } if *recognised_interest == suspended
+        )));
+
+        // accrual carries on from where it was, with nothing waived
+        let interest = loan.facility.state.accrued_interest;
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        assert!(loan.facility.state.accrued_interest > interest);
+        assert!(matches!(loan.facility.events.events().last(), Some(Event::InterestAccrued { .. })));
+    }
+
+    #[test]
+    fn test_penalty_interest_accrues_daily_then_default_rate() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        let first_due = loan.facility.state.installments[0].due_date;
+        let daily = |amount: Money| Money::from_decimal(
+            amount.as_decimal() * rust_decimal_macros::dec!(0.30) / rust_decimal_macros::dec!(365)
+        );
+
+        // past grace the first charge covers every day overdue so far
+        control.set(first_due + chrono::Duration::days(11));
+        loan.update_daily_status().unwrap();
+        let arrears = loan.facility.state.arrears_amount;
+        let penalties = loan.facility.state.accrued_penalties;
+        assert_eq!(penalties.round_dp(6), (daily(arrears) * rust_decimal_macros::dec!(11)).round_dp(6));
+
+        // each later day charges only that day
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.accrued_penalties - penalties, daily(arrears));
+
+        // in default the whole balance accrues at the default rate, held out
+        // of income as the preset enters non-accrual on the same day
+        control.set(first_due + chrono::Duration::days(95));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.status, FacilityStatus::Default);
+        assert!(loan.facility.state.non_accrual);
+        let penalties = loan.facility.state.accrued_penalties;
+        let interest = loan.facility.state.accrued_interest;
+        let suspended = loan.facility.state.suspended_interest;
+
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        let principal = loan.facility.state.outstanding_principal;
+        assert_eq!(loan.facility.state.accrued_penalties, penalties);
+        assert_eq!(
+            (loan.facility.state.accrued_interest - interest).round_dp(2),
+            daily(principal).round_dp(2)
+        );
+        assert_eq!(
+            loan.facility.state.suspended_interest - suspended,
+            loan.facility.state.accrued_interest - interest
+        );
+    }
+
+    #[test]
+    fn test_penalty_interest_restarts_with_each_delinquency() {
+        let time = SafeTimeProvider::new(TimeSource::Test(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()
+        ));
+        let control = time.test_control().unwrap();
+
+        let mut loan = TermLoan::builder()
+            .loan_type(TermLoanType::PersonalLoan)
+            .amount(Money::from_major(5_000))
+            .rate(Rate::from_percentage(15))
+            .term_months(24)
+            .set_time(&time)
+            .build()
+            .unwrap();
+        loan.originate_and_disburse().unwrap();
+        let installments = loan.facility.state.installments.clone();
+        let daily = |amount: Money| Money::from_decimal(
+            amount.as_decimal() * rust_decimal_macros::dec!(0.30) / rust_decimal_macros::dec!(365)
+        );
+
+        // first delinquency, cured straight away
+        control.set(installments[0].due_date + chrono::Duration::days(12));
+        loan.update_daily_status().unwrap();
+        loan.process_payment(loan.facility.state.arrears_amount).unwrap();
+
+        // second delinquency charges its own days, then one day at a time
+        control.set(installments[1].due_date + chrono::Duration::days(11));
+        let penalties = loan.facility.state.accrued_penalties;
+        loan.update_daily_status().unwrap();
+        let arrears = loan.facility.state.arrears_amount;
+        assert_eq!(
+            (loan.facility.state.accrued_penalties - penalties).round_dp(6),
+            (daily(arrears) * rust_decimal_macros::dec!(11)).round_dp(6)
+        );
+
+        let penalties = loan.facility.state.accrued_penalties;
+        control.advance(chrono::Duration::days(1));
+        loan.update_daily_status().unwrap();
+        assert_eq!(loan.facility.state.accrued_penalties - penalties, daily(arrears));
+    }
 }
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, Utc};
 use hourglass_rs::SafeTimeProvider;
 use uuid::Uuid;
 
@@ -5,12 +6,17 @@ use crate::config::FacilityConfig;
 use crate::decimal::{Money, Rate};
 use crate::errors::{FacilityError, Result};
 use crate::events::{Event, EventStore};
-use crate::interest::{AccrualEngine, DailyAccrual, PenaltyEngine};
+use crate::interest::{AccrualEngine, DailyAccrual, InterestMethod, PenaltyEngine};
+use crate::payments::overpayment::LoanParameters;
 use crate::payments::{
-    PaymentContext, PaymentProcessor, PaymentRequest, PaymentResult, PaymentWaterfall,
+    OverpaymentHandler, OverpaymentResult, PartialPaymentStrategy, PaymentContext,
+    PaymentProcessor, PaymentRequest, PaymentResult, SuspenseAccount,
+};
+use crate::state::{FacilitySpecificState, FacilityState, InstallmentStatus, StateSnapshot};
+use crate::types::{
+    CollateralPosition, FacilityId, FacilityStatus, OverpaymentStrategy,
+    PaymentApplication,
 };
-use crate::state::{FacilityState, StateSnapshot};
-use crate::types::{CollateralPosition, FacilityId, FacilityStatus};
 
 /// core facility struct
 pub struct Facility {
@@ -19,6 +25,8 @@ pub struct Facility {
     pub state: FacilityState,
     pub events: EventStore,
     pub snapshots: Vec<StateSnapshot>,
+    /// partial payments held until they cover the minimum payment due
+    pub suspense: SuspenseAccount,
 }
 
 impl Facility {
@@ -27,6 +35,7 @@ impl Facility {
         Self {
             id: state.facility_id,
             config,
+            suspense: SuspenseAccount::new(state.facility_id),
             state,
             events: EventStore::new(),
             snapshots: Vec::new(),
@@ -93,8 +102,7 @@ impl Facility {
 
         // apply origination fee if configured
         if let Some(fee) = facility.config.fee_config.origination_fee {
-            facility.state.accrued_fees += fee;
-            facility.state.total_fees_charged += fee;
+            facility.state.charge_fee("origination_fee", fee);
         }
 
         // capture initial snapshot
@@ -123,11 +131,14 @@ impl Facility {
             });
         }
 
+        let now = time_provider.now();
+        self.config.limits.check_drawdown(amount, &self.state.transaction_usage, now)?;
+
         // record disbursement
         self.state.record_disbursement(amount);
+        self.state.transaction_usage.record(amount, now);
 
         // emit event
-        let now = time_provider.now();
         if self.state.total_disbursed == amount {
             // first disbursement
             self.events.emit(Event::FacilityActivated {
@@ -159,10 +170,25 @@ impl Facility {
 
     /// accrue interest
     pub fn accrue_interest(&mut self, time_provider: &SafeTimeProvider) -> Result<Vec<DailyAccrual>> {
+        self.accrue_interest_on(self.state.outstanding_principal, time_provider)
+    }
+
+    /// accrue interest on the given principal, for facilities that price part
+    /// of their balance separately
+    pub(crate) fn accrue_interest_on(
+        &mut self,
+        principal: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<Vec<DailyAccrual>> {
         let now = time_provider.now();
 
         // check if we should accrue
-        if self.state.outstanding_principal.is_zero() {
+        if principal.is_zero() {
+            return Ok(Vec::new());
+        }
+
+        // average daily balance interest is charged at statement cut
+        if let InterestMethod::AverageDailyBalance { .. } = self.config.interest_config.interest_method {
             return Ok(Vec::new());
         }
 
@@ -170,23 +196,22 @@ impl Facility {
         let engine = AccrualEngine::new(self.config.interest_config.day_count_convention);
 
         // accrue daily interest
+        // once in default the whole balance accrues at the default rate
+        let rate = match self.config.interest_config.default_rate {
+            Some(default_rate) if self.in_default_rate() => default_rate,
+            _ => self.config.financial_terms.interest_rate,
+        };
+
         let accruals = engine.accrue_daily(
-            self.state.outstanding_principal,
-            self.config.financial_terms.interest_rate,
+            principal,
+            rate,
             self.state.last_interest_accrual,
             time_provider,
         );
 
         // update state
         for accrual in &accruals {
-            self.state.accrued_interest += accrual.interest_amount;
-
-            // emit event
-            self.events.emit(Event::InterestAccrued {
-                facility_id: self.id,
-                amount: accrual.interest_amount,
-                timestamp: accrual.date,
-            });
+            self.book_interest(accrual.interest_amount, accrual.date);
         }
 
         self.state.last_interest_accrual = now;
@@ -194,6 +219,28 @@ impl Facility {
         Ok(accruals)
     }
 
+    /// add accrued interest to the balance; on non-accrual it is still owed
+    /// but held out of income until the facility is current again
+    pub(crate) fn book_interest(&mut self, amount: Money, timestamp: DateTime<Utc>) {
+        self.state.accrued_interest += amount;
+
+        if self.state.non_accrual {
+            self.state.suspended_interest += amount;
+
+            self.events.emit(Event::InterestSuspended {
+                facility_id: self.id,
+                amount,
+                timestamp,
+            });
+        } else {
+            self.events.emit(Event::InterestAccrued {
+                facility_id: self.id,
+                amount,
+                timestamp,
+            });
+        }
+    }
+
     /// process payment with system time
     pub fn process_payment_now(&mut self, amount: Money) -> Result<PaymentResult> {
         let time = SafeTimeProvider::new(hourglass_rs::TimeSource::System);
@@ -201,10 +248,25 @@ impl Facility {
     }
 
     /// process payment
+    ///
+    /// payments below the minimum payment due follow the partial payment
+    /// strategy: applied anyway, rejected, or held in suspense until the
+    /// held total covers the minimum
     pub fn process_payment(
         &mut self,
         amount: Money,
         time_provider: &SafeTimeProvider,
+    ) -> Result<PaymentResult> {
+        self.process_payment_with_strategy(amount, None, time_provider)
+    }
+
+    /// process payment, routing principal paid beyond the amount due through
+    /// `overpayment_strategy` or the configured one
+    pub fn process_payment_with_strategy(
+        &mut self,
+        amount: Money,
+        overpayment_strategy: Option<OverpaymentStrategy>,
+        time_provider: &SafeTimeProvider,
     ) -> Result<PaymentResult> {
         // validate
         if !self.state.can_accept_payment() {
@@ -212,6 +274,89 @@ impl Facility {
                 status: self.state.status,
             });
         }
+        self.config.limits.check_payment(amount, self.state.total_outstanding())?;
+        
+        let now = time_provider.now();
+        let payment_id = Uuid::new_v4();
+        let reference = format!("payment-{}", payment_id);
+        let strategy = self.config.payment_config.partial_payment_strategy;
+        let total_outstanding = self.state.total_outstanding();
+        let shortfall_against = |minimum: Money, provided: Money| provided < minimum && provided < total_outstanding;
+        
+        let mut amount_to_apply = amount;
+        match (strategy, self.state.minimum_payment_due) {
+            (PartialPaymentStrategy::Reject, Some(minimum)) if shortfall_against(minimum, amount) => {
+                return Err(FacilityError::PaymentBelowMinimum {
+                    minimum,
+                    provided: amount,
+                });
+            }
+            (PartialPaymentStrategy::HoldInSuspense, Some(minimum))
+                if shortfall_against(minimum, amount + self.suspense.balance) =>
+            {
+                return Ok(self.hold_in_suspense(payment_id, amount, minimum, reference, now));
+            }
+            (PartialPaymentStrategy::HoldInSuspense, _) if !self.suspense.balance.is_zero() => {
+                // held funds go out together with the payment that completes them
+                let released = self.suspense.release_funds();
+                self.state.suspense_balance = self.suspense.balance;
+                amount_to_apply += released;
+                
+                self.events.emit(Event::SuspenseReleased {
+                    facility_id: self.id,
+                    amount: released,
+                    timestamp: now,
+                });
+            }
+            _ => {}
+        }
+        let minimum_payment = match strategy {
+            PartialPaymentStrategy::ApplyImmediately => None,
+            _ => self.state.minimum_payment_due,
+        };
+
+        self.apply_payment(amount_to_apply, minimum_payment, false, overpayment_strategy, reference, time_provider)
+    }
+
+    /// process a payment applied to principal only, outside the waterfall;
+    /// the whole amount is handled by the overpayment strategy
+    pub fn process_principal_payment(
+        &mut self,
+        amount: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<PaymentResult> {
+        if !self.state.can_accept_payment() {
+            return Err(FacilityError::FacilityNotActive {
+                status: self.state.status,
+            });
+        }
+        self.config.limits.check_payment(amount, self.state.total_outstanding())?;
+
+        let reference = format!("principal-payment-{}", Uuid::new_v4());
+        self.apply_payment(amount, None, true, None, reference, time_provider)
+    }
+
+    /// bill an escrow installment to be collected with the next payment
+    pub fn bill_escrow(&mut self, amount: Money) {
+        self.state.escrow_due += amount;
+    }
+
+    /// bill an insurance premium to be collected with the next payment
+    pub fn bill_insurance(&mut self, amount: Money) {
+        self.state.insurance_due += amount;
+    }
+
+    /// run a payment through the product waterfall and update state
+    fn apply_payment(
+        &mut self,
+        amount: Money,
+        minimum_payment: Option<Money>,
+        principal_only: bool,
+        overpayment_strategy: Option<OverpaymentStrategy>,
+        reference: String,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<PaymentResult> {
+        let now = time_provider.now();
 
         // create payment context
         let mut context = PaymentContext {
@@ -220,43 +365,81 @@ impl Facility {
             accrued_penalties: self.state.accrued_penalties,
             accrued_interest: self.state.accrued_interest,
             outstanding_principal: self.state.outstanding_principal,
-            minimum_payment: self.state.minimum_payment_due,
+            minimum_payment,
             payment_due_date: self.state.next_payment_due,
             days_overdue: self.state.days_past_due,
+            fee_balances: self.state.fee_balances.clone(),
+            past_due_amount: self.state.arrears_amount,
+            current_installment_due: self.current_installment_due(),
+            escrow_due: self.state.escrow_due,
+            insurance_due: self.state.insurance_due,
         };
 
         // create payment request
         let request = PaymentRequest {
             facility_id: self.id,
             amount,
-            payment_date: time_provider.now(),
-            reference: format!("payment-{}", Uuid::new_v4()),
-            is_principal_only: false,
+            payment_date: now,
+            reference,
+            is_principal_only: principal_only,
         };
 
-        // process through waterfall
-        let processor = PaymentProcessor::new(PaymentWaterfall::standard());
-        let result = processor.process(request, &mut context, time_provider, &mut self.events)?;
+        // process through the product waterfall
+        let processor = PaymentProcessor::new(self.config.payment_config.waterfall.clone());
+        let mut result = processor.process(request, &mut context, time_provider, &mut self.events)?;
 
         // update state from context
         self.state.accrued_fees = context.accrued_fees;
         self.state.accrued_penalties = context.accrued_penalties;
         self.state.accrued_interest = context.accrued_interest;
         self.state.outstanding_principal = context.outstanding_principal;
+        self.state.fee_balances = context.fee_balances;
+        self.state.arrears_amount = context.past_due_amount;
+        self.state.escrow_due = context.escrow_due;
+        self.state.insurance_due = context.insurance_due;
 
         // update payment tracking
-        self.state.record_payment(amount, time_provider.now());
+        self.state.record_payment(amount, now);
         self.state.total_interest_paid += result.application.to_interest;
         self.state.total_fees_paid += result.application.to_fees;
 
+        // principal paid beyond what was due goes through the overpayment strategy
+        let application = &result.application;
+        let installments_paid = application.to_interest + application.to_principal;
+        let overpayment = match self.state.minimum_payment_due {
+            _ if principal_only => application.to_principal,
+            // installments are interest and principal, charges come on top
+            Some(minimum) if !self.state.installments.is_empty() => {
+                (installments_paid - installments_paid.min(minimum)).min(application.to_principal)
+            }
+            Some(minimum) => {
+                // escrow and insurance are billed on top of the installment
+                let charges = result.amount_applied - application.to_principal;
+                let due = (minimum + application.to_escrow + application.to_insurance).max(charges);
+                (result.amount_applied - result.amount_applied.min(due)).min(application.to_principal)
+            }
+            None => Money::ZERO,
+        };
+
+        // interest and principal that were due go to the oldest unpaid installments
+        if !self.state.installments.is_empty() {
+            self.state.allocate_to_installments(installments_paid - overpayment, now);
+            self.state.refresh_installments(now);
+        }
+
+        if overpayment > Money::ZERO && self.state.outstanding_principal > Money::ZERO {
+            let strategy = overpayment_strategy.unwrap_or(self.config.payment_config.overpayment_strategy);
+            result.overpayment = Some(self.apply_overpayment(overpayment, strategy, time_provider)?);
+        }
+
         // check if fully paid
         if self.state.total_outstanding().is_zero() {
-            self.state.update_status(FacilityStatus::Settled, time_provider.now());
+            self.state.update_status(FacilityStatus::Settled, now);
 
             self.events.emit(Event::FacilitySettled {
                 facility_id: self.id,
                 settlement_amount: amount,
-                timestamp: time_provider.now(),
+                timestamp: now,
             });
         }
 
@@ -266,7 +449,110 @@ impl Facility {
         Ok(result)
     }
 
-    /// apply penalty interest
+    /// interest and principal still owed on the installment currently due
+    fn current_installment_due(&self) -> Money {
+        if self.state.installments.is_empty() {
+            return Money::ZERO;
+        }
+        let minimum = self.state.minimum_payment_due.unwrap_or(Money::ZERO);
+        minimum - minimum.min(self.state.arrears_amount)
+    }
+
+    /// run an overpayment already taken off principal through the overpayment
+    /// handler and carry the new installment, term or limit into state
+    fn apply_overpayment(
+        &mut self,
+        overpayment: Money,
+        strategy: OverpaymentStrategy,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<OverpaymentResult> {
+        let (emi_amount, remaining_months, credit_limit) = match &self.state.facility_specific {
+            FacilitySpecificState::TermLoan { scheduled_payment, remaining_term_months, .. } => {
+                (*scheduled_payment, *remaining_term_months, None)
+            }
+            FacilitySpecificState::Revolving { credit_limit, .. } => {
+                (Money::ZERO, 0, Some(*credit_limit))
+            }
+            _ => (Money::ZERO, 0, None),
+        };
+        let loan_params = LoanParameters {
+            interest_rate: self.config.financial_terms.interest_rate,
+            remaining_months,
+            emi_amount,
+            credit_limit,
+            maturity_date: self.config.financial_terms.maturity_date,
+        };
+
+        // the handler takes the overpayment off principal itself
+        let mut context = PaymentContext {
+            facility_id: self.id,
+            accrued_fees: self.state.accrued_fees,
+            accrued_penalties: self.state.accrued_penalties,
+            accrued_interest: self.state.accrued_interest,
+            outstanding_principal: self.state.outstanding_principal + overpayment,
+            minimum_payment: None,
+            payment_due_date: self.state.next_payment_due,
+            days_overdue: self.state.days_past_due,
+            ..Default::default()
+        };
+
+        let handler = OverpaymentHandler::new(self.id, strategy);
+        let result = handler.handle(overpayment, &mut context, &loan_params, time_provider, &mut self.events)?;
+
+        match &mut self.state.facility_specific {
+            FacilitySpecificState::TermLoan { scheduled_payment, remaining_term_months, prepayment_amount, .. } => {
+                *prepayment_amount += overpayment;
+                if let Some(new_emi) = result.new_emi {
+                    *scheduled_payment = new_emi;
+                }
+                if let Some(new_term) = result.new_term_months {
+                    *remaining_term_months = new_term;
+                }
+            }
+            FacilitySpecificState::Revolving { credit_limit, available_credit, .. } => {
+                if let Some(new_limit) = result.new_credit_limit {
+                    *available_credit = *available_credit - (*credit_limit - new_limit).min(*available_credit);
+                    *credit_limit = new_limit;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(result)
+    }
+
+    /// park a partial payment in suspense without applying it
+    fn hold_in_suspense(
+        &mut self,
+        payment_id: Uuid,
+        amount: Money,
+        minimum_payment: Money,
+        reference: String,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> PaymentResult {
+        self.suspense.add_deposit(amount, now, reference);
+        self.state.suspense_balance = self.suspense.balance;
+
+        self.events.emit(Event::PaymentHeldInSuspense {
+            facility_id: self.id,
+            amount,
+            suspense_balance: self.suspense.balance,
+            minimum_payment,
+            timestamp: now,
+        });
+
+        PaymentResult {
+            payment_id,
+            amount_applied: Money::ZERO,
+            application: PaymentApplication::default(),
+            remaining_balance: self.state.total_outstanding(),
+            payment_date: now,
+            overpayment: None,
+        }
+    }
+
+    /// apply penalty interest on the overdue amount for the days overdue not
+    /// yet charged
     pub fn apply_penalty_interest(
         &mut self,
         time_provider: &SafeTimeProvider,
@@ -275,32 +561,72 @@ impl Facility {
             return Ok(Money::ZERO);
         }
 
+        // in default the whole balance accrues at the default rate instead
+        if self.in_default_rate() {
+            return Ok(Money::ZERO);
+        }
+
         let penalty_config = self.config.interest_config.penalty_config
             .as_ref()
             .ok_or(FacilityError::InvalidConfiguration {
                 message: "No penalty configuration".to_string(),
             })?;
 
-        let engine = PenaltyEngine::new(penalty_config.clone());
+        let now = time_provider.now();
+        let year_basis = AccrualEngine::new(self.config.interest_config.day_count_convention)
+            .year_basis(now.year());
+        let engine = PenaltyEngine::new(penalty_config.clone()).with_year_basis(year_basis);
+        let days_past_due = self.state.days_past_due;
+
+        // whole days since the last charge; the first charge of a delinquency
+        // covers it so far
+        let uncharged_days = match self.state.last_penalty_accrual {
+            Some(last) if (now - last).num_days() < days_past_due as i64 => {
+                let days = (now - last).num_days().max(0);
+                self.state.last_penalty_accrual = Some(last + chrono::Duration::days(days));
+                days as u32
+            }
+            _ => {
+                self.state.last_penalty_accrual = Some(now);
+                days_past_due
+            }
+        };
 
         // calculate penalty on overdue amount
-        let overdue_amount = self.state.minimum_payment_due.unwrap_or(Money::ZERO);
-        let calculation = engine.calculate_penalty(overdue_amount, self.state.days_past_due);
+        let overdue_amount = if self.state.installments.is_empty() {
+            self.state.minimum_payment_due.unwrap_or(Money::ZERO)
+        } else {
+            self.state.arrears_amount
+        };
+        let calculation = engine.calculate_daily_penalty(
+            overdue_amount,
+            days_past_due - uncharged_days,
+            days_past_due,
+        );
 
         if calculation.penalty_amount > Money::ZERO {
             self.state.accrued_penalties += calculation.penalty_amount;
+            if self.state.non_accrual {
+                self.state.suspended_interest += calculation.penalty_amount;
+            }
 
             self.events.emit(Event::PenaltyInterestApplied {
                 facility_id: self.id,
                 amount: calculation.penalty_amount,
-                days_overdue: self.state.days_past_due,
-                timestamp: time_provider.now(),
+                days_overdue: days_past_due,
+                timestamp: now,
             });
         }
 
         Ok(calculation.penalty_amount)
     }
 
+    /// in default with a default rate configured
+    pub(crate) fn in_default_rate(&self) -> bool {
+        self.state.status == FacilityStatus::Default
+            && self.config.interest_config.default_rate.is_some()
+    }
+
     /// update collateral
     pub fn update_collateral(
         &mut self,
@@ -391,92 +717,242 @@ impl Facility {
 
     /// update daily status based on actual time
     pub fn update_daily_status(&mut self, time_provider: &SafeTimeProvider) -> Result<()> {
+        self.update_daily_status_on(self.state.outstanding_principal, time_provider)
+    }
+
+    /// update daily status, accruing interest on the given principal
+    pub(crate) fn update_daily_status_on(
+        &mut self,
+        principal: Money,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
         let now = time_provider.now();
 
-        // calculate actual days past due
-        if let Some(due_date) = self.state.next_payment_due {
-            if now > due_date {
+        // written off balances no longer move through delinquency
+        if self.state.status == FacilityStatus::ChargedOff {
+            return Ok(());
+        }
+
+        // overdue since the oldest unpaid installment, or the next payment due
+        // date when no installments are tracked
+        let overdue_since = if self.state.installments.is_empty() {
+            self.state.next_payment_due.filter(|due_date| {
                 // check if payment was made after due date
                 let payment_made = self.state.last_payment_date
-                    .map(|pd| pd >= due_date)
+                    .map(|pd| pd >= *due_date)
                     .unwrap_or(false);
+                now > *due_date && !payment_made
+            })
+        } else {
+            self.state.refresh_installments(now);
+            self.state.oldest_past_due_installment(now).map(|i| i.due_date)
+        };
 
-                if !payment_made {
-                    // calculate days past due
-                    let days_overdue = (now - due_date).num_days() as u32;
-                    self.state.days_past_due = days_overdue;
-
-                    // update status based on DPD and grace period
-                    let grace_period = self.config.interest_config.grace_period_days;
-
-                    let new_status = match days_overdue {
-                        0 => FacilityStatus::Active,
-                        d if d <= grace_period => FacilityStatus::GracePeriod,
-                        _ => FacilityStatus::Delinquent,
-                    };
-
-                    // emit event if status changed
-                    if new_status != self.state.status {
-                        let old_status = self.state.status;
-                        self.state.update_status(new_status, now);
+        // calculate actual days past due; a new delinquency starts its
+        // penalty charges afresh
+        match overdue_since {
+            Some(due_date) => self.state.days_past_due = (now - due_date).num_days() as u32,
+            None => {
+                self.state.days_past_due = 0;
+                self.state.last_penalty_accrual = None;
+            }
+        }
+        self.update_delinquency_bucket(now);
+
+        if let Some(due_date) = overdue_since {
+            let days_overdue = self.state.days_past_due;
+
+            // update status based on DPD, grace period and delinquency policy
+            let grace_period = self.config.interest_config.grace_period_days;
+            let policy = &self.config.delinquency_policy;
+            let reached = |threshold: Option<u32>| threshold.is_some_and(|days| days_overdue >= days);
+
+            let new_status = match days_overdue {
+                0 => FacilityStatus::Active,
+                _ if reached(policy.charge_off_after_days) => FacilityStatus::ChargedOff,
+                // default is only cured once arrears are cleared
+                _ if self.state.is_in_default() => self.state.status,
+                _ if reached(policy.default_after_days) => FacilityStatus::Default,
+                d if d <= grace_period => FacilityStatus::GracePeriod,
+                _ => FacilityStatus::Delinquent,
+            };
+
+            // emit event if status changed
+            if new_status != self.state.status {
+                let old_status = self.state.status;
+                self.state.update_status(new_status, now);
+
+                self.events.emit(Event::StatusChanged {
+                    facility_id: self.id,
+                    old_status,
+                    new_status,
+                    reason: format!("{} days past due", days_overdue),
+                    timestamp: now,
+                });
+
+                // emit grace period event if entering grace
+                if new_status == FacilityStatus::GracePeriod && old_status == FacilityStatus::Active {
+                    self.events.emit(Event::GracePeriodStarted {
+                        facility_id: self.id,
+                        payment_due_date: due_date.date_naive(),
+                        grace_ends_at: (due_date + chrono::Duration::days(grace_period as i64)).date_naive(),
+                        timestamp: now,
+                    });
+                }
 
-                        self.events.emit(Event::StatusChanged {
+                // emit grace period expired if leaving grace
+                if old_status == FacilityStatus::GracePeriod && days_overdue > grace_period {
+                    self.events.emit(Event::GracePeriodExpired {
+                        facility_id: self.id,
+                        days_overdue,
+                        timestamp: now,
+                    });
+
+                    // apply late fee; installments are charged individually below
+                    let amount_due = self.state.minimum_payment_due
+                        .or(self.state.next_payment_amount)
+                        .unwrap_or(Money::ZERO);
+                    let late_fee = self.config.fee_config.late_fee_for(amount_due)
+                        .filter(|_| self.state.installments.is_empty());
+                    if let Some(fee) = late_fee {
+                        self.state.charge_fee("late_fee", fee);
+
+                        self.events.emit(Event::LateFeeApplied {
                             facility_id: self.id,
-                            old_status,
-                            new_status,
-                            reason: format!("{} days past due", days_overdue),
+                            fee_amount: fee,
+                            days_overdue,
+                            installment_number: None,
                             timestamp: now,
                         });
-
-                        // emit grace period event if entering grace
-                        if new_status == FacilityStatus::GracePeriod && old_status == FacilityStatus::Active {
-                            self.events.emit(Event::GracePeriodStarted {
-                                facility_id: self.id,
-                                payment_due_date: due_date.date_naive(),
-                                grace_ends_at: (due_date + chrono::Duration::days(grace_period as i64)).date_naive(),
-                                timestamp: now,
-                            });
-                        }
-
-                        // emit grace period expired if leaving grace
-                        if old_status == FacilityStatus::GracePeriod && new_status == FacilityStatus::Delinquent {
-                            self.events.emit(Event::GracePeriodExpired {
-                                facility_id: self.id,
-                                days_overdue,
-                                timestamp: now,
-                            });
-
-                            // apply late fee
-                            if let Some(fee) = self.config.fee_config.late_fee {
-                                self.state.accrued_fees += fee;
-                                self.state.total_fees_charged += fee;
-
-                                self.events.emit(Event::LateFeeApplied {
-                                    facility_id: self.id,
-                                    fee_amount: fee,
-                                    days_overdue,
-                                    timestamp: now,
-                                });
-                            }
-                        }
                     }
+                }
 
-                    // apply penalty interest if past grace period
-                    if days_overdue > grace_period {
-                        self.apply_penalty_interest(time_provider)?;
-                    }
+                if new_status == FacilityStatus::ChargedOff {
+                    self.write_off(now);
+                    return Ok(());
                 }
             }
+
+            self.assess_installment_late_fees(now);
+
+            // apply penalty interest if past grace period
+            if days_overdue > grace_period && self.config.interest_config.penalty_config.is_some() {
+                self.apply_penalty_interest(time_provider)?;
+            }
+        } else if matches!(
+            self.state.status,
+            FacilityStatus::GracePeriod | FacilityStatus::Delinquent | FacilityStatus::Default
+        ) {
+            // arrears cleared
+            let old_status = self.state.status;
+            self.state.update_status(FacilityStatus::Active, now);
+
+            self.events.emit(Event::StatusChanged {
+                facility_id: self.id,
+                old_status,
+                new_status: FacilityStatus::Active,
+                reason: "Arrears cleared".to_string(),
+                timestamp: now,
+            });
         }
 
         // accrue daily interest regardless of status
-        if self.state.outstanding_principal > Money::ZERO {
-            self.accrue_interest(time_provider)?;
+        if principal > Money::ZERO {
+            self.accrue_interest_on(principal, time_provider)?;
         }
 
         Ok(())
     }
 
+    /// charge one late fee on each installment still unpaid after the grace
+    /// period. installment waterfalls pay installments ahead of late fees, so
+    /// fees never pyramid
+    fn assess_installment_late_fees(&mut self, now: DateTime<Utc>) {
+        let grace_period = self.config.interest_config.grace_period_days;
+
+        for index in 0..self.state.installments.len() {
+            let installment = &self.state.installments[index];
+            let days_overdue = (now - installment.due_date).num_days().max(0) as u32;
+            if installment.late_fee_charged
+                || installment.status == InstallmentStatus::Paid
+                || days_overdue <= grace_period
+            {
+                continue;
+            }
+            let number = installment.number;
+            let fee = self.config.fee_config.late_fee_for(installment.amount);
+
+            self.state.installments[index].late_fee_charged = true;
+            if let Some(fee) = fee {
+                self.state.charge_fee("late_fee", fee);
+
+                self.events.emit(Event::LateFeeApplied {
+                    facility_id: self.id,
+                    fee_amount: fee,
+                    days_overdue,
+                    installment_number: Some(number),
+                    timestamp: now,
+                });
+            }
+        }
+    }
+
+    /// move to the bucket for the current DPD, entering or leaving non-accrual
+    fn update_delinquency_bucket(&mut self, now: DateTime<Utc>) {
+        let days_past_due = self.state.days_past_due;
+        let bucket = self.config.delinquency_policy.bucket_for(days_past_due);
+
+        if bucket != self.state.delinquency_bucket {
+            self.events.emit(Event::DelinquencyBucketChanged {
+                facility_id: self.id,
+                old_bucket: self.state.delinquency_bucket,
+                new_bucket: bucket,
+                days_past_due,
+                timestamp: now,
+            });
+            self.state.delinquency_bucket = bucket;
+        }
+
+        // accrual resumes only once the facility is current again
+        let non_accrual = match self.config.delinquency_policy.non_accrual_after_days {
+            _ if days_past_due == 0 => false,
+            Some(days) if days_past_due >= days => true,
+            _ => self.state.non_accrual,
+        };
+
+        if non_accrual != self.state.non_accrual {
+            self.state.non_accrual = non_accrual;
+
+            // interest held in suspense is recognised once accrual resumes
+            let recognised_interest = if non_accrual {
+                Money::ZERO
+            } else {
+                std::mem::replace(&mut self.state.suspended_interest, Money::ZERO)
+            };
+
+            self.events.emit(Event::NonAccrualStatusChanged {
+                facility_id: self.id,
+                non_accrual,
+                days_past_due,
+                recognised_interest,
+                timestamp: now,
+            });
+        }
+    }
+
+    /// record the outstanding balance as a loss on charge-off
+    fn write_off(&mut self, now: DateTime<Utc>) {
+        let loss_amount = self.state.total_outstanding();
+        self.state.write_off_amount = Some(loss_amount);
+        self.state.write_off_date = Some(now);
+
+        self.events.emit(Event::FacilityChargedOff {
+            facility_id: self.id,
+            loss_amount,
+            timestamp: now,
+        });
+    }
+
     /// get events
     pub fn take_events(&mut self) -> Vec<Event> {
         self.events.take_events()
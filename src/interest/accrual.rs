@@ -102,7 +102,7 @@ impl AccrualEngine {
         
         let mut accrual_date = last_accrual;
         for _ in 0..days {
-            accrual_date = accrual_date + chrono::Duration::days(1);
+            accrual_date += chrono::Duration::days(1);
             let interest = principal.as_decimal() * daily_rate;
             
             accruals.push(DailyAccrual {
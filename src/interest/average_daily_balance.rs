@@ -0,0 +1,213 @@
+use chrono::{DateTime, Datelike, Utc};
+use rust_decimal::Decimal;
+
+use crate::decimal::{Money, Rate};
+use crate::interest::{AccrualEngine, DayCountConvention};
+
+/// method used to charge interest on revolving balances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum InterestMethod {
+    /// accrue daily on the end-of-day principal
+    DailyAccrual,
+    /// average daily balance per billing cycle, charged at statement cut
+    AverageDailyBalance {
+        /// include transactions posted during the cycle in the daily balances
+        include_new_transactions: bool,
+    },
+}
+
+/// point-in-time balance of a segment after a posting
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceChange {
+    pub timestamp: DateTime<Utc>,
+    /// balance after the posting
+    pub balance: Money,
+    /// amount of new transactions (draws) included in this posting
+    pub new_transactions: Money,
+}
+
+/// end-of-day balance used in the average daily balance calculation
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyBalance {
+    pub date: DateTime<Utc>,
+    pub balance: Money,
+    /// cumulative new transactions posted in the cycle up to this day
+    pub new_transactions: Money,
+}
+
+/// result of an average daily balance calculation
+#[derive(Debug, Clone, PartialEq)]
+pub struct AverageDailyBalanceResult {
+    pub average_daily_balance: Money,
+    pub daily_periodic_rate: Rate,
+    pub days: u32,
+    pub interest_amount: Money,
+}
+
+/// engine for average daily balance interest
+pub struct AverageDailyBalanceEngine {
+    pub convention: DayCountConvention,
+    pub include_new_transactions: bool,
+}
+
+impl AverageDailyBalanceEngine {
+    pub fn new(convention: DayCountConvention, include_new_transactions: bool) -> Self {
+        Self {
+            convention,
+            include_new_transactions,
+        }
+    }
+
+    /// expand balance changes into end-of-day balances for the cycle
+    pub fn daily_balances(
+        &self,
+        changes: &[BalanceChange],
+        cycle_start: DateTime<Utc>,
+        cycle_end: DateTime<Utc>,
+    ) -> Vec<DailyBalance> {
+        let days = (cycle_end - cycle_start).num_days();
+        let mut balances = Vec::new();
+
+        for day in 0..days {
+            let day_start = cycle_start + chrono::Duration::days(day);
+            let day_end = day_start + chrono::Duration::days(1);
+
+            // balance at end of day is the last posting before the day closes
+            let balance = changes
+                .iter()
+                .rfind(|c| c.timestamp < day_end)
+                .map(|c| c.balance)
+                .unwrap_or(Money::ZERO);
+
+            let new_transactions = changes
+                .iter()
+                .filter(|c| c.timestamp >= cycle_start && c.timestamp < day_end)
+                .fold(Money::ZERO, |acc, c| acc + c.new_transactions);
+
+            balances.push(DailyBalance {
+                date: day_start,
+                balance,
+                new_transactions,
+            });
+        }
+
+        balances
+    }
+
+    /// calculate cycle interest with daily periodic rate compounding
+    pub fn calculate(
+        &self,
+        daily_balances: &[DailyBalance],
+        annual_rate: Rate,
+        cycle_end: DateTime<Utc>,
+    ) -> AverageDailyBalanceResult {
+        let year_basis = AccrualEngine::new(self.convention).year_basis(cycle_end.year());
+        let daily_rate = annual_rate.as_decimal() / Decimal::from(year_basis);
+        let days = daily_balances.len() as u32;
+
+        if days == 0 {
+            return AverageDailyBalanceResult {
+                average_daily_balance: Money::ZERO,
+                daily_periodic_rate: Rate::from_decimal(daily_rate),
+                days: 0,
+                interest_amount: Money::ZERO,
+            };
+        }
+
+        // each day's interest is added to the next day's balance
+        let mut compounded_interest = Decimal::ZERO;
+        let mut balance_sum = Decimal::ZERO;
+
+        for day in daily_balances {
+            let mut principal = day.balance;
+            if !self.include_new_transactions {
+                principal = (principal - day.new_transactions).max(Money::ZERO);
+            }
+
+            let balance = principal.as_decimal() + compounded_interest;
+            balance_sum += balance;
+            compounded_interest += balance * daily_rate;
+        }
+
+        let average = balance_sum / Decimal::from(days);
+
+        AverageDailyBalanceResult {
+            average_daily_balance: Money::from_decimal(average),
+            daily_periodic_rate: Rate::from_decimal(daily_rate),
+            days,
+            interest_amount: Money::from_decimal(average * daily_rate * Decimal::from(days)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn change(day: u32, balance: i64, new_transactions: i64) -> BalanceChange {
+        BalanceChange {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, day, 12, 0, 0).unwrap(),
+            balance: Money::from_major(balance),
+            new_transactions: Money::from_major(new_transactions),
+        }
+    }
+
+    #[test]
+    fn test_daily_balances_from_changes() {
+        let engine = AverageDailyBalanceEngine::new(DayCountConvention::Actual365, true);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
+
+        let changes = vec![change(1, 1_000, 1_000), change(16, 3_000, 2_000)];
+        let balances = engine.daily_balances(&changes, start, end);
+
+        assert_eq!(balances.len(), 30);
+        assert_eq!(balances[0].balance, Money::from_major(1_000));
+        assert_eq!(balances[14].balance, Money::from_major(1_000));
+        assert_eq!(balances[15].balance, Money::from_major(3_000));
+        assert_eq!(balances[29].new_transactions, Money::from_major(3_000));
+    }
+
+    #[test]
+    fn test_average_daily_balance_interest() {
+        let engine = AverageDailyBalanceEngine::new(DayCountConvention::Actual365, true);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
+
+        // $1,000 for 15 days then $3,000 for 15 days
+        let changes = vec![change(1, 1_000, 1_000), change(16, 3_000, 2_000)];
+        let balances = engine.daily_balances(&changes, start, end);
+        let result = engine.calculate(&balances, Rate::from_percentage(18), end);
+
+        // compounding pushes the average slightly above $2,000
+        assert!(result.average_daily_balance > Money::from_major(2_000));
+        assert!(result.average_daily_balance < Money::from_major(2_020));
+
+        let simple = Money::from_major(2_000) * (dec!(0.18) / dec!(365) * dec!(30));
+        assert!(result.interest_amount > simple);
+        assert_eq!(result.interest_amount.round_dp(2), Money::from_str_exact("29.75").unwrap());
+    }
+
+    #[test]
+    fn test_excluding_new_transactions() {
+        let engine = AverageDailyBalanceEngine::new(DayCountConvention::Actual365, false);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
+
+        // opening balance posted before the cycle, new purchase mid-cycle
+        let opening = BalanceChange {
+            timestamp: start - chrono::Duration::days(1),
+            balance: Money::from_major(1_000),
+            new_transactions: Money::ZERO,
+        };
+        let changes = vec![opening, change(16, 3_000, 2_000)];
+        let balances = engine.daily_balances(&changes, start, end);
+        let result = engine.calculate(&balances, Rate::from_percentage(18), end);
+
+        // only the opening $1,000 is charged
+        assert!(result.average_daily_balance >= Money::from_major(1_000));
+        assert!(result.average_daily_balance < Money::from_major(1_010));
+    }
+}
@@ -92,19 +92,8 @@ impl CompoundingEngine {
         annual_rate: Rate,
         time_years: Decimal,
     ) -> Money {
-        // use approximation for e^x = 1 + x + x^2/2! + x^3/3! + ...
-        let rate_decimal = annual_rate.as_decimal();
-        let x = rate_decimal * time_years;
-        
-        // taylor series approximation for e^x (first 10 terms for accuracy)
-        let mut compound_factor = Decimal::ONE;
-        let mut term = Decimal::ONE;
-        for i in 1..10 {
-            term = term * x / Decimal::from(i);
-            compound_factor = compound_factor + term;
-        }
-        
-        let final_amount = principal.as_decimal() * compound_factor;
+        let compound_factor = exp(annual_rate.as_decimal() * time_years);
+        let final_amount = principal.as_decimal().saturating_mul(compound_factor);
         Money::from_decimal(final_amount - principal.as_decimal())
     }
     
@@ -200,6 +189,43 @@ impl InterestCalculator for CompoundingEngine {
     }
 }
 
+/// e^x to full decimal precision
+///
+/// the argument is halved until it is below one so the taylor series
+/// converges quickly, then the result is squared back up. saturates at
+/// `Decimal::MAX` once e^x leaves the decimal range (x above about 66)
+pub fn exp(x: Decimal) -> Decimal {
+    if x.is_sign_negative() {
+        return Decimal::ONE / exp(-x);
+    }
+    
+    let mut reduced = x;
+    let mut halvings = 0;
+    while reduced > Decimal::ONE {
+        reduced /= dec!(2);
+        halvings += 1;
+    }
+    
+    // sum terms until they vanish at decimal precision
+    let mut result = Decimal::ONE;
+    let mut term = Decimal::ONE;
+    for i in 1..64u32 {
+        term = term * reduced / Decimal::from(i);
+        if term.is_zero() {
+            break;
+        }
+        result += term;
+    }
+    
+    for _ in 0..halvings {
+        result = match result.checked_mul(result) {
+            Some(squared) => squared,
+            None => return Decimal::MAX,
+        };
+    }
+    result
+}
+
 /// calculate the future value with compound interest
 pub fn future_value(
     present_value: Money,
@@ -221,14 +247,7 @@ pub fn present_value(
 ) -> Money {
     match frequency {
         CompoundingFrequency::Continuous => {
-            // use taylor series for e^(-x)
-            let x = annual_rate.as_decimal() * years;
-            let mut discount_factor = Decimal::ONE;
-            let mut term = Decimal::ONE;
-            for i in 1..10 {
-                term = term * (-x) / Decimal::from(i);
-                discount_factor = discount_factor + term;
-            }
+            let discount_factor = exp(-(annual_rate.as_decimal() * years));
             Money::from_decimal(future_value.as_decimal() * discount_factor)
         }
         _ => {
@@ -252,6 +271,27 @@ pub fn present_value(
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_exp_precision() {
+        assert_eq!(exp(Decimal::ZERO), Decimal::ONE);
+        assert_eq!(exp(Decimal::ONE).round_dp(20), dec!(2.71828182845904523536));
+        assert_eq!(exp(dec!(-1)).round_dp(20), dec!(0.36787944117144232160));
+        
+        // daily continuous factor for 20% APR
+        let daily = exp(dec!(0.2) / dec!(365));
+        assert_eq!(daily.round_dp(18), dec!(1.000548095354876851));
+    }
+    
+    #[test]
+    fn test_exp_saturates_beyond_decimal_range() {
+        assert_eq!(exp(dec!(100)), Decimal::MAX);
+        assert!(exp(dec!(-100)) < dec!(0.000000000000000000000001));
+        
+        // discounting at a huge rate times term leaves almost nothing
+        let pv = present_value(Money::from_major(1_000), Rate::from_percentage(500), dec!(20), CompoundingFrequency::Continuous);
+        assert_eq!(pv.round_dp(2), Money::ZERO);
+    }
+    
     #[test]
     fn test_monthly_compounding() {
         let engine = CompoundingEngine::new(CompoundingFrequency::Monthly);
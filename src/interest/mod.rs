@@ -1,4 +1,5 @@
 pub mod accrual;
+pub mod average_daily_balance;
 pub mod compound;
 pub mod penalty;
 
@@ -9,7 +10,11 @@ use crate::decimal::{Money, Rate};
 use crate::errors::Result;
 
 pub use accrual::{AccrualEngine, DailyAccrual, DayCountConvention, MonthlyAccrual};
-pub use compound::{CompoundingEngine, CompoundingFrequency};
+pub use average_daily_balance::{
+    AverageDailyBalanceEngine, AverageDailyBalanceResult, BalanceChange, DailyBalance,
+    InterestMethod,
+};
+pub use compound::{exp, CompoundingEngine, CompoundingFrequency};
 pub use penalty::{PenaltyConfig, PenaltyEngine};
 
 /// interest calculation result
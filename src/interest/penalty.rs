@@ -1,6 +1,5 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
-use rust_decimal_macros::dec;
 
 use crate::decimal::{Money, Rate};
 use crate::errors::Result;
@@ -21,6 +20,9 @@ pub struct PenaltyConfig {
     pub minimum_penalty: Money,
     /// maximum penalty rate cap
     pub maximum_penalty_rate: Option<Rate>,
+    /// progressive rates by days overdue, the penalty rate applies outside
+    /// them. opt-in: the product presets leave this empty
+    pub tiers: Vec<PenaltyTier>,
 }
 
 impl PenaltyConfig {
@@ -32,8 +34,15 @@ impl PenaltyConfig {
             grace_period_days: 0,
             minimum_penalty: Money::ZERO,
             maximum_penalty_rate: None,
+            tiers: Vec::new(),
         }
     }
+
+    /// charge progressive rates by days overdue
+    pub fn with_tiers(mut self, tiers: Vec<PenaltyTier>) -> Self {
+        self.tiers = tiers;
+        self
+    }
     
     /// get the effective penalty rate
     pub fn effective_penalty_rate(&self) -> Rate {
@@ -49,16 +58,43 @@ impl PenaltyConfig {
             penalty_rate
         }
     }
+
+    /// penalty rate charged for a given day overdue
+    pub fn rate_for_day(&self, days_overdue: u32) -> Rate {
+        let tier = self.tiers.iter().find(|tier| {
+            days_overdue >= tier.min_days && tier.max_days.is_none_or(|max| days_overdue <= max)
+        });
+
+        match tier {
+            Some(tier) => {
+                let rate = self.base_rate.as_decimal() * tier.rate_multiplier;
+                let capped = match self.maximum_penalty_rate {
+                    Some(max_rate) => rate.min(max_rate.as_decimal()),
+                    None => rate,
+                };
+                Rate::from_decimal(capped)
+            }
+            None => self.effective_penalty_rate(),
+        }
+    }
 }
 
 /// engine for calculating penalty interest
 pub struct PenaltyEngine {
     pub config: PenaltyConfig,
+    /// days in the year for daily rates, from the day count convention
+    pub year_basis: u32,
 }
 
 impl PenaltyEngine {
     pub fn new(config: PenaltyConfig) -> Self {
-        Self { config }
+        Self { config, year_basis: 365 }
+    }
+
+    /// daily rates over a year basis other than 365
+    pub fn with_year_basis(mut self, year_basis: u32) -> Self {
+        self.year_basis = year_basis;
+        self
     }
     
     /// calculate penalty interest on overdue amount
@@ -79,7 +115,7 @@ impl PenaltyEngine {
         
         let days_charged = days_overdue - self.config.grace_period_days;
         let penalty_rate = self.config.effective_penalty_rate();
-        let daily_penalty_rate = penalty_rate.as_decimal() / dec!(365);
+        let daily_penalty_rate = penalty_rate.as_decimal() / Decimal::from(self.year_basis);
         
         let penalty = overdue_amount.as_decimal() * daily_penalty_rate * Decimal::from(days_charged);
         let penalty_amount = Money::from_decimal(penalty).max(self.config.minimum_penalty);
@@ -93,6 +129,30 @@ impl PenaltyEngine {
         }
     }
     
+    /// penalty for days `from_day + 1` through `to_day` overdue, each day at its
+    /// tier rate. used for daily accrual, so no minimum penalty is applied
+    pub fn calculate_daily_penalty(
+        &self,
+        overdue_amount: Money,
+        from_day: u32,
+        to_day: u32,
+    ) -> PenaltyCalculation {
+        let first_day = from_day.max(self.config.grace_period_days) + 1;
+        let year_basis = Decimal::from(self.year_basis);
+
+        let penalty: Decimal = (first_day..=to_day)
+            .map(|day| overdue_amount.as_decimal() * self.config.rate_for_day(day).as_decimal() / year_basis)
+            .sum();
+
+        PenaltyCalculation {
+            penalty_amount: Money::from_decimal(penalty),
+            effective_rate: self.config.rate_for_day(to_day),
+            days_charged: (to_day + 1).saturating_sub(first_day),
+            overdue_base: overdue_amount,
+            grace_applied: to_day <= self.config.grace_period_days,
+        }
+    }
+
     /// calculate default interest (entire balance at penalty rate)
     pub fn calculate_default_interest(
         &self,
@@ -100,7 +160,7 @@ impl PenaltyEngine {
         days_in_default: u32,
     ) -> DefaultInterest {
         let penalty_rate = self.config.effective_penalty_rate();
-        let daily_rate = penalty_rate.as_decimal() / dec!(365);
+        let daily_rate = penalty_rate.as_decimal() / Decimal::from(self.year_basis);
         
         let interest = outstanding_balance.as_decimal() * daily_rate * Decimal::from(days_in_default);
         
@@ -133,7 +193,7 @@ impl PenaltyEngine {
                 let tier_rate = Rate::from_decimal(
                     self.config.base_rate.as_decimal() * tier.rate_multiplier
                 );
-                let daily_rate = tier_rate.as_decimal() / dec!(365);
+                let daily_rate = tier_rate.as_decimal() / Decimal::from(self.year_basis);
                 let tier_penalty = overdue_amount.as_decimal() * daily_rate * Decimal::from(days_in_tier);
                 
                 let penalty_amount = Money::from_decimal(tier_penalty);
@@ -181,7 +241,7 @@ impl InterestCalculator for PenaltyEngine {
     }
     
     fn get_daily_rate(&self, annual_rate: Rate) -> Rate {
-        Rate::from_decimal(annual_rate.as_decimal() / dec!(365))
+        Rate::from_decimal(annual_rate.as_decimal() / Decimal::from(self.year_basis))
     }
 }
 
@@ -205,7 +265,7 @@ pub struct DefaultInterest {
 }
 
 /// penalty tier for progressive penalties
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct PenaltyTier {
     pub name: String,
     pub min_days: u32,
@@ -234,6 +294,7 @@ pub struct TierCalculation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal_macros::dec;
     
     #[test]
     fn test_basic_penalty() {
@@ -346,4 +407,54 @@ mod tests {
         
         assert!(result.total_penalty > Money::ZERO);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_daily_penalty_accrues_incrementally_by_tier() {
+        let config = PenaltyConfig::new(Rate::from_percentage(10), dec!(1.5)).with_tiers(vec![
+            PenaltyTier {
+                name: "Early".to_string(),
+                min_days: 1,
+                max_days: Some(30),
+                rate_multiplier: dec!(1.5),
+            },
+            PenaltyTier {
+                name: "Late".to_string(),
+                min_days: 31,
+                max_days: None,
+                rate_multiplier: dec!(2.0),
+            },
+        ]);
+        assert_eq!(config.rate_for_day(30), Rate::from_percentage(15));
+        assert_eq!(config.rate_for_day(31), Rate::from_percentage(20));
+        let engine = PenaltyEngine::new(config);
+
+        let overdue = Money::from_major(1_000);
+        let first_ten = engine.calculate_daily_penalty(overdue, 0, 10);
+        let cumulative = engine.calculate_penalty(overdue, 10);
+        assert_eq!(first_ten.penalty_amount.round_dp(2), cumulative.penalty_amount.round_dp(2));
+        assert_eq!(first_ten.days_charged, 10);
+
+        // charging day by day adds up to charging the whole period at once
+        let whole = engine.calculate_daily_penalty(overdue, 0, 45);
+        let split = engine.calculate_daily_penalty(overdue, 0, 30).penalty_amount
+            + engine.calculate_daily_penalty(overdue, 30, 45).penalty_amount;
+        assert_eq!(whole.penalty_amount.round_dp(6), split.round_dp(6));
+        assert_eq!(whole.effective_rate, Rate::from_percentage(20));
+
+        let day_31 = engine.calculate_daily_penalty(overdue, 30, 31);
+        assert_eq!(day_31.penalty_amount.round_dp(2), Money::from_str_exact("0.55").unwrap());
+    }
+
+    #[test]
+    fn test_penalty_follows_year_basis() {
+        let config = PenaltyConfig::new(Rate::from_percentage(10), dec!(1.5));
+        let engine = PenaltyEngine::new(config).with_year_basis(360);
+
+        let overdue = Money::from_major(1_000);
+        let result = engine.calculate_daily_penalty(overdue, 0, 24);
+
+        // 1,000 at 15% over 24 of 360 days
+        assert_eq!(result.penalty_amount.round_dp(2), Money::from_major(10));
+        assert_eq!(engine.get_daily_rate(Rate::from_percentage(36)), Rate::from_decimal(dec!(0.001)));
+    }
+}
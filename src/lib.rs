@@ -15,17 +15,19 @@ pub use decimal::{Money, Rate};
 pub use errors::{FacilityError, Result};
 pub use events::{Event, EventStore};
 pub use interest::{
-    AccrualEngine, CompoundingEngine, DayCountConvention, InterestCalculation, PenaltyConfig,
-    PenaltyEngine,
+    AccrualEngine, CompoundingEngine, DayCountConvention, InterestCalculation, InterestMethod,
+    PenaltyConfig, PenaltyEngine,
 };
 pub use collateral::{
     BitcoinCollateral, LiquidationEngine, LiquidationMethod, LiquidationResult,
     LtvCalculator, LtvMonitor, PriceFeed,
 };
 pub use types::{
-    AmortizationMethod, CollateralPosition, DeficiencyBalance,
-    FacilityId, FacilityStatus, LtvStatus, LtvThresholds, OpenTermType, OverpaymentStrategy,
+    AmortizationMethod, BalanceSegment, CollateralPosition, CreditLimitChangeReason,
+    DeficiencyBalance, DelinquencyBucket, DrawType, FacilityId, FacilityStatus, LtvStatus, LtvThresholds,
+    OpenTermType, OverdraftFeeKind, OverdraftReturnReason, OverpaymentStrategy,
     PaymentApplication, PaymentSchedule, RecoveryStatus, RevolvingType, TermLoanType,
+    TransactionFeeKind,
 };
 
 // re-export external dependencies that users will need
@@ -99,49 +99,68 @@ impl AmortizationSchedule {
         strategy: RecalculationStrategy,
         time_provider: &SafeTimeProvider,
     ) -> Result<()> {
-        let remaining_balance = self.balance_after_payment(after_payment_number) - prepayment_amount;
-        let remaining_months = self.term_months - after_payment_number;
-        
-        match strategy {
-            RecalculationStrategy::ReduceEmi => {
-                // recalculate with same term, lower EMI
-                let calculator = AmortizationCalculator::new(self.amortization_method);
-                let new_payments = calculator.calculate_schedule(
-                    remaining_balance,
-                    self.interest_rate,
-                    remaining_months,
-                    self.payments[after_payment_number as usize].payment_date,
-                    time_provider,
-                )?;
-                
-                // replace future payments
-                self.payments.truncate(after_payment_number as usize);
-                self.payments.extend(new_payments);
-            }
+        let remaining_balance = if after_payment_number == 0 {
+            self.principal
+        } else {
+            self.balance_after_payment(after_payment_number)
+        } - prepayment_amount;
+        
+        self.recalculate_from_balance(remaining_balance, after_payment_number, strategy, time_provider)
+    }
+    
+    /// re-amortize the payments after `after_payment_number` from an actual balance
+    pub fn recalculate_from_balance(
+        &mut self,
+        remaining_balance: Money,
+        after_payment_number: u32,
+        strategy: RecalculationStrategy,
+        time_provider: &SafeTimeProvider,
+    ) -> Result<()> {
+        let remaining_months = self.term_months.saturating_sub(after_payment_number);
+        let index = after_payment_number as usize;
+        
+        // future payments continue from the last settled payment date
+        let from_date = match index {
+            0 => self.start_date,
+            _ => self.payments[index - 1].payment_date,
+        };
+        let (cumulative_interest, cumulative_principal) = match index {
+            0 => (Money::ZERO, Money::ZERO),
+            _ => (self.payments[index - 1].cumulative_interest, self.payments[index - 1].cumulative_principal),
+        };
+        
+        let new_term = match strategy {
+            RecalculationStrategy::ReduceEmi => remaining_months,
             RecalculationStrategy::ReduceTerm => {
                 // keep EMI same, reduce term
-                let current_emi = self.payments[0].payment_amount;
-                let new_term = calculate_term_for_payment(
-                    remaining_balance,
-                    self.interest_rate,
-                    current_emi,
-                );
-                
-                let calculator = AmortizationCalculator::new(self.amortization_method);
-                let new_payments = calculator.calculate_schedule(
-                    remaining_balance,
-                    self.interest_rate,
-                    new_term,
-                    self.payments[after_payment_number as usize].payment_date,
-                    time_provider,
-                )?;
-                
-                self.payments.truncate(after_payment_number as usize);
-                self.payments.extend(new_payments);
-                self.term_months = after_payment_number + new_term;
+                let current_emi = self.payments
+                    .get(index)
+                    .unwrap_or(&self.payments[0])
+                    .payment_amount;
+                calculate_term_for_payment(remaining_balance, self.interest_rate, current_emi)
+                    .min(remaining_months)
             }
+        };
+        
+        let calculator = AmortizationCalculator::new(self.amortization_method);
+        let mut new_payments = calculator.calculate_schedule(
+            remaining_balance,
+            self.interest_rate,
+            new_term,
+            from_date,
+            time_provider,
+        )?;
+        for payment in &mut new_payments {
+            payment.payment_number += after_payment_number;
+            payment.cumulative_interest += cumulative_interest;
+            payment.cumulative_principal += cumulative_principal;
         }
         
+        // replace future payments
+        self.payments.truncate(index);
+        self.payments.extend(new_payments);
+        self.term_months = after_payment_number + new_term;
+        
         // recalculate totals
         self.total_interest = self.payments
             .iter()
@@ -403,7 +422,7 @@ fn add_months(date: DateTime<Utc>, months: u32) -> DateTime<Utc> {
     let mut result = date;
     for _ in 0..months {
         let days_in_month = days_in_month(result.year(), result.month());
-        result = result + Duration::days(days_in_month as i64);
+        result += Duration::days(days_in_month as i64);
     }
     result
 }
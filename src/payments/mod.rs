@@ -2,6 +2,8 @@ pub mod amortization;
 pub mod overpayment;
 pub mod waterfall;
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use hourglass_rs::SafeTimeProvider;
 
@@ -10,10 +12,10 @@ use crate::errors::{FacilityError, Result};
 use crate::events::EventStore;
 use crate::types::{FacilityId, OverpaymentStrategy};
 
-pub use amortization::{AmortizationCalculator, AmortizationSchedule, ScheduledPayment};
+pub use amortization::{AmortizationCalculator, AmortizationSchedule, RecalculationStrategy, ScheduledPayment};
 pub use overpayment::{OverpaymentHandler, OverpaymentResult};
 pub use waterfall::{
-    PaymentProcessor, PaymentResult, PaymentWaterfall, WaterfallPriority,
+    PaymentProcessor, PaymentResult, PaymentWaterfall, WaterfallComponent, WaterfallPriority,
 };
 
 /// payment request
@@ -27,7 +29,7 @@ pub struct PaymentRequest {
 }
 
 /// payment context with current balances
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PaymentContext {
     pub facility_id: FacilityId,
     pub accrued_fees: Money,
@@ -37,6 +39,14 @@ pub struct PaymentContext {
     pub minimum_payment: Option<Money>,
     pub payment_due_date: Option<DateTime<Utc>>,
     pub days_overdue: u32,
+    /// accrued fees itemized by kind, a subset of `accrued_fees`
+    pub fee_balances: BTreeMap<String, Money>,
+    /// interest and principal of missed installments
+    pub past_due_amount: Money,
+    /// interest and principal of the installment currently due
+    pub current_installment_due: Money,
+    pub escrow_due: Money,
+    pub insurance_due: Money,
 }
 
 impl PaymentContext {
@@ -78,14 +88,14 @@ pub enum PartialPaymentStrategy {
 }
 
 /// suspense account for holding partial payments
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SuspenseAccount {
     pub facility_id: FacilityId,
     pub balance: Money,
     pub deposits: Vec<SuspenseDeposit>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SuspenseDeposit {
     pub amount: Money,
     pub deposit_date: DateTime<Utc>,
@@ -159,6 +169,7 @@ mod tests {
             minimum_payment: Some(Money::from_major(200)),
             payment_due_date: None,
             days_overdue: 0,
+            ..Default::default()
         };
         
         // test zero payment
@@ -6,7 +6,7 @@ use rust_decimal_macros::dec;
 use crate::decimal::{Money, Rate};
 use crate::errors::Result;
 use crate::events::{Event, EventStore};
-use crate::types::{FacilityId, OverpaymentStrategy};
+use crate::types::{CreditLimitChangeReason, FacilityId, OverpaymentStrategy};
 
 use super::PaymentContext;
 
@@ -76,6 +76,7 @@ impl OverpaymentHandler {
             new_emi: None,
             old_term_months: None,
             new_term_months: None,
+            new_credit_limit: None,
             savings: self.calculate_interest_savings(overpayment, Money::ZERO),
         })
     }
@@ -121,6 +122,7 @@ impl OverpaymentHandler {
             new_emi: Some(new_emi),
             old_term_months: Some(loan_params.remaining_months),
             new_term_months: Some(loan_params.remaining_months),
+            new_credit_limit: None,
             savings: total_savings,
         })
     }
@@ -166,6 +168,7 @@ impl OverpaymentHandler {
             new_emi: Some(loan_params.emi_amount),
             old_term_months: Some(old_term),
             new_term_months: Some(new_term),
+            new_credit_limit: None,
             savings,
         })
     }
@@ -186,6 +189,7 @@ impl OverpaymentHandler {
             facility_id: self.facility_id,
             old_limit,
             new_limit,
+            reason: CreditLimitChangeReason::Overpayment,
             timestamp: time_provider.now(),
         });
         
@@ -198,6 +202,7 @@ impl OverpaymentHandler {
             new_emi: None,
             old_term_months: None,
             new_term_months: None,
+            new_credit_limit: Some(new_limit),
             savings: Money::ZERO,
         })
     }
@@ -229,6 +234,7 @@ pub struct OverpaymentResult {
     pub new_emi: Option<Money>,
     pub old_term_months: Option<u32>,
     pub new_term_months: Option<u32>,
+    pub new_credit_limit: Option<Money>,
     pub savings: Money,
 }
 
@@ -397,6 +403,7 @@ mod tests {
             minimum_payment: None,
             payment_due_date: None,
             days_overdue: 0,
+            ..Default::default()
         };
         
         let loan_params = LoanParameters {
@@ -437,6 +444,7 @@ mod tests {
             minimum_payment: None,
             payment_due_date: None,
             days_overdue: 0,
+            ..Default::default()
         };
         
         let loan_params = LoanParameters {
@@ -476,6 +484,7 @@ mod tests {
             minimum_payment: None,
             payment_due_date: None,
             days_overdue: 0,
+            ..Default::default()
         };
         
         let loan_params = LoanParameters {
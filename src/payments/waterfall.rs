@@ -1,12 +1,15 @@
 use chrono::{DateTime, Utc};
 use hourglass_rs::SafeTimeProvider;
 
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
 use crate::decimal::Money;
-use crate::errors::Result;
+use crate::errors::{FacilityError, Result};
 use crate::events::{Event, EventStore};
 use crate::types::{FacilityId, PaymentApplication};
 
-use super::{PaymentContext, PaymentRequest};
+use super::{OverpaymentResult, PaymentContext, PaymentRequest};
 
 /// waterfall priority levels
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -19,49 +22,102 @@ pub enum WaterfallPriority {
     Sixth = 6,
 }
 
+/// balance a waterfall step pays down
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaterfallComponent {
+    /// all accrued fees
+    Fees,
+    /// accrued fees of one kind only, e.g. "late_fee"
+    FeeType(String),
+    Penalties,
+    Interest,
+    Principal,
+    /// interest then principal of missed installments, ahead of the current one
+    PastDueInstallments,
+    /// interest then principal of the installment currently due
+    CurrentInstallment,
+    Escrow,
+    Insurance,
+}
+
 /// payment waterfall configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaymentWaterfall {
-    pub fees_priority: WaterfallPriority,
-    pub penalties_priority: WaterfallPriority,
-    pub interest_priority: WaterfallPriority,
-    pub principal_priority: WaterfallPriority,
+    /// components in the order payments are applied
+    pub steps: Vec<WaterfallComponent>,
     pub allow_overpayment: bool,
 }
 
 impl PaymentWaterfall {
-    /// standard waterfall: fees -> penalties -> interest -> principal
-    pub fn standard() -> Self {
+    /// waterfall applying payments to `steps` in order
+    pub fn new(steps: Vec<WaterfallComponent>) -> Self {
         Self {
-            fees_priority: WaterfallPriority::First,
-            penalties_priority: WaterfallPriority::Second,
-            interest_priority: WaterfallPriority::Third,
-            principal_priority: WaterfallPriority::Fourth,
+            steps,
             allow_overpayment: true,
         }
     }
     
+    /// standard waterfall: fees -> penalties -> interest -> principal
+    pub fn standard() -> Self {
+        Self::new(vec![
+            WaterfallComponent::Fees,
+            WaterfallComponent::Penalties,
+            WaterfallComponent::Interest,
+            WaterfallComponent::Principal,
+        ])
+    }
+    
     /// interest-first waterfall for certain products
     pub fn interest_first() -> Self {
-        Self {
-            fees_priority: WaterfallPriority::Second,
-            penalties_priority: WaterfallPriority::Third,
-            interest_priority: WaterfallPriority::First,
-            principal_priority: WaterfallPriority::Fourth,
-            allow_overpayment: true,
-        }
+        Self::new(vec![
+            WaterfallComponent::Interest,
+            WaterfallComponent::Fees,
+            WaterfallComponent::Penalties,
+            WaterfallComponent::Principal,
+        ])
     }
     
     /// principal-only waterfall (must have zero other balances)
     pub fn principal_only() -> Self {
         Self {
-            fees_priority: WaterfallPriority::First,
-            penalties_priority: WaterfallPriority::Second,
-            interest_priority: WaterfallPriority::Third,
-            principal_priority: WaterfallPriority::Fourth,
+            steps: vec![WaterfallComponent::Principal],
             allow_overpayment: false,
         }
     }
+    
+    /// installment loan waterfall: arrears and the current installment ahead
+    /// of fees, so a payment covering the installment never leaves it short
+    /// to pay a late fee
+    pub fn installment() -> Self {
+        Self::new(vec![
+            WaterfallComponent::PastDueInstallments,
+            WaterfallComponent::CurrentInstallment,
+            WaterfallComponent::Fees,
+            WaterfallComponent::Penalties,
+            WaterfallComponent::Interest,
+            WaterfallComponent::Principal,
+        ])
+    }
+    
+    /// mortgage waterfall: arrears, then the current installment with escrow and insurance
+    pub fn mortgage() -> Self {
+        Self::new(vec![
+            WaterfallComponent::PastDueInstallments,
+            WaterfallComponent::CurrentInstallment,
+            WaterfallComponent::Escrow,
+            WaterfallComponent::Insurance,
+            WaterfallComponent::Fees,
+            WaterfallComponent::Penalties,
+            WaterfallComponent::Interest,
+            WaterfallComponent::Principal,
+        ])
+    }
+    
+    /// append a step to the end of the waterfall
+    pub fn then(mut self, component: WaterfallComponent) -> Self {
+        self.steps.push(component);
+        self
+    }
 }
 
 /// payment processor
@@ -75,6 +131,8 @@ impl PaymentProcessor {
     }
     
     /// process payment through waterfall
+    ///
+    /// principal-only payments skip the waterfall and the minimum payment check
     pub fn process(
         &self,
         payment: PaymentRequest,
@@ -83,28 +141,26 @@ impl PaymentProcessor {
         events: &mut EventStore,
     ) -> Result<PaymentResult> {
         // validate payment
-        context.validate_payment(payment.amount)?;
+        if payment.is_principal_only {
+            if payment.amount <= Money::ZERO {
+                return Err(FacilityError::InvalidPaymentAmount { amount: payment.amount });
+            }
+        } else {
+            context.validate_payment(payment.amount)?;
+        }
         
         let mut remaining = payment.amount;
-        let mut application = PaymentApplication {
-            to_fees: Money::ZERO,
-            to_penalties: Money::ZERO,
-            to_interest: Money::ZERO,
-            to_principal: Money::ZERO,
-            excess: Money::ZERO,
+        let mut application = PaymentApplication::default();
+        
+        let principal_only = [WaterfallComponent::Principal];
+        let steps = if payment.is_principal_only {
+            &principal_only[..]
+        } else {
+            &self.waterfall.steps[..]
         };
         
-        // create priority order
-        let mut priorities = vec![
-            (self.waterfall.fees_priority, PaymentComponent::Fees),
-            (self.waterfall.penalties_priority, PaymentComponent::Penalties),
-            (self.waterfall.interest_priority, PaymentComponent::Interest),
-            (self.waterfall.principal_priority, PaymentComponent::Principal),
-        ];
-        priorities.sort_by_key(|&(priority, _)| priority);
-        
-        // apply payment in priority order
-        for (_, component) in priorities {
+        // apply payment in waterfall order
+        for component in steps {
             remaining = self.apply_to_component(
                 component,
                 remaining,
@@ -138,44 +194,103 @@ impl PaymentProcessor {
         });
         
         Ok(PaymentResult {
-            payment_id: payment.facility_id,
+            payment_id: Uuid::new_v4(),
             amount_applied: application.total_applied(),
             application,
             remaining_balance: context.total_outstanding(),
             payment_date: payment.payment_date,
+            overpayment: None,
         })
     }
     
     fn apply_to_component(
         &self,
-        component: PaymentComponent,
+        component: &WaterfallComponent,
         available: Money,
         context: &mut PaymentContext,
         application: &mut PaymentApplication,
     ) -> Money {
-        let (balance, applied_field) = match component {
-            PaymentComponent::Fees => (&mut context.accrued_fees, &mut application.to_fees),
-            PaymentComponent::Penalties => (&mut context.accrued_penalties, &mut application.to_penalties),
-            PaymentComponent::Interest => (&mut context.accrued_interest, &mut application.to_interest),
-            PaymentComponent::Principal => (&mut context.outstanding_principal, &mut application.to_principal),
-        };
-        
+        match component {
+            WaterfallComponent::Fees => {
+                let payment = available.min(context.accrued_fees);
+                context.accrued_fees -= payment;
+                application.to_fees += payment;
+                
+                // itemized fees are part of accrued fees, keep them within it
+                let mut excess_items = context.fee_balances.values().fold(Money::ZERO, |acc, x| acc + *x)
+                    - context.accrued_fees;
+                for balance in context.fee_balances.values_mut() {
+                    if excess_items <= Money::ZERO {
+                        break;
+                    }
+                    let reduction = excess_items.min(*balance);
+                    *balance -= reduction;
+                    excess_items -= reduction;
+                }
+                context.fee_balances.retain(|_, balance| !balance.is_zero());
+                
+                available - payment
+            }
+            WaterfallComponent::FeeType(kind) => {
+                let balance = context.fee_balances.get(kind).copied().unwrap_or(Money::ZERO);
+                let payment = available.min(balance).min(context.accrued_fees);
+                if payment.is_zero() {
+                    return available;
+                }
+                context.accrued_fees -= payment;
+                application.to_fees += payment;
+                if balance == payment {
+                    context.fee_balances.remove(kind);
+                } else if let Some(balance) = context.fee_balances.get_mut(kind) {
+                    *balance -= payment;
+                }
+                available - payment
+            }
+            WaterfallComponent::Penalties => {
+                Self::pay(available, &mut context.accrued_penalties, &mut application.to_penalties)
+            }
+            WaterfallComponent::Interest => {
+                Self::pay(available, &mut context.accrued_interest, &mut application.to_interest)
+            }
+            WaterfallComponent::Principal => {
+                Self::pay(available, &mut context.outstanding_principal, &mut application.to_principal)
+            }
+            WaterfallComponent::PastDueInstallments => {
+                let mut budget = available.min(context.past_due_amount);
+                let before = budget;
+                budget = Self::pay(budget, &mut context.accrued_interest, &mut application.to_interest);
+                budget = Self::pay(budget, &mut context.outstanding_principal, &mut application.to_principal);
+                let payment = before - budget;
+                context.past_due_amount -= payment;
+                available - payment
+            }
+            WaterfallComponent::CurrentInstallment => {
+                let mut budget = available.min(context.current_installment_due);
+                let before = budget;
+                budget = Self::pay(budget, &mut context.accrued_interest, &mut application.to_interest);
+                budget = Self::pay(budget, &mut context.outstanding_principal, &mut application.to_principal);
+                let payment = before - budget;
+                context.current_installment_due -= payment;
+                available - payment
+            }
+            WaterfallComponent::Escrow => {
+                Self::pay(available, &mut context.escrow_due, &mut application.to_escrow)
+            }
+            WaterfallComponent::Insurance => {
+                Self::pay(available, &mut context.insurance_due, &mut application.to_insurance)
+            }
+        }
+    }
+    
+    /// pay down one balance, returning what is left of the payment
+    fn pay(available: Money, balance: &mut Money, applied: &mut Money) -> Money {
         let payment = available.min(*balance);
         *balance -= payment;
-        *applied_field = payment;
-        
+        *applied += payment;
         available - payment
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum PaymentComponent {
-    Fees,
-    Penalties,
-    Interest,
-    Principal,
-}
-
 /// payment result
 #[derive(Debug, Clone, PartialEq)]
 pub struct PaymentResult {
@@ -184,6 +299,8 @@ pub struct PaymentResult {
     pub application: PaymentApplication,
     pub remaining_balance: Money,
     pub payment_date: DateTime<Utc>,
+    /// how principal paid beyond the amount due was handled
+    pub overpayment: Option<OverpaymentResult>,
 }
 
 /// facility-specific waterfall configurations
@@ -196,6 +313,12 @@ pub mod facility_waterfalls {
         balance_priorities: Vec<(String, WaterfallPriority)>,
     }
     
+    impl Default for CreditCardWaterfall {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+    
     impl CreditCardWaterfall {
         pub fn new() -> Self {
             Self {
@@ -325,6 +448,7 @@ mod tests {
             minimum_payment: None,
             payment_due_date: None,
             days_overdue: 0,
+            ..Default::default()
         }
     }
     
@@ -438,4 +562,108 @@ mod tests {
         assert_eq!(application.to_interest, Money::from_major(100));
         assert_eq!(application.to_principal, Money::from_major(250));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_configurable_waterfall_buckets() {
+        let waterfall = PaymentWaterfall::new(vec![
+            WaterfallComponent::PastDueInstallments,
+            WaterfallComponent::FeeType("late_fee".to_string()),
+            WaterfallComponent::Escrow,
+            WaterfallComponent::Insurance,
+            WaterfallComponent::Interest,
+            WaterfallComponent::Fees,
+            WaterfallComponent::Principal,
+        ]);
+        
+        // product teams define waterfalls in config
+        let json = serde_json::to_string(&waterfall).unwrap();
+        let waterfall: PaymentWaterfall = serde_json::from_str(&json).unwrap();
+        
+        let processor = PaymentProcessor::new(waterfall);
+        let mut context = create_test_context();
+        context.accrued_penalties = Money::ZERO;
+        context.fee_balances.insert("late_fee".to_string(), Money::from_major(30));
+        context.past_due_amount = Money::from_major(150);
+        context.escrow_due = Money::from_major(200);
+        context.insurance_due = Money::from_major(40);
+        let mut events = EventStore::new();
+        
+        let time = SafeTimeProvider::new(TimeSource::Test(Utc::now()));
+        
+        let payment = PaymentRequest {
+            facility_id: context.facility_id,
+            amount: Money::from_major(500),
+            payment_date: time.now(),
+            reference: "test".to_string(),
+            is_principal_only: false,
+        };
+        
+        let result = processor.process(payment, &mut context, &time, &mut events).unwrap();
+        
+        // past due: $100 interest + $50 principal, then $30 late fee, $200 escrow,
+        // $40 insurance, $20 remaining fees and $60 principal
+        assert_eq!(result.application.to_interest, Money::from_major(100));
+        assert_eq!(result.application.to_fees, Money::from_major(50));
+        assert_eq!(result.application.to_escrow, Money::from_major(200));
+        assert_eq!(result.application.to_insurance, Money::from_major(40));
+        assert_eq!(result.application.to_principal, Money::from_major(110));
+        assert_eq!(context.past_due_amount, Money::ZERO);
+        assert!(context.fee_balances.is_empty());
+        assert_eq!(context.outstanding_principal, Money::from_major(890));
+    }
+    
+    #[test]
+    fn test_installment_paid_ahead_of_late_fee() {
+        let processor = PaymentProcessor::new(PaymentWaterfall::installment());
+        let mut context = create_test_context();
+        context.accrued_penalties = Money::ZERO;
+        context.current_installment_due = Money::from_major(300);
+        let mut events = EventStore::new();
+        
+        let time = SafeTimeProvider::new(TimeSource::Test(Utc::now()));
+        
+        let payment = PaymentRequest {
+            facility_id: context.facility_id,
+            amount: Money::from_major(320),
+            payment_date: time.now(),
+            reference: "test".to_string(),
+            is_principal_only: false,
+        };
+        
+        let result = processor.process(payment, &mut context, &time, &mut events).unwrap();
+        
+        // $100 interest and $200 principal cover the installment, the
+        // remaining $20 goes to the $50 fee
+        assert_eq!(result.application.to_interest, Money::from_major(100));
+        assert_eq!(result.application.to_principal, Money::from_major(200));
+        assert_eq!(result.application.to_fees, Money::from_major(20));
+        assert_eq!(context.current_installment_due, Money::ZERO);
+        assert_eq!(context.accrued_fees, Money::from_major(30));
+    }
+    
+    #[test]
+    fn test_principal_only_payment() {
+        let processor = PaymentProcessor::new(PaymentWaterfall::standard());
+        let mut context = create_test_context();
+        context.minimum_payment = Some(Money::from_major(500));
+        let mut events = EventStore::new();
+        
+        let time = SafeTimeProvider::new(TimeSource::Test(Utc::now()));
+        
+        let payment = PaymentRequest {
+            facility_id: context.facility_id,
+            amount: Money::from_major(300),
+            payment_date: time.now(),
+            reference: "test".to_string(),
+            is_principal_only: true,
+        };
+        
+        let result = processor.process(payment, &mut context, &time, &mut events).unwrap();
+        
+        // skips fees, penalties, interest and the minimum payment check
+        assert_eq!(result.application.to_principal, Money::from_major(300));
+        assert_eq!(result.application.to_fees, Money::ZERO);
+        assert_eq!(context.accrued_interest, Money::from_major(100));
+        assert_eq!(context.outstanding_principal, Money::from_major(700));
+    }
+}
@@ -1,10 +1,12 @@
-use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::decimal::{Money, Rate};
 use crate::types::{
-    CollateralPosition, FacilityId, FacilityStatus,
+    BalanceSegment, CollateralPosition, DelinquencyBucket, FacilityId, FacilityStatus,
 };
 
 /// facility state
@@ -21,10 +23,17 @@ pub struct FacilityState {
     pub accrued_interest: Money,
     pub accrued_fees: Money,
     pub accrued_penalties: Money,
+    /// accrued fees itemized by kind, a subset of `accrued_fees`
+    pub fee_balances: BTreeMap<String, Money>,
+    /// unpaid amount of installments past their due date
+    pub arrears_amount: Money,
+    pub escrow_due: Money,
+    pub insurance_due: Money,
     
     // disbursement tracking
     pub total_disbursed: Money,
     pub available_commitment: Money,
+    pub transaction_usage: TransactionUsage,
     
     // payment tracking
     pub total_payments_received: Money,
@@ -36,6 +45,8 @@ pub struct FacilityState {
     
     // interest tracking
     pub last_interest_accrual: DateTime<Utc>,
+    /// penalty interest has been charged up to here
+    pub last_penalty_accrual: Option<DateTime<Utc>>,
     pub total_interest_paid: Money,
     pub capitalized_interest: Money,
     
@@ -55,6 +66,14 @@ pub struct FacilityState {
     pub days_past_due: u32,
     pub payment_count: u32,
     pub missed_payment_count: u32,
+    /// contractual installments, oldest first; delinquency is measured from these when present
+    pub installments: Vec<Installment>,
+    pub installments_past_due: u32,
+    pub delinquency_bucket: DelinquencyBucket,
+    /// interest income is not recognised while seriously delinquent
+    pub non_accrual: bool,
+    /// interest accrued on non-accrual, still owed but held out of income
+    pub suspended_interest: Money,
     
     // facility-specific state
     pub facility_specific: FacilitySpecificState,
@@ -71,6 +90,98 @@ pub struct FacilityState {
     pub recovery_amount: Option<Money>,
 }
 
+/// payment status of a contractual installment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InstallmentStatus {
+    Due,
+    PartiallyPaid,
+    Paid,
+}
+
+/// scheduled installment tracked for delinquency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Installment {
+    pub number: u32,
+    pub due_date: DateTime<Utc>,
+    pub amount: Money,
+    pub paid: Money,
+    pub status: InstallmentStatus,
+    pub paid_date: Option<DateTime<Utc>>,
+    /// a late fee has been assessed on this installment
+    pub late_fee_charged: bool,
+}
+
+impl Installment {
+    pub fn new(number: u32, due_date: DateTime<Utc>, amount: Money) -> Self {
+        Self {
+            number,
+            due_date,
+            amount,
+            paid: Money::ZERO,
+            status: InstallmentStatus::Due,
+            paid_date: None,
+            late_fee_charged: false,
+        }
+    }
+    
+    /// amount still owed on this installment
+    pub fn outstanding(&self) -> Money {
+        (self.amount - self.paid).max(Money::ZERO)
+    }
+    
+    /// unpaid and past its due date
+    pub fn is_past_due(&self, now: DateTime<Utc>) -> bool {
+        self.status != InstallmentStatus::Paid && self.due_date < now
+    }
+    
+    /// apply up to `amount`, returning what is left over
+    fn apply(&mut self, amount: Money, now: DateTime<Utc>) -> Money {
+        let payment = amount.min(self.outstanding());
+        self.paid += payment;
+        if self.outstanding().is_zero() {
+            self.status = InstallmentStatus::Paid;
+            self.paid_date = Some(now);
+        } else if self.paid > Money::ZERO {
+            self.status = InstallmentStatus::PartiallyPaid;
+        }
+        amount - payment
+    }
+}
+
+/// drawdowns in the current calendar month, used for daily and monthly limits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TransactionUsage {
+    pub drawdowns: Vec<(DateTime<Utc>, Money)>,
+}
+
+impl TransactionUsage {
+    /// total drawn on the calendar day of `now`
+    pub fn daily_total(&self, now: DateTime<Utc>) -> Money {
+        self.drawdowns
+            .iter()
+            .filter(|(date, _)| date.date_naive() == now.date_naive())
+            .fold(Money::ZERO, |acc, (_, amount)| acc + *amount)
+    }
+    
+    /// total drawn in the calendar month of `now`
+    pub fn monthly_total(&self, now: DateTime<Utc>) -> Money {
+        self.drawdowns
+            .iter()
+            .filter(|(date, _)| same_month(*date, now))
+            .fold(Money::ZERO, |acc, (_, amount)| acc + *amount)
+    }
+    
+    /// record a drawdown, dropping usage from earlier months
+    pub fn record(&mut self, amount: Money, timestamp: DateTime<Utc>) {
+        self.drawdowns.retain(|(date, _)| same_month(*date, timestamp));
+        self.drawdowns.push((timestamp, amount));
+    }
+}
+
+fn same_month(a: DateTime<Utc>, b: DateTime<Utc>) -> bool {
+    a.year() == b.year() && a.month() == b.month()
+}
+
 /// facility-specific state
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FacilitySpecificState {
@@ -92,7 +203,9 @@ pub enum FacilitySpecificState {
         available_credit: Money,
         cash_advance_balance: Money,
         purchase_balance: Money,
+        balance_transfer_balance: Money,
         promotional_balance: Money,
+        penalty_balance: Money,
         overlimit_amount: Money,
         statement_balance: Money,
         statement_date: Option<DateTime<Utc>>,
@@ -139,7 +252,9 @@ impl FacilityState {
                     available_credit: limit,
                     cash_advance_balance: Money::ZERO,
                     purchase_balance: Money::ZERO,
+                    balance_transfer_balance: Money::ZERO,
                     promotional_balance: Money::ZERO,
+                    penalty_balance: Money::ZERO,
                     overlimit_amount: Money::ZERO,
                     statement_balance: Money::ZERO,
                     statement_date: None,
@@ -164,8 +279,13 @@ impl FacilityState {
             accrued_interest: Money::ZERO,
             accrued_fees: Money::ZERO,
             accrued_penalties: Money::ZERO,
+            fee_balances: BTreeMap::new(),
+            arrears_amount: Money::ZERO,
+            escrow_due: Money::ZERO,
+            insurance_due: Money::ZERO,
             total_disbursed: Money::ZERO,
             available_commitment: commitment,
+            transaction_usage: TransactionUsage::default(),
             total_payments_received: Money::ZERO,
             last_payment_amount: None,
             last_payment_date: None,
@@ -173,6 +293,7 @@ impl FacilityState {
             next_payment_amount: None,
             minimum_payment_due: None,
             last_interest_accrual: origination_date,
+            last_penalty_accrual: None,
             total_interest_paid: Money::ZERO,
             capitalized_interest: Money::ZERO,
             total_fees_charged: Money::ZERO,
@@ -186,6 +307,11 @@ impl FacilityState {
             days_past_due: 0,
             payment_count: 0,
             missed_payment_count: 0,
+            installments: Vec::new(),
+            installments_past_due: 0,
+            delinquency_bucket: DelinquencyBucket::Current,
+            non_accrual: false,
+            suspended_interest: Money::ZERO,
             facility_specific,
             collateral: None,
             suspense_balance: Money::ZERO,
@@ -206,13 +332,63 @@ impl FacilityState {
             FacilitySpecificState::Revolving { 
                 cash_advance_balance,
                 purchase_balance,
+                balance_transfer_balance,
                 promotional_balance,
+                penalty_balance,
                 ..
-            } => *cash_advance_balance + *purchase_balance + *promotional_balance,
+            } => {
+                *cash_advance_balance
+                    + *purchase_balance
+                    + *balance_transfer_balance
+                    + *promotional_balance
+                    + *penalty_balance
+            }
             _ => self.outstanding_principal,
         }
     }
     
+    /// get principal balance of a revolving segment
+    pub fn segment_balance(&self, segment: BalanceSegment) -> Money {
+        match &self.facility_specific {
+            FacilitySpecificState::Revolving {
+                cash_advance_balance,
+                purchase_balance,
+                balance_transfer_balance,
+                promotional_balance,
+                penalty_balance,
+                ..
+            } => match segment {
+                BalanceSegment::Purchase => *purchase_balance,
+                BalanceSegment::CashAdvance => *cash_advance_balance,
+                BalanceSegment::BalanceTransfer => *balance_transfer_balance,
+                BalanceSegment::Promotional => *promotional_balance,
+                BalanceSegment::Penalty => *penalty_balance,
+            },
+            _ => Money::ZERO,
+        }
+    }
+    
+    /// get mutable principal balance of a revolving segment
+    pub fn segment_balance_mut(&mut self, segment: BalanceSegment) -> Option<&mut Money> {
+        match &mut self.facility_specific {
+            FacilitySpecificState::Revolving {
+                cash_advance_balance,
+                purchase_balance,
+                balance_transfer_balance,
+                promotional_balance,
+                penalty_balance,
+                ..
+            } => Some(match segment {
+                BalanceSegment::Purchase => purchase_balance,
+                BalanceSegment::CashAdvance => cash_advance_balance,
+                BalanceSegment::BalanceTransfer => balance_transfer_balance,
+                BalanceSegment::Promotional => promotional_balance,
+                BalanceSegment::Penalty => penalty_balance,
+            }),
+            _ => None,
+        }
+    }
+    
     /// check if facility is performing
     pub fn is_performing(&self) -> bool {
         matches!(
@@ -255,14 +431,76 @@ impl FacilityState {
         self.last_payment_date = Some(timestamp);
         self.payment_count += 1;
         
-        // reset DPD if payment sufficient
+        // reset DPD if payment sufficient, installments track their own
         if let Some(minimum) = self.minimum_payment_due {
-            if amount >= minimum {
+            if amount >= minimum && self.installments.is_empty() {
                 self.days_past_due = 0;
             }
         }
     }
     
+    /// replace the installments after `after_number` with a new schedule
+    pub fn replace_installments_after(&mut self, after_number: u32, installments: Vec<Installment>) {
+        self.installments.retain(|i| i.number <= after_number);
+        self.installments.extend(installments);
+    }
+    
+    /// apply a payment to the oldest unpaid installments, returning any amount left over
+    pub fn allocate_to_installments(&mut self, amount: Money, now: DateTime<Utc>) -> Money {
+        let mut remaining = amount;
+        for installment in self.installments.iter_mut().filter(|i| i.status != InstallmentStatus::Paid) {
+            if remaining.is_zero() {
+                break;
+            }
+            remaining = installment.apply(remaining, now);
+        }
+        remaining
+    }
+    
+    /// number of installments paid in order from the first
+    pub fn installments_paid(&self) -> u32 {
+        self.installments
+            .iter()
+            .take_while(|i| i.status == InstallmentStatus::Paid)
+            .count() as u32
+    }
+    
+    /// oldest installment that is unpaid past its due date
+    pub fn oldest_past_due_installment(&self, now: DateTime<Utc>) -> Option<&Installment> {
+        self.installments.iter().find(|i| i.is_past_due(now))
+    }
+    
+    /// recompute arrears, days past due and the amounts due from the installments
+    pub fn refresh_installments(&mut self, now: DateTime<Utc>) {
+        if self.installments.is_empty() {
+            return;
+        }
+        
+        let past_due: Vec<&Installment> = self.installments.iter().filter(|i| i.is_past_due(now)).collect();
+        let arrears = past_due.iter().fold(Money::ZERO, |acc, i| acc + i.outstanding());
+        let installments_past_due = past_due.len() as u32;
+        let days_past_due = past_due.first().map_or(0, |i| (now - i.due_date).num_days().max(0) as u32);
+        let oldest_unpaid = self.installments.iter().find(|i| i.status != InstallmentStatus::Paid);
+        let upcoming = self.installments
+            .iter()
+            .find(|i| i.status != InstallmentStatus::Paid && i.due_date >= now);
+        
+        self.next_payment_due = oldest_unpaid.map(|i| i.due_date);
+        self.next_payment_amount = upcoming.map(|i| i.amount);
+        self.minimum_payment_due = oldest_unpaid
+            .map(|_| arrears + upcoming.map_or(Money::ZERO, |i| i.outstanding()));
+        self.arrears_amount = arrears;
+        self.installments_past_due = installments_past_due;
+        self.days_past_due = days_past_due;
+    }
+    
+    /// charge a fee, itemized under `kind` so waterfalls can target it
+    pub fn charge_fee(&mut self, kind: &str, amount: Money) {
+        self.accrued_fees += amount;
+        self.total_fees_charged += amount;
+        *self.fee_balances.entry(kind.to_string()).or_insert(Money::ZERO) += amount;
+    }
+    
     /// record disbursement
     pub fn record_disbursement(&mut self, amount: Money) {
         self.total_disbursed += amount;
@@ -33,6 +33,100 @@ pub enum RevolvingType {
     HELOC,
 }
 
+/// balance segment within a revolving facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BalanceSegment {
+    Purchase,
+    CashAdvance,
+    BalanceTransfer,
+    Promotional,
+    /// new transactions booked while a penalty apr is active
+    Penalty,
+}
+
+/// type of draw on a revolving facility
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DrawType {
+    Purchase,
+    CashAdvance,
+    BalanceTransfer,
+    ForeignPurchase,
+    ForeignCashAdvance,
+}
+
+impl DrawType {
+    /// segment the draw is booked to
+    pub fn segment(&self) -> BalanceSegment {
+        match self {
+            DrawType::Purchase | DrawType::ForeignPurchase => BalanceSegment::Purchase,
+            DrawType::CashAdvance | DrawType::ForeignCashAdvance => BalanceSegment::CashAdvance,
+            DrawType::BalanceTransfer => BalanceSegment::BalanceTransfer,
+        }
+    }
+    
+    /// check if the draw is made in a foreign currency or country
+    pub fn is_foreign(&self) -> bool {
+        matches!(self, DrawType::ForeignPurchase | DrawType::ForeignCashAdvance)
+    }
+}
+
+/// kind of per-transaction fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionFeeKind {
+    CashAdvance,
+    BalanceTransfer,
+    ForeignTransaction,
+}
+
+impl TransactionFeeKind {
+    /// key the fee is itemized under in the facility's fee balances
+    pub fn fee_kind(&self) -> &'static str {
+        match self {
+            TransactionFeeKind::CashAdvance => "cash_advance_fee",
+            TransactionFeeKind::BalanceTransfer => "balance_transfer_fee",
+            TransactionFeeKind::ForeignTransaction => "foreign_transaction_fee",
+        }
+    }
+}
+
+/// kind of overdraft fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverdraftFeeKind {
+    PaidItem,
+    ReturnedItem,
+    Daily,
+    Sustained,
+}
+
+/// why an overdrawing item was returned unpaid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverdraftReturnReason {
+    LimitExceeded,
+    NotOptedIn,
+    ItemTooLarge,
+    Suspended,
+}
+
+/// reason code for a credit limit change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CreditLimitChangeReason {
+    Manual,
+    /// on-time payment streak with steady use of the line
+    PaymentHistory,
+    Delinquency,
+    Overlimit,
+    /// line left largely unused over the review window
+    LowUtilization,
+    /// utilization climbing sharply over the review window
+    RisingUtilization,
+    /// overpayment applied as a limit reduction
+    Overpayment,
+    /// overdraft relied on continuously since the last review
+    PersistentUse,
+    /// too little deposited into the account since the last review
+    LowDeposits,
+}
+
 /// facility status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FacilityStatus {
@@ -56,6 +150,31 @@ pub enum FacilityStatus {
     ChargedOff,
 }
 
+/// delinquency bucket by days past due, ordered by severity. named for the
+/// standard 30 day cycles; `DelinquencyPolicy::bucket_starts` sets where each
+/// one starts for a product
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DelinquencyBucket {
+    #[default]
+    Current,
+    Days1To29,
+    Days30To59,
+    Days60To89,
+    Days90To119,
+    Days120Plus,
+}
+
+impl DelinquencyBucket {
+    /// past due buckets in severity order
+    pub const PAST_DUE: [DelinquencyBucket; 5] = [
+        DelinquencyBucket::Days1To29,
+        DelinquencyBucket::Days30To59,
+        DelinquencyBucket::Days60To89,
+        DelinquencyBucket::Days90To119,
+        DelinquencyBucket::Days120Plus,
+    ];
+}
+
 /// amortization method for term loans
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AmortizationMethod {
@@ -134,12 +253,15 @@ pub struct PaymentApplication {
     pub to_penalties: Money,
     pub to_interest: Money,
     pub to_principal: Money,
+    pub to_escrow: Money,
+    pub to_insurance: Money,
     pub excess: Money,
 }
 
 impl PaymentApplication {
     pub fn total_applied(&self) -> Money {
         self.to_fees + self.to_penalties + self.to_interest + self.to_principal
+            + self.to_escrow + self.to_insurance
     }
 }
 